@@ -0,0 +1,160 @@
+use lyon::math::{Angle, Transform};
+
+use crate::{
+    node::Bounds,
+    scene::SceneTask,
+    signal::{create_signal, lerp, Executor, Lerp, NSignal, RawSignal, Signal},
+};
+
+/// `CameraView::default()` shows the scene like there is no camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraView {
+    /// How far the center of the screen is moved in pixels
+    pub position: [f32; 2],
+    /// `2` shows everything two times bigger
+    pub zoom: f32,
+    /// In radians counter clockwise, the scene turns the other way
+    pub rotation: f32,
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        Self {
+            position: [0.; 2],
+            zoom: 1.,
+            rotation: 0.,
+        }
+    }
+}
+
+impl CameraView {
+    /// From the root group to the screen in pixels, `center` is the center of the screen
+    /// and `up` is the one of `World`
+    pub(crate) fn transform(&self, center: [f32; 2], up: f32) -> Transform {
+        let [cx, cy] = center;
+        let [x, y] = self.position;
+        Transform::translation(-cx - x, -cy - y)
+            .then_rotate(Angle::radians(-self.rotation * up))
+            .then_scale(self.zoom, self.zoom)
+            .then_translate([cx, cy].into())
+    }
+}
+
+pub struct Camera<'a> {
+    scene: &'a SceneTask,
+
+    pub position: Signal<'a, [f32; 2]>,
+    pub zoom: Signal<'a, f32>,
+    pub rotation: Signal<'a, f32>,
+}
+
+impl<'a> Camera<'a> {
+    pub(crate) fn new(view: CameraView, raw: RawCamera, scene: &'a SceneTask) -> Self {
+        Self {
+            scene,
+            position: Signal::new(raw.position, scene, view.position),
+            zoom: Signal::new(raw.zoom, scene, view.zoom),
+            rotation: Signal::new(raw.rotation, scene, view.rotation),
+        }
+    }
+
+    /// Moves and zooms until the bounds of the node with `padding` pixels around it fill the screen
+    /// the bounds are mapped through the groups of the node, the rotation of the camera is not changed
+    /// and a group without nodes does not move the camera
+    pub fn focus_on(&mut self, node: &impl Bounds, padding: f32, time: f64) -> Executor<'_> {
+        let bounds = node.bounds();
+        let (width, height, center) = {
+            let info = self.scene.info.try_read().unwrap();
+            let (width, height) = (info.width.get() as f32, info.height.get() as f32);
+            (width, height, info.origin.center(width, height))
+        };
+
+        let from_position = *self.position.value();
+        let from_zoom = *self.zoom.value();
+
+        let scene = self.scene;
+        let (position_signal, zoom_signal) = (&mut self.position, &mut self.zoom);
+        let mut sum = 0.;
+        Executor::new(move || Box::pin(scene.present(1))).add(move |send| {
+            Box::pin(async move {
+                let Some([[min_x, min_y], [max_x, max_y]]) = scene.world_bounds(bounds).await
+                else {
+                    return;
+                };
+                let position = [
+                    (min_x + max_x) / 2. - center[0],
+                    (min_y + max_y) / 2. - center[1],
+                ];
+                let zoom = (width / (max_x - min_x + 2. * padding))
+                    .min(height / (max_y - min_y + 2. * padding))
+                    .abs();
+                let zoom = if zoom.is_finite() && zoom > 0. {
+                    zoom
+                } else {
+                    from_zoom
+                };
+
+                while sum < 1. {
+                    sum += scene.delta() / time;
+                    let time = sum.min(1.);
+                    position_signal
+                        .set(from_position.lerp(&position, time))
+                        .await;
+                    // exponential, so the zoom looks like it has the same speed all the time
+                    let zoom = if from_zoom > 0. {
+                        from_zoom * (zoom / from_zoom).powf(time as f32)
+                    } else {
+                        lerp(from_zoom, zoom, time)
+                    };
+                    zoom_signal.set(zoom).await;
+                    send.send(()).await.unwrap();
+                }
+            })
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct NCamera {
+    position: NSignal<[f32; 2]>,
+    zoom: NSignal<f32>,
+    rotation: NSignal<f32>,
+}
+
+pub struct RawCamera {
+    position: RawSignal<[f32; 2]>,
+    zoom: RawSignal<f32>,
+    rotation: RawSignal<f32>,
+}
+
+impl NCamera {
+    pub(crate) fn create() -> (NCamera, RawCamera) {
+        let (nposition, position) = create_signal();
+        let (nzoom, zoom) = create_signal();
+        let (nrotation, rotation) = create_signal();
+
+        let camera = NCamera {
+            position,
+            zoom,
+            rotation,
+        };
+        let raw = RawCamera {
+            position: nposition,
+            zoom: nzoom,
+            rotation: nrotation,
+        };
+        (camera, raw)
+    }
+
+    pub(crate) fn update(&mut self, view: &mut CameraView) {
+        if let Some(position) = self.position.get() {
+            view.position = position;
+        }
+        if let Some(zoom) = self.zoom.get() {
+            view.zoom = zoom;
+        }
+        if let Some(rotation) = self.rotation.get() {
+            view.rotation = rotation;
+        }
+    }
+}
@@ -0,0 +1,473 @@
+use crate::{
+    color::Color,
+    fill::{Fill, FillRamp, FILL_GLSL},
+    gcx::{
+        blend::BlendMode,
+        buffer::{BufferType, BufferUsage},
+        shader::Shader,
+        vertex_array::{Field, Fields, VertexArray},
+        PrimitiveType, GCX,
+    },
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    node::{centered, Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    shadow::{Glow, Shadow},
+    signal::{create_signal, NSignal, RawSignal, Signal},
+};
+
+#[derive(Debug)]
+pub struct CircleBuilder {
+    pub(super) radius: f32,
+    pub(super) fill: Fill,
+    pub(super) position: [f32; 2],
+    pub(super) stroke_width: f32,
+    pub(super) stroke_color: Color,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) shadow: Shadow,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl CircleBuilder {
+    pub fn new(radius: f32, fill: impl Into<Fill>) -> Self {
+        Self {
+            radius,
+            fill: fill.into(),
+            position: [0.; 2],
+            stroke_width: 0.,
+            stroke_color: Color::TRANSPARENT,
+            blend: BlendMode::Normal,
+            z_index: 0,
+            shadow: Shadow::default(),
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    /// The center, the gradient points are relative to it
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// A ring centered on the edge, drawn above the fill
+    pub fn with_stroke(mut self, width: f32, color: impl Into<Color>) -> Self {
+        self.stroke_width = width;
+        self.stroke_color = color.into();
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// A shadow behind the node, `offset` and `blur` are in pixels of the screen
+    pub fn with_shadow(mut self, color: impl Into<Color>, offset: [f32; 2], blur: f32) -> Self {
+        self.shadow.color = color.into();
+        self.shadow.offset = offset;
+        self.shadow.blur = blur;
+        self
+    }
+
+    /// A glow around the node, `radius` is in pixels of the screen
+    pub fn with_glow(mut self, color: impl Into<Color>, radius: f32) -> Self {
+        self.shadow.glow = Glow {
+            color: color.into(),
+            radius,
+        };
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+pub struct Circle<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+
+    pub position: Signal<'a, [f32; 2]>,
+    pub radius: Signal<'a, f32>,
+    pub fill: Signal<'a, Fill>,
+    pub stroke_width: Signal<'a, f32>,
+    pub stroke_color: Signal<'a, Color>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+    pub shadow_color: Signal<'a, Color>,
+    pub shadow_offset: Signal<'a, [f32; 2]>,
+    pub shadow_blur: Signal<'a, f32>,
+    pub glow: Signal<'a, Glow>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Circle<'a> {
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+}
+
+impl<'a> Bounds for Circle<'a> {
+    fn bounds(&self) -> NodeBounds {
+        let radius = outer_radius(
+            *self.radius.value(),
+            *self.stroke_width.value(),
+            *self.stroke_color.value(),
+        );
+        NodeBounds::Local(self.parent, centered(*self.position.value(), [radius; 2]))
+    }
+}
+
+/// The stroke is centered on the edge, so half of it is outside
+fn outer_radius(radius: f32, stroke_width: f32, stroke_color: Color) -> f32 {
+    if stroke_color.a > 0. {
+        radius + stroke_width / 2.
+    } else {
+        radius
+    }
+}
+
+impl<'a> Drop for Circle<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Circle when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for CircleBuilder {
+    type Node<'a> = Circle<'a>;
+    type NodeManager = CircleNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawCircle,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        Circle {
+            scene,
+            parent,
+            dropped: false,
+            position: Signal::new(raw.position, scene, self.position),
+            radius: Signal::new(raw.radius, scene, self.radius),
+            fill: Signal::new(raw.fill, scene, self.fill.clone()),
+            stroke_width: Signal::new(raw.stroke_width, scene, self.stroke_width),
+            stroke_color: Signal::new(raw.stroke_color, scene, self.stroke_color),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            shadow_color: Signal::new(raw.shadow_color, scene, self.shadow.color),
+            shadow_offset: Signal::new(raw.shadow_offset, scene, self.shadow.offset),
+            shadow_blur: Signal::new(raw.shadow_blur, scene, self.shadow.blur),
+            glow: Signal::new(raw.glow, scene, self.shadow.glow),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+pub struct NCircle {
+    ramp: FillRamp,
+    parent: GroupId,
+    builder: CircleBuilder,
+    inner: NCircleInner,
+}
+
+pub struct NCircleInner {
+    drop: NSignal<()>,
+    position: NSignal<[f32; 2]>,
+    radius: NSignal<f32>,
+    fill: NSignal<Fill>,
+    stroke_width: NSignal<f32>,
+    stroke_color: NSignal<Color>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+    shadow_color: NSignal<Color>,
+    shadow_offset: NSignal<[f32; 2]>,
+    shadow_blur: NSignal<f32>,
+    glow: NSignal<Glow>,
+}
+
+pub struct RawCircle {
+    drop: RawSignal<()>,
+    position: RawSignal<[f32; 2]>,
+    radius: RawSignal<f32>,
+    fill: RawSignal<Fill>,
+    stroke_width: RawSignal<f32>,
+    stroke_color: RawSignal<Color>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+    shadow_color: RawSignal<Color>,
+    shadow_offset: RawSignal<[f32; 2]>,
+    shadow_blur: RawSignal<f32>,
+    glow: RawSignal<Glow>,
+}
+
+#[derive(Default)]
+pub struct CircleNodeManager {
+    pub(super) circles: Vec<NCircle>,
+    pub(super) shader: Option<Shader>,
+    /// A square from `-1` to `1` that is scaled to every circle
+    quad: Option<VertexArray>,
+
+    pending: Option<NCircleInner>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CircleVertex {
+    corner: [f32; 2],
+}
+
+impl Fields for CircleVertex {
+    fn fields() -> Vec<Field> {
+        vec![Field::new::<[f32; 2]>("corner")]
+    }
+}
+
+impl NodeManager for CircleNodeManager {
+    type NodeBuilder = CircleBuilder;
+    type RawNode = RawCircle;
+
+    fn init(&mut self, gcx: &GCX) {
+        let shader = gcx
+            .create_shader()
+            .vertex(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                layout(location = 0) in vec2 corner;
+
+                uniform vec2 Position;
+                // the half size of the square around the circle
+                uniform float Extent;
+                uniform mat3 World;
+
+                out vec2 Local;
+
+                void main(){
+                    Local = corner * Extent;
+                    gl_Position = vec4((World * vec3(Position + Local, 1.0)).xy, 0.0, 1.0);
+                }
+            "#,
+            )
+            .fragment(format!(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                {FILL_GLSL}
+
+                uniform float Radius;
+                uniform float StrokeWidth;
+                uniform vec4 StrokeColor;
+                uniform float WorldOpacity;
+
+                in vec2 Local;
+                out vec4 color;
+                void main(){{
+                    float dist = length(Local);
+                    // one pixel of the screen, for the smooth edge
+                    float pixel = max(fwidth(dist), 0.0001);
+                    float inside = clamp((Radius - dist) / pixel + 0.5, 0.0, 1.0);
+                    float ring = StrokeWidth > 0.0
+                        ? clamp((StrokeWidth * 0.5 - abs(dist - Radius)) / pixel + 0.5, 0.0, 1.0)
+                        : 0.0;
+                    vec4 stroke = vec4(StrokeColor.rgb * StrokeColor.a, StrokeColor.a) * ring;
+                    color = (stroke + fill(Local) * inside * (1.0 - stroke.a)) * WorldOpacity;
+                    // the square would be the shape of a stencil mask
+                    if (color.a <= 0.0) {{
+                        discard;
+                    }}
+                }}
+                "#,
+            ))
+            .build(gcx)
+            .unwrap();
+
+        let buffer = gcx.create_buffer(
+            BufferType::ArrayBuffer,
+            &[[-1., -1.], [1., -1.], [-1., 1.], [1., 1.]].map(|corner| CircleVertex { corner }),
+            BufferUsage::DRAW_STATIC,
+        );
+        self.quad = Some(gcx.create_vertex_array::<CircleVertex>(buffer).build(gcx));
+        self.shader.replace(shader);
+    }
+
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
+        self.circles.push(NCircle {
+            ramp: FillRamp::new(gcx, &builder.fill),
+            parent,
+            builder,
+            inner: self.pending.take().unwrap(),
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.circles
+            .iter()
+            .map(|circle| DrawItem {
+                z_index: circle.builder.z_index,
+                parent: circle.parent,
+                mask: circle.builder.mask,
+                mask_source: circle.builder.mask_source,
+                shadow: Some(circle.builder.shadow).filter(Shadow::is_visible),
+            })
+            .collect()
+    }
+
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.circles
+            .iter()
+            .map(|circle| {
+                let builder = &circle.builder;
+                let radius =
+                    outer_radius(builder.radius, builder.stroke_width, builder.stroke_color);
+                centered(builder.position, [radius; 2])
+            })
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let (Some(shader), Some(quad)) = (&self.shader, &self.quad) else {
+            panic!()
+        };
+        gcx.use_shader(shader, |gcx| {
+            for circle in items.iter().map(|&i| &self.circles[i]) {
+                let builder = &circle.builder;
+                let [x, y] = builder.position;
+                let stroke_width = if builder.stroke_color.a > 0. {
+                    builder.stroke_width.max(0.)
+                } else {
+                    0.
+                };
+                // with room for the smooth edge
+                let extent = builder.radius.max(0.) + stroke_width / 2. + 2.;
+
+                let _ = shader.set_uniform("Position", (x, y));
+                let _ = shader.set_uniform("Extent", extent);
+                let _ = shader.set_uniform("Radius", builder.radius);
+                let _ = shader.set_uniform("StrokeWidth", stroke_width);
+                let Color { r, g, b, a } = builder.stroke_color;
+                let _ = shader.set_uniform("StrokeColor", (r, g, b, a));
+                graph.world(circle.parent).set_uniforms(shader);
+                circle.ramp.set_uniforms(shader, &builder.fill, 0);
+                let _blend = gcx.blend(builder.blend);
+                gcx.use_vertex_array(quad, |gcx| {
+                    gcx.draw_arrays(PrimitiveType::TrianglesStrip, 0, 4);
+                });
+            }
+        });
+    }
+
+    fn create_node(&mut self) -> RawCircle {
+        let (nposition, position) = create_signal();
+        let (nradius, radius) = create_signal();
+        let (nfill, fill) = create_signal();
+        let (nstroke_width, stroke_width) = create_signal();
+        let (nstroke_color, stroke_color) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (nshadow_color, shadow_color) = create_signal();
+        let (nshadow_offset, shadow_offset) = create_signal();
+        let (nshadow_blur, shadow_blur) = create_signal();
+        let (nglow, glow) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NCircleInner {
+            drop,
+            position,
+            radius,
+            fill,
+            stroke_width,
+            stroke_color,
+            blend,
+            z_index,
+            shadow_color,
+            shadow_offset,
+            shadow_blur,
+            glow,
+        });
+
+        RawCircle {
+            drop: ndrop,
+            position: nposition,
+            radius: nradius,
+            fill: nfill,
+            stroke_width: nstroke_width,
+            stroke_color: nstroke_color,
+            blend: nblend,
+            z_index: nz_index,
+            shadow_color: nshadow_color,
+            shadow_offset: nshadow_offset,
+            shadow_blur: nshadow_blur,
+            glow: nglow,
+        }
+    }
+
+    fn update(&mut self) {
+        self.circles.retain_mut(|circle| {
+            if let Some(position) = circle.inner.position.get() {
+                circle.builder.position = position;
+            }
+            if let Some(radius) = circle.inner.radius.get() {
+                circle.builder.radius = radius;
+            }
+            if let Some(fill) = circle.inner.fill.get() {
+                circle.ramp.update(&fill);
+                circle.builder.fill = fill;
+            }
+            if let Some(stroke_width) = circle.inner.stroke_width.get() {
+                circle.builder.stroke_width = stroke_width;
+            }
+            if let Some(stroke_color) = circle.inner.stroke_color.get() {
+                circle.builder.stroke_color = stroke_color;
+            }
+            if let Some(blend) = circle.inner.blend.get() {
+                circle.builder.blend = blend;
+            }
+            if let Some(z_index) = circle.inner.z_index.get() {
+                circle.builder.z_index = z_index;
+            }
+            if let Some(color) = circle.inner.shadow_color.get() {
+                circle.builder.shadow.color = color;
+            }
+            if let Some(offset) = circle.inner.shadow_offset.get() {
+                circle.builder.shadow.offset = offset;
+            }
+            if let Some(blur) = circle.inner.shadow_blur.get() {
+                circle.builder.shadow.blur = blur;
+            }
+            if let Some(glow) = circle.inner.glow.get() {
+                circle.builder.shadow.glow = glow;
+            }
+
+            circle.inner.drop.get().is_none()
+        });
+    }
+}
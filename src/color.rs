@@ -1,4 +1,7 @@
-use crate::gcx::vertex_array::{DataType, GLType};
+use crate::{
+    gcx::vertex_array::{DataType, GLType},
+    signal::{lerp, Lerp},
+};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, bytemuck::Zeroable, bytemuck::Pod)]
@@ -23,11 +26,34 @@ impl Color {
     pub const RED: Self = Self::new(1.0, 0.0, 0.0, 1.0);
     pub const GREEN: Self = Self::new(0.0, 1.0, 0.0, 1.0);
     pub const BLUE: Self = Self::new(0.0, 0.0, 1.0, 1.0);
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
     pub const ALPHA: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
 
     pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
         Self { r, g, b, a }
     }
+
+    /// From `0xRRGGBBAA`
+    pub const fn hex(value: u32) -> Self {
+        Self {
+            r: (value >> 24 & 255) as f32 / 255.,
+            g: (value >> 16 & 255) as f32 / 255.,
+            b: (value >> 8 & 255) as f32 / 255.,
+            a: (value & 255) as f32 / 255.,
+        }
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        Self {
+            r: lerp(self.r, to.r, time),
+            g: lerp(self.g, to.g, time),
+            b: lerp(self.b, to.b, time),
+            a: lerp(self.a, to.a, time),
+        }
+    }
 }
 
 impl From<i32> for Color {
@@ -0,0 +1,819 @@
+use std::{path::Path, sync::Arc};
+
+use crate::{
+    color::Color,
+    gcx::{
+        blend::BlendMode,
+        framebuffer::Framebuffer,
+        shader::Shader,
+        texture::{Format, InternalFormat, Texture},
+        vertex_array::VertexArray,
+        BufferBit, DataType, PrimitiveType, GCX,
+    },
+    mask::{layer_quad, LAYER_VERTEX},
+    scene::SceneTask,
+    shadow::Shadow,
+    signal::{create_signal, lerp, Lerp, NSignal, RawSignal, Signal},
+};
+
+#[derive(Debug)]
+pub enum LutError {
+    Io(std::io::Error),
+    /// The line and what is wrong with it
+    Parse(usize, String),
+    /// The size of the LUT and the number of colors
+    Size(usize, usize),
+    /// There is no `LUT_3D_SIZE`, like in a 1D LUT
+    MissingSize,
+}
+
+impl std::fmt::Display for LutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LutError::Io(err) => write!(f, "Cannot read LUT: {err}"),
+            LutError::Parse(line, err) => write!(f, "Cannot parse LUT at line {line}: {err}"),
+            LutError::Size(size, colors) => write!(
+                f,
+                "A LUT of size {size} needs {} colors but has {colors}",
+                size * size * size
+            ),
+            LutError::MissingSize => write!(f, "The LUT has no LUT_3D_SIZE"),
+        }
+    }
+}
+
+impl std::error::Error for LutError {}
+
+impl From<std::io::Error> for LutError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// A 3D look up table of colors, is cheap to clone
+#[derive(Clone)]
+pub struct Lut {
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    /// RGB colors, red changes first then green then blue
+    data: Arc<[f32]>,
+}
+
+impl std::fmt::Debug for Lut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Lut")
+            .field("size", &self.size)
+            .field("domain_min", &self.domain_min)
+            .field("domain_max", &self.domain_max)
+            .finish()
+    }
+}
+
+impl Lut {
+    /// Parses a `.cube` file with a `LUT_3D_SIZE`, the keywords that it does not know are skipped
+    pub fn from_cube(source: &str) -> Result<Self, LutError> {
+        let mut size = None;
+        let mut domain_min = [0.; 3];
+        let mut domain_max = [1.; 3];
+        let mut data = Vec::new();
+
+        for (i, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let first = words.next().unwrap_or_default();
+            let numbers = |words: std::str::SplitWhitespace| {
+                let numbers = words
+                    .map(|word| word.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| LutError::Parse(i + 1, err.to_string()))?;
+                <[f32; 3]>::try_from(numbers)
+                    .map_err(|_| LutError::Parse(i + 1, "Needs three numbers".to_string()))
+            };
+
+            match first {
+                "LUT_3D_SIZE" => {
+                    let value = words.next().and_then(|word| word.parse::<usize>().ok());
+                    size = Some(value.filter(|size| *size >= 2).ok_or_else(|| {
+                        LutError::Parse(i + 1, "The size needs to be at least 2".to_string())
+                    })?);
+                }
+                "LUT_1D_SIZE" => {
+                    return Err(LutError::Parse(
+                        i + 1,
+                        "A 1D LUT is not supported".to_string(),
+                    ))
+                }
+                "DOMAIN_MIN" => domain_min = numbers(words)?,
+                "DOMAIN_MAX" => domain_max = numbers(words)?,
+                _ if first.parse::<f32>().is_ok() => data.extend(numbers(line.split_whitespace())?),
+                // like `TITLE` and `LUT_3D_INPUT_RANGE`
+                _ => {}
+            }
+        }
+
+        let size = size.ok_or(LutError::MissingSize)?;
+        if data.len() != size * size * size * 3 {
+            return Err(LutError::Size(size, data.len() / 3));
+        }
+
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            data: data.into(),
+        })
+    }
+
+    pub fn from_cube_file(path: impl AsRef<Path>) -> Result<Self, LutError> {
+        Self::from_cube(&std::fs::read_to_string(path)?)
+    }
+
+    /// The number of colors on every side
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// A full screen pass that is applied to everything that is drawn in a group or on the screen
+#[derive(Debug, Clone)]
+pub enum Effect {
+    /// A gaussian blur, the `radius` is in pixels of the screen up to `64`
+    Blur { radius: f32 },
+    /// What is brighter than the `threshold` glows with a blur of `radius` pixels
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    },
+    /// Red and blue are moved apart by up to `offset` pixels at the edges
+    ChromaticAberration { offset: f32 },
+    /// Darkens from `radius` to `radius + softness`, `1` is the distance from the center to the top
+    Vignette {
+        intensity: f32,
+        radius: f32,
+        softness: f32,
+    },
+    /// `brightness` is added to the color, `contrast` and `saturation` do nothing at `1`
+    ColorAdjust {
+        brightness: f32,
+        contrast: f32,
+        saturation: f32,
+    },
+    /// Maps the colors with the LUT, `intensity` mixes the graded color with the color before
+    ColorGrade { lut: Lut, intensity: f32 },
+}
+
+impl Effect {
+    /// The same effect with parameters that change nothing
+    pub fn neutral(&self) -> Self {
+        match self {
+            Effect::Blur { .. } => Effect::Blur { radius: 0. },
+            Effect::Bloom {
+                threshold, radius, ..
+            } => Effect::Bloom {
+                threshold: *threshold,
+                intensity: 0.,
+                radius: *radius,
+            },
+            Effect::ChromaticAberration { .. } => Effect::ChromaticAberration { offset: 0. },
+            Effect::Vignette {
+                radius, softness, ..
+            } => Effect::Vignette {
+                intensity: 0.,
+                radius: *radius,
+                softness: *softness,
+            },
+            Effect::ColorAdjust { .. } => Effect::ColorAdjust {
+                brightness: 0.,
+                contrast: 1.,
+                saturation: 1.,
+            },
+            Effect::ColorGrade { lut, .. } => Effect::ColorGrade {
+                lut: lut.clone(),
+                intensity: 0.,
+            },
+        }
+    }
+}
+
+/// Effects of the same kind interpolate the parameters, different kinds and LUTs switch at the half
+impl Lerp for Effect {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        match (self, to) {
+            (Effect::Blur { radius }, Effect::Blur { radius: to_radius }) => Effect::Blur {
+                radius: lerp(*radius, *to_radius, time),
+            },
+            (
+                Effect::Bloom {
+                    threshold,
+                    intensity,
+                    radius,
+                },
+                Effect::Bloom {
+                    threshold: to_threshold,
+                    intensity: to_intensity,
+                    radius: to_radius,
+                },
+            ) => Effect::Bloom {
+                threshold: lerp(*threshold, *to_threshold, time),
+                intensity: lerp(*intensity, *to_intensity, time),
+                radius: lerp(*radius, *to_radius, time),
+            },
+            (
+                Effect::ChromaticAberration { offset },
+                Effect::ChromaticAberration { offset: to_offset },
+            ) => Effect::ChromaticAberration {
+                offset: lerp(*offset, *to_offset, time),
+            },
+            (
+                Effect::Vignette {
+                    intensity,
+                    radius,
+                    softness,
+                },
+                Effect::Vignette {
+                    intensity: to_intensity,
+                    radius: to_radius,
+                    softness: to_softness,
+                },
+            ) => Effect::Vignette {
+                intensity: lerp(*intensity, *to_intensity, time),
+                radius: lerp(*radius, *to_radius, time),
+                softness: lerp(*softness, *to_softness, time),
+            },
+            (
+                Effect::ColorAdjust {
+                    brightness,
+                    contrast,
+                    saturation,
+                },
+                Effect::ColorAdjust {
+                    brightness: to_brightness,
+                    contrast: to_contrast,
+                    saturation: to_saturation,
+                },
+            ) => Effect::ColorAdjust {
+                brightness: lerp(*brightness, *to_brightness, time),
+                contrast: lerp(*contrast, *to_contrast, time),
+                saturation: lerp(*saturation, *to_saturation, time),
+            },
+            (
+                Effect::ColorGrade { lut, intensity },
+                Effect::ColorGrade {
+                    lut: to_lut,
+                    intensity: to_intensity,
+                },
+            ) => Effect::ColorGrade {
+                lut: if time < 0.5 { lut } else { to_lut }.clone(),
+                intensity: lerp(*intensity, *to_intensity, time),
+            },
+            _ if time < 0.5 => self.clone(),
+            _ => to.clone(),
+        }
+    }
+}
+
+/// The effects are interpolated one by one, an effect that is only in one of the lists
+/// goes from or to its `Effect::neutral`
+impl Lerp for Vec<Effect> {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        (0..self.len().max(to.len()))
+            .map(|i| match (self.get(i), to.get(i)) {
+                (Some(from), Some(to)) => from.lerp(to, time),
+                (Some(from), None) => from.lerp(&from.neutral(), time),
+                (None, Some(to)) => to.neutral().lerp(to, time),
+                (None, None) => unreachable!(),
+            })
+            .collect()
+    }
+}
+
+/// The effects that are applied to the whole screen
+pub struct PostProcess<'a> {
+    pub effects: Signal<'a, Vec<Effect>>,
+}
+
+impl<'a> PostProcess<'a> {
+    pub(crate) fn new(effects: Vec<Effect>, raw: RawPostProcess, scene: &'a SceneTask) -> Self {
+        Self {
+            effects: Signal::new(raw.effects, scene, effects),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct NPostProcess {
+    effects: NSignal<Vec<Effect>>,
+}
+
+pub struct RawPostProcess {
+    effects: RawSignal<Vec<Effect>>,
+}
+
+impl NPostProcess {
+    pub(crate) fn create() -> (NPostProcess, RawPostProcess) {
+        let (neffects, effects) = create_signal();
+        (
+            NPostProcess { effects },
+            RawPostProcess { effects: neffects },
+        )
+    }
+
+    pub(crate) fn update(&mut self, effects: &mut Vec<Effect>) {
+        if let Some(new) = self.effects.get() {
+            *effects = new;
+        }
+    }
+}
+
+const PASS_HEADER: &str = r#"
+    #version 320 es
+
+    precision highp float;
+
+    uniform sampler2D INPUT;
+    // one pixel in uv
+    uniform vec2 Texel;
+
+    in vec2 UV;
+    out vec4 color;
+
+    const vec3 LUMA = vec3(0.2126, 0.7152, 0.0722);
+"#;
+
+const BLUR: &str = r#"
+    // one pixel along the axis of the pass
+    uniform vec2 Direction;
+    uniform float Radius;
+
+    void main(){
+        float sigma = max(Radius / 2.0, 0.0001);
+        int taps = int(min(ceil(Radius), 64.0));
+        vec4 sum = texture(INPUT, UV);
+        float weight = 1.0;
+        for (int i = 1; i <= taps; i++) {
+            float w = exp(-float(i * i) / (2.0 * sigma * sigma));
+            vec2 offset = Direction * Texel * float(i);
+            sum += (texture(INPUT, UV + offset) + texture(INPUT, UV - offset)) * w;
+            weight += 2.0 * w;
+        }
+        color = sum / weight;
+    }
+"#;
+
+const BRIGHT: &str = r#"
+    uniform float Threshold;
+
+    void main(){
+        vec4 c = texture(INPUT, UV);
+        color = c * smoothstep(Threshold, Threshold + 0.1, dot(c.rgb, LUMA));
+    }
+"#;
+
+const COMBINE: &str = r#"
+    uniform sampler2D BLOOM;
+    uniform float Intensity;
+
+    void main(){
+        color = clamp(texture(INPUT, UV) + texture(BLOOM, UV) * Intensity, 0.0, 1.0);
+    }
+"#;
+
+const CHROMATIC: &str = r#"
+    uniform float Offset;
+
+    void main(){
+        vec2 offset = (UV - 0.5) * 2.0 * Offset * Texel;
+        vec4 r = texture(INPUT, UV + offset);
+        vec4 g = texture(INPUT, UV);
+        vec4 b = texture(INPUT, UV - offset);
+        color = vec4(r.r, g.g, b.b, max(max(r.a, g.a), b.a));
+    }
+"#;
+
+const VIGNETTE: &str = r#"
+    uniform float Intensity;
+    uniform float Radius;
+    uniform float Softness;
+
+    void main(){
+        // in half heights from the center
+        float dist = length((UV - 0.5) * vec2(Texel.y / Texel.x, 1.0)) * 2.0;
+        float factor = 1.0 - Intensity * smoothstep(Radius, Radius + max(Softness, 0.0001), dist);
+        vec4 c = texture(INPUT, UV);
+        color = vec4(c.rgb * factor, c.a);
+    }
+"#;
+
+const ADJUST: &str = r#"
+    uniform float Brightness;
+    uniform float Contrast;
+    uniform float Saturation;
+
+    void main(){
+        vec4 c = texture(INPUT, UV);
+        if (c.a <= 0.0) {
+            color = vec4(0.0);
+            return;
+        }
+        vec3 rgb = c.rgb / c.a + Brightness;
+        rgb = (rgb - 0.5) * Contrast + 0.5;
+        rgb = mix(vec3(dot(rgb, LUMA)), rgb, Saturation);
+        color = vec4(clamp(rgb, 0.0, 1.0) * c.a, c.a);
+    }
+"#;
+
+const GRADE: &str = r#"
+    precision highp sampler3D;
+
+    uniform sampler3D LUT;
+    uniform float Size;
+    uniform vec3 DomainMin;
+    uniform vec3 DomainMax;
+    uniform float Intensity;
+
+    void main(){
+        vec4 c = texture(INPUT, UV);
+        if (c.a <= 0.0) {
+            color = vec4(0.0);
+            return;
+        }
+        vec3 rgb = c.rgb / c.a;
+        vec3 t = clamp((rgb - DomainMin) / (DomainMax - DomainMin), 0.0, 1.0);
+        vec3 graded = texture(LUT, t * (Size - 1.0) / Size + 0.5 / Size).rgb;
+        rgb = mix(rgb, clamp(graded, 0.0, 1.0), Intensity);
+        color = vec4(rgb * c.a, c.a);
+    }
+"#;
+
+const TINT: &str = r#"
+    // premultiplied
+    uniform vec4 Color;
+    // in pixels
+    uniform vec2 Offset;
+
+    void main(){
+        color = Color * texture(INPUT, UV - Offset * Texel).a;
+    }
+"#;
+
+const COPY: &str = r#"
+    void main(){
+        color = texture(INPUT, UV);
+    }
+"#;
+
+struct EffectShaders {
+    blur: Shader,
+    bright: Shader,
+    combine: Shader,
+    chromatic: Shader,
+    vignette: Shader,
+    adjust: Shader,
+    grade: Shader,
+    tint: Shader,
+    copy: Shader,
+}
+
+/// The 3D textures of this many LUTs are kept, the one that was used last is dropped first
+const MAX_LUTS: usize = 4;
+
+/// The offscreen framebuffers of the effects, they have the size of the viewport
+/// the content is drawn in the first one and the passes go between the three
+pub(crate) struct EffectLayers {
+    targets: [Framebuffer; 3],
+    shaders: EffectShaders,
+    quad: VertexArray,
+    /// The last used is at the end
+    luts: Vec<(Lut, Texture)>,
+}
+
+impl EffectLayers {
+    pub(crate) fn new(gcx: &GCX) -> Self {
+        let shader = |pass: &str| {
+            gcx.create_shader()
+                .vertex(LAYER_VERTEX)
+                .fragment(format!("{PASS_HEADER}{pass}"))
+                .build(gcx)
+                .unwrap()
+        };
+        let shaders = EffectShaders {
+            blur: shader(BLUR),
+            bright: shader(BRIGHT),
+            combine: shader(COMBINE),
+            chromatic: shader(CHROMATIC),
+            vignette: shader(VIGNETTE),
+            adjust: shader(ADJUST),
+            grade: shader(GRADE),
+            tint: shader(TINT),
+            copy: shader(COPY),
+        };
+
+        let [width, height] = gcx.viewport_size();
+        Self {
+            targets: std::array::from_fn(|_| gcx.create_framebuffer(width, height, false)),
+            shaders,
+            quad: layer_quad(gcx),
+            luts: Vec::new(),
+        }
+    }
+
+    /// Makes the framebuffers again if the viewport has a different size
+    pub(crate) fn resize(&mut self, gcx: &GCX) {
+        let [width, height] = gcx.viewport_size();
+        if self.targets[0].width() != width || self.targets[0].height() != height {
+            self.targets = std::array::from_fn(|_| gcx.create_framebuffer(width, height, false));
+        }
+    }
+
+    /// Draws `content` with the `effects` on the current framebuffer
+    pub(crate) fn draw<S>(
+        &mut self,
+        gcx: &GCX,
+        effects: &[Effect],
+        state: &mut S,
+        content: impl FnOnce(&GCX, &mut S),
+    ) {
+        gcx.use_framebuffer(&self.targets[0], |gcx| {
+            gcx.clear_color(0);
+            gcx.clear(BufferBit::COLOR);
+            content(gcx, state);
+        });
+
+        let mut current = 0;
+        for effect in effects {
+            // the two targets that are not the current one
+            let (a, b) = ((current + 1) % 3, (current + 2) % 3);
+            let shaders = &self.shaders;
+            current = match effect {
+                Effect::Blur { radius } => {
+                    self.blur(gcx, current, a, b, *radius);
+                    b
+                }
+                Effect::Bloom {
+                    threshold,
+                    intensity,
+                    radius,
+                } => {
+                    self.pass(gcx, &shaders.bright, current, Some(a), |shader| {
+                        let _ = shader.set_uniform("Threshold", *threshold);
+                    });
+                    self.blur(gcx, a, b, a, *radius);
+                    self.targets[a].texture().activate(1);
+                    self.pass(gcx, &shaders.combine, current, Some(b), |shader| {
+                        let _ = shader.set_uniform("BLOOM", 1);
+                        let _ = shader.set_uniform("Intensity", *intensity);
+                    });
+                    b
+                }
+                Effect::ChromaticAberration { offset } => {
+                    self.pass(gcx, &shaders.chromatic, current, Some(a), |shader| {
+                        let _ = shader.set_uniform("Offset", *offset);
+                    });
+                    a
+                }
+                Effect::Vignette {
+                    intensity,
+                    radius,
+                    softness,
+                } => {
+                    self.pass(gcx, &shaders.vignette, current, Some(a), |shader| {
+                        let _ = shader.set_uniform("Intensity", *intensity);
+                        let _ = shader.set_uniform("Radius", *radius);
+                        let _ = shader.set_uniform("Softness", *softness);
+                    });
+                    a
+                }
+                Effect::ColorAdjust {
+                    brightness,
+                    contrast,
+                    saturation,
+                } => {
+                    self.pass(gcx, &shaders.adjust, current, Some(a), |shader| {
+                        let _ = shader.set_uniform("Brightness", *brightness);
+                        let _ = shader.set_uniform("Contrast", *contrast);
+                        let _ = shader.set_uniform("Saturation", *saturation);
+                    });
+                    a
+                }
+                Effect::ColorGrade { lut, intensity } => {
+                    self.lut_texture(gcx, lut).activate(1);
+                    self.pass(gcx, &self.shaders.grade, current, Some(a), |shader| {
+                        let [min_r, min_g, min_b] = lut.domain_min;
+                        let [max_r, max_g, max_b] = lut.domain_max;
+                        let _ = shader.set_uniform("LUT", 1);
+                        let _ = shader.set_uniform("Size", lut.size as f32);
+                        let _ = shader.set_uniform("DomainMin", (min_r, min_g, min_b));
+                        let _ = shader.set_uniform("DomainMax", (max_r, max_g, max_b));
+                        let _ = shader.set_uniform("Intensity", *intensity);
+                    });
+                    a
+                }
+            };
+        }
+
+        let _blend = gcx.blend(BlendMode::Normal);
+        self.pass(gcx, &self.shaders.copy, current, None, |_| {});
+    }
+
+    /// Draws the shadow and the glow of `content` on the current framebuffer and then `content`
+    /// `up` is the one of `World`
+    pub(crate) fn draw_shadow<S>(
+        &mut self,
+        gcx: &GCX,
+        shadow: &Shadow,
+        up: f32,
+        state: &mut S,
+        mut content: impl FnMut(&GCX, &mut S),
+    ) {
+        gcx.use_framebuffer(&self.targets[0], |gcx| {
+            gcx.clear_color(0);
+            gcx.clear(BufferBit::COLOR);
+            content(gcx, state);
+        });
+
+        let layers = [
+            (shadow.color, shadow.offset, shadow.blur, BlendMode::Normal),
+            (
+                shadow.glow.color,
+                [0.; 2],
+                shadow.glow.radius,
+                BlendMode::Screen,
+            ),
+        ];
+        for (color, [x, y], blur, blend) in layers {
+            if color.a <= 0. {
+                continue;
+            }
+            self.blur(gcx, 0, 1, 2, blur);
+            let _blend = gcx.blend(blend);
+            self.pass(gcx, &self.shaders.tint, 2, None, |shader| {
+                let Color { r, g, b, a } = color;
+                let _ = shader.set_uniform("Color", (r * a, g * a, b * a, a));
+                let _ = shader.set_uniform("Offset", (x, y * up));
+            });
+        }
+
+        content(gcx, state);
+    }
+
+    /// The 3D texture of the `lut`, it is made if it is not one of the last `MAX_LUTS`
+    fn lut_texture(&mut self, gcx: &GCX, lut: &Lut) -> Texture {
+        let cached = self
+            .luts
+            .iter()
+            .position(|(cached, _)| Arc::ptr_eq(&cached.data, &lut.data));
+        let entry = match cached {
+            Some(i) => self.luts.remove(i),
+            None => {
+                if self.luts.len() >= MAX_LUTS {
+                    self.luts.remove(0);
+                }
+                let size = lut.size as i32;
+                let texture = gcx.create_texture_3d(
+                    InternalFormat::RGB16F,
+                    [size; 3],
+                    Format::RGB,
+                    DataType::F32,
+                    &lut.data,
+                );
+                (lut.clone(), texture)
+            }
+        };
+        let texture = entry.1.clone();
+        self.luts.push(entry);
+        texture
+    }
+
+    /// A horizontal pass from `from` to `via` and a vertical one from `via` to `to`
+    fn blur(&self, gcx: &GCX, from: usize, via: usize, to: usize, radius: f32) {
+        for (from, to, direction) in [(from, via, (1., 0.)), (via, to, (0., 1.))] {
+            self.pass(gcx, &self.shaders.blur, from, Some(to), |shader| {
+                let _ = shader.set_uniform("Direction", direction);
+                let _ = shader.set_uniform("Radius", radius);
+            });
+        }
+    }
+
+    /// Draws the `input` target with the `shader` in to the `output` target or the current framebuffer
+    /// with the blend mode that it has, the other textures need to be activated from unit `1`
+    fn pass(
+        &self,
+        gcx: &GCX,
+        shader: &Shader,
+        input: usize,
+        output: Option<usize>,
+        uniforms: impl FnOnce(&Shader),
+    ) {
+        let input = &self.targets[input];
+        let draw = |gcx: &GCX| {
+            gcx.use_shader(shader, |gcx| {
+                input.texture().activate(0);
+                let _ = shader.set_uniform("INPUT", 0);
+                let texel = (1. / input.width() as f32, 1. / input.height() as f32);
+                let _ = shader.set_uniform("Texel", texel);
+                uniforms(shader);
+                gcx.use_vertex_array(&self.quad, |gcx| {
+                    gcx.draw_arrays(PrimitiveType::TrianglesFan, 0, 4);
+                });
+            });
+        };
+
+        match output {
+            Some(output) => gcx.use_framebuffer(&self.targets[output], |gcx| {
+                gcx.clear_color(0);
+                gcx.clear(BufferBit::COLOR);
+                // the target is empty, so this only copies
+                let _blend = gcx.blend(BlendMode::Normal);
+                draw(gcx);
+            }),
+            None => draw(gcx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDENTITY: &str = "
+        # an identity LUT
+        TITLE \"identity\"
+        LUT_3D_SIZE 2
+        DOMAIN_MIN 0 0 0
+        DOMAIN_MAX 1 1 1
+
+        0 0 0
+        1 0 0
+        0 1 0
+        1 1 0
+        0 0 1
+        1 0 1
+        0 1 1
+        1 1 1
+    ";
+
+    #[test]
+    fn parses_cube() {
+        let lut = Lut::from_cube(IDENTITY).unwrap();
+        assert_eq!(lut.size(), 2);
+        assert_eq!(lut.domain_min, [0.; 3]);
+        assert_eq!(lut.domain_max, [1.; 3]);
+        assert_eq!(lut.data.len(), 8 * 3);
+        // red changes first
+        assert_eq!(&lut.data[3..6], &[1., 0., 0.]);
+        assert_eq!(&lut.data[6..9], &[0., 1., 0.]);
+    }
+
+    #[test]
+    fn domain() {
+        let source = IDENTITY
+            .replace("DOMAIN_MIN 0 0 0", "DOMAIN_MIN -1 -1 -1")
+            .replace("DOMAIN_MAX 1 1 1", "DOMAIN_MAX 2 2 2.5");
+        let lut = Lut::from_cube(&source).unwrap();
+        assert_eq!(lut.domain_min, [-1.; 3]);
+        assert_eq!(lut.domain_max, [2., 2., 2.5]);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Lut::from_cube("0 0 0"),
+            Err(LutError::MissingSize)
+        ));
+        assert!(matches!(
+            Lut::from_cube("LUT_3D_SIZE 1\n0 0 0"),
+            Err(LutError::Parse(1, _))
+        ));
+        assert!(matches!(
+            Lut::from_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1"),
+            Err(LutError::Parse(1, _))
+        ));
+        assert!(matches!(
+            Lut::from_cube("LUT_3D_SIZE 2\n0 0"),
+            Err(LutError::Parse(2, _))
+        ));
+        assert!(matches!(
+            Lut::from_cube("LUT_3D_SIZE 2\n0 x 0"),
+            Err(LutError::Parse(2, _))
+        ));
+        assert!(matches!(
+            Lut::from_cube("LUT_3D_SIZE 2\n0 0 0\n1 1 1"),
+            Err(LutError::Size(2, 2))
+        ));
+    }
+
+    #[test]
+    fn skips_unknown_keywords() {
+        let source = IDENTITY.replace(
+            "DOMAIN_MIN 0 0 0",
+            "LUT_3D_INPUT_RANGE 0 1\nLUT_IN_VIDEO_RANGE\nDOMAIN_MIN 0 0 0",
+        );
+        let lut = Lut::from_cube(&source).unwrap();
+        assert_eq!(lut.data.len(), 8 * 3);
+    }
+}
@@ -1,4 +1,4 @@
-use std::{future::Future, num::NonZeroU32, pin::Pin, sync::Arc};
+use std::{collections::HashMap, future::Future, num::NonZeroU32, pin::Pin, sync::Arc};
 
 use tokio::{
     spawn,
@@ -10,11 +10,15 @@ use tokio::{
 };
 
 use crate::{
+    effect::{Effect, EffectLayers},
     engine_message::{EngineMessage, EngineSender},
     gcx::GCX,
-    info::Info,
-    node::AbstractNodeManager,
+    group::{GroupId, SceneGraph},
+    info::{Info, Origin},
+    mask::{Mask, MaskId, MaskLayers},
+    node::{union, AbstractNodeManager, NodeBounds},
     scene::SceneTask,
+    shadow::Shadow,
     OSend,
 };
 
@@ -35,10 +39,127 @@ pub struct Engine {
     pub info: Arc<RwLock<Info>>,
 
     nodes: Vec<Box<dyn AbstractNodeManager>>,
+    graph: SceneGraph,
+    mask_layers: Option<MaskLayers>,
+    /// For the effects of groups, one for every depth of groups with effects in each other
+    effect_layers: Vec<Option<EffectLayers>>,
+    /// For the effects of the screen
+    screen_layers: Option<EffectLayers>,
+    /// For the shadows of the nodes
+    shadow_layers: Option<EffectLayers>,
 
     audio_buffer: Vec<f32>,
 }
 
+/// A node that is drawn in this frame
+struct Item {
+    z_index: i32,
+    manager: usize,
+    /// The index in to the `draw_items` of the manager
+    item: usize,
+    mask: Option<Mask>,
+    /// The group of the effects that it is drawn with
+    layer: Option<GroupId>,
+    shadow: Option<Shadow>,
+    /// The one of `World`
+    up: f32,
+}
+
+/// What is needed to draw the items of a frame
+struct Frame<'a> {
+    nodes: &'a mut [Box<dyn AbstractNodeManager>],
+    graph: &'a SceneGraph,
+    /// The manager and the item of every mask
+    sources: HashMap<MaskId, (usize, usize)>,
+    mask_layers: &'a mut Option<MaskLayers>,
+    effect_layers: &'a mut Vec<Option<EffectLayers>>,
+    shadow_layers: &'a mut Option<EffectLayers>,
+}
+
+impl<'a> Frame<'a> {
+    /// The items in the same group with effects right in `outer` that are next to each other are drawn in
+    /// the same layer, the groups with effects in that group are drawn in their own layers in it first
+    /// `outer` is `None` for the screen and `depth` is the count of the layers around the items
+    fn draw(&mut self, gcx: &GCX, items: &[Item], outer: Option<GroupId>, depth: usize) {
+        let graph = self.graph;
+        for run in
+            items.chunk_by(|a, b| graph.layer_in(a.layer, outer) == graph.layer_in(b.layer, outer))
+        {
+            let Some(group) = graph.layer_in(run[0].layer, outer) else {
+                self.draw_masked(gcx, run);
+                continue;
+            };
+            if self.effect_layers.len() <= depth {
+                self.effect_layers.resize_with(depth + 1, || None);
+            }
+            let mut layers = self.effect_layers[depth]
+                .take()
+                .unwrap_or_else(|| EffectLayers::new(gcx));
+            layers.resize(gcx);
+            layers.draw(gcx, graph.effects(group), self, |gcx, frame| {
+                frame.draw(gcx, run, Some(group), depth + 1)
+            });
+            self.effect_layers[depth] = Some(layers);
+        }
+    }
+
+    /// The items with the same mask that are next to each other are drawn in the same layer
+    fn draw_masked(&mut self, gcx: &GCX, items: &[Item]) {
+        let graph = self.graph;
+        for run in items.chunk_by(|a, b| a.mask == b.mask) {
+            let Some(mask) = run[0].mask else {
+                self.draw_items(gcx, run);
+                continue;
+            };
+            let mut layers = self
+                .mask_layers
+                .take()
+                .unwrap_or_else(|| MaskLayers::new(gcx));
+            layers.resize(gcx);
+            let source = self.sources.get(&mask.id).copied();
+            layers.draw(
+                gcx,
+                mask,
+                self,
+                |gcx, frame| {
+                    if let Some((manager, item)) = source {
+                        frame.nodes[manager].render(gcx, graph, &[item]);
+                    }
+                },
+                |gcx, frame| frame.draw_items(gcx, run),
+            );
+            *self.mask_layers = Some(layers);
+        }
+    }
+
+    /// The items of the same manager that are next to each other are drawn together
+    /// an item with a shadow is drawn alone
+    fn draw_items(&mut self, gcx: &GCX, items: &[Item]) {
+        let graph = self.graph;
+        let mut indices = Vec::new();
+        for run in items
+            .chunk_by(|a, b| a.manager == b.manager && a.shadow.is_none() && b.shadow.is_none())
+        {
+            if let Some(shadow) = &run[0].shadow {
+                let Item { manager, item, .. } = run[0];
+                let mut layers = self
+                    .shadow_layers
+                    .take()
+                    .unwrap_or_else(|| EffectLayers::new(gcx));
+                layers.resize(gcx);
+                layers.draw_shadow(gcx, shadow, run[0].up, self, |gcx, frame| {
+                    frame.nodes[manager].render(gcx, graph, &[item])
+                });
+                *self.shadow_layers = Some(layers);
+                continue;
+            }
+            indices.clear();
+            indices.extend(run.iter().map(|item| item.item));
+            self.nodes[run[0].manager].render(gcx, self.graph, &indices);
+        }
+    }
+}
+
 impl Engine {
     pub fn new(
         fps: f64,
@@ -52,6 +173,7 @@ impl Engine {
             delta,
             width,
             height,
+            origin: Origin::default(),
         };
 
         let (engine_sender, receiver) = channel(8);
@@ -64,6 +186,11 @@ impl Engine {
             scenes: Vec::default(),
             info: Arc::new(RwLock::new(info)),
             nodes: Vec::default(),
+            graph: SceneGraph::default(),
+            mask_layers: None,
+            effect_layers: Vec::new(),
+            screen_layers: None,
+            shadow_layers: None,
             counter: 0,
             engine_sender,
             receiver,
@@ -72,6 +199,17 @@ impl Engine {
         }
     }
 
+    pub fn with_origin(self, origin: Origin) -> Self {
+        self.info.try_write().unwrap().origin = origin;
+        self
+    }
+
+    /// The effects of the whole screen, a scene can change them with `SceneTask::post_process`
+    pub fn with_effects(mut self, effects: Vec<Effect>) -> Self {
+        self.graph.set_effects(effects);
+        self
+    }
+
     pub fn audio_buffer(&self) -> &[f32] {
         &self.audio_buffer
     }
@@ -109,10 +247,88 @@ impl Engine {
         for sample in self.audio_buffer.iter_mut() {
             *sample = 0.;
         }
+        let draws = self
+            .nodes
+            .iter()
+            .map(|node| node.draw_items())
+            .collect::<Vec<_>>();
+        let parents = draws.iter().flatten().map(|draw| draw.parent).collect();
+        self.graph.remove_empty(&parents);
+
+        let info = self.info.try_read().unwrap();
+        self.graph.propagate(
+            info.width.get() as f32,
+            info.height.get() as f32,
+            info.origin,
+        );
+        let delta = info.delta;
+        drop(info);
+
+        // the sort is stable so the registration order is kept
+        let mut items = Vec::new();
+        let mut sources = HashMap::new();
+        for (manager, draws) in draws.into_iter().enumerate() {
+            for (item, draw) in draws.into_iter().enumerate() {
+                if let Some(id) = draw.mask_source {
+                    sources.insert(id, (manager, item));
+                    continue;
+                }
+                let world = self.graph.world(draw.parent);
+                items.push(Item {
+                    z_index: draw.z_index,
+                    manager,
+                    item,
+                    mask: draw.mask.or(world.mask),
+                    layer: world.layer,
+                    shadow: draw.shadow,
+                    up: world.up,
+                });
+            }
+        }
+        items.sort_by_key(|item| item.z_index);
+
+        let graph = &self.graph;
+        let mut frame = Frame {
+            nodes: &mut self.nodes,
+            graph,
+            sources,
+            mask_layers: &mut self.mask_layers,
+            effect_layers: &mut self.effect_layers,
+            shadow_layers: &mut self.shadow_layers,
+        };
+        if graph.screen_effects().is_empty() {
+            frame.draw(gcx, &items, None, 0);
+        } else {
+            let layers = self
+                .screen_layers
+                .get_or_insert_with(|| EffectLayers::new(gcx));
+            layers.resize(gcx);
+            layers.draw(gcx, graph.screen_effects(), &mut frame, |gcx, frame| {
+                frame.draw(gcx, &items, None, 0)
+            });
+        }
+
         for node in self.nodes.iter_mut() {
-            node.render(gcx);
             node.audio_process(&mut self.audio_buffer);
         }
+        self.graph.tick(delta);
+    }
+
+    /// The bounds of a group are the ones of the nodes that are drawn in it or in its children
+    fn world_bounds(&self, bounds: NodeBounds) -> Option<[[f32; 2]; 2]> {
+        let graph = &self.graph;
+        match bounds {
+            NodeBounds::Local(group, bounds) => Some(graph.root_bounds(group, bounds)),
+            NodeBounds::Group(group) => self
+                .nodes
+                .iter()
+                .flat_map(|node| node.draw_items().into_iter().zip(node.bounds(graph)))
+                .filter(|(draw, _)| {
+                    draw.mask_source.is_none() && graph.contains(group, draw.parent)
+                })
+                .map(|(draw, bounds)| graph.root_bounds(draw.parent, bounds))
+                .reduce(union),
+        }
     }
 
     pub fn finished(&self) -> bool {
@@ -137,14 +353,27 @@ impl Engine {
                     EngineMessage::Present(send) => {
                         self.waiting.push(send);
                     }
-                    EngineMessage::CreateNode(ty, builder) => {
+                    EngineMessage::CreateNode(ty, parent, builder) => {
                         for node in self.nodes.iter_mut() {
                             if node.ty_id() == ty.id {
-                                node.init_node(gcx, builder);
+                                node.init_node(gcx, builder, parent);
                                 break;
                             }
                         }
                     }
+                    EngineMessage::CreateGroup(group, send) => {
+                        send.send(self.graph.insert(group)).unwrap();
+                    }
+                    EngineMessage::CreateCamera(camera, send) => {
+                        send.send(self.graph.set_camera(camera)).unwrap();
+                    }
+                    EngineMessage::WorldBounds(bounds, send) => {
+                        send.send(self.world_bounds(bounds)).unwrap();
+                    }
+                    EngineMessage::CreatePostProcess(post_process, send) => {
+                        send.send(self.graph.set_post_process(post_process))
+                            .unwrap();
+                    }
                     EngineMessage::CreateRef(ty, send) => {
                         for node in self.nodes.iter_mut() {
                             if node.ty_id() == ty.id {
@@ -156,6 +385,7 @@ impl Engine {
                         panic!("The `{}` is not registered! You need to call `Engine::register::<{0}>()`", ty.name);
                     }
                     EngineMessage::Update => {
+                        self.graph.update();
                         for node in self.nodes.iter_mut() {
                             node.update();
                         }
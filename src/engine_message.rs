@@ -2,7 +2,13 @@ use std::any::{Any, TypeId};
 
 use tokio::sync::mpsc::Sender;
 
-use crate::OSend;
+use crate::{
+    camera::{CameraView, NCamera},
+    effect::{Effect, NPostProcess},
+    group::{GroupId, NGroup},
+    node::NodeBounds,
+    OSend,
+};
 
 #[derive(Debug)]
 pub struct Ty {
@@ -21,7 +27,15 @@ impl Ty {
 #[derive(Debug)]
 pub enum EngineMessage {
     CreateRef(Ty, OSend<Box<dyn Any + Send + Sync + 'static>>),
-    CreateNode(Ty, Box<dyn Any + Send + Sync + 'static>),
+    /// The node will be in the group
+    CreateNode(Ty, GroupId, Box<dyn Any + Send + Sync + 'static>),
+    CreateGroup(NGroup, OSend<GroupId>),
+    /// Sends back the view that the camera starts with
+    CreateCamera(NCamera, OSend<CameraView>),
+    /// Sends back the effects that the screen has now
+    CreatePostProcess(NPostProcess, OSend<Vec<Effect>>),
+    /// Sends back the bounds in pixels of the root group, `None` for a group without nodes
+    WorldBounds(NodeBounds, OSend<Option<[[f32; 2]; 2]>>),
     Present(OSend<()>),
     Update,
 }
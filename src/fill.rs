@@ -0,0 +1,439 @@
+use crate::{
+    color::Color,
+    gcx::{
+        shader::Shader,
+        texture::{Format, InternalFormat, Texture, TextureFilter, TextureTarget, TextureType},
+        DataType, GCX,
+    },
+    signal::{lerp, Lerp},
+};
+
+/// Texels in the ramp of a gradient
+const RAMP_WIDTH: usize = 256;
+
+/// The GLSL of `vec4 fill(vec2 position)` that returns the premultiplied color of the fill
+/// at the position relative to the node, the uniforms are set by `FillRamp::set_uniforms`
+pub(crate) const FILL_GLSL: &str = r#"
+    uniform int FillKind;
+    uniform vec4 FillColor;
+    uniform vec4 FillGeometry;
+    uniform sampler2D FILL_RAMP;
+
+    vec4 fill(vec2 position){
+        if (FillKind == 0) {
+            return vec4(FillColor.rgb * FillColor.a, FillColor.a);
+        }
+
+        float t;
+        if (FillKind == 1) {
+            vec2 direction = FillGeometry.zw - FillGeometry.xy;
+            t = dot(position - FillGeometry.xy, direction) / max(dot(direction, direction), 1e-12);
+        } else if (FillKind == 2) {
+            t = length(position - FillGeometry.xy) / max(FillGeometry.z, 1e-12);
+        } else {
+            vec2 offset = position - FillGeometry.xy;
+            t = fract((atan(offset.y, offset.x) - FillGeometry.z) / 6.28318530718);
+        }
+        // the centers of the first and the last of the 256 texels
+        float u = (clamp(t, 0.0, 1.0) * 255.0 + 0.5) / 256.0;
+        return texture(FILL_RAMP, vec2(u, 0.5));
+    }
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    /// From `0` to `1` along the gradient
+    pub offset: f32,
+    pub color: Color,
+}
+
+impl GradientStop {
+    pub fn new(offset: f32, color: impl Into<Color>) -> Self {
+        Self {
+            offset,
+            color: color.into(),
+        }
+    }
+}
+
+/// How the inside of a shape is painted, the points are relative to the position of the node
+///
+/// the stops should be sorted by the offset, before the first and after the last the color is the one of the stop
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    Solid(Color),
+    LinearGradient {
+        start: [f32; 2],
+        end: [f32; 2],
+        stops: Vec<GradientStop>,
+    },
+    RadialGradient {
+        center: [f32; 2],
+        radius: f32,
+        stops: Vec<GradientStop>,
+    },
+    /// The stops go counter clockwise around the center
+    ConicGradient {
+        center: [f32; 2],
+        /// In radians from the positive x axis, where the first stop is
+        angle: f32,
+        stops: Vec<GradientStop>,
+    },
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Self::Solid(Color::TRANSPARENT)
+    }
+}
+
+impl From<Color> for Fill {
+    fn from(value: Color) -> Self {
+        Self::Solid(value)
+    }
+}
+
+impl Fill {
+    pub fn linear(start: [f32; 2], end: [f32; 2], stops: Vec<GradientStop>) -> Self {
+        Self::LinearGradient { start, end, stops }
+    }
+
+    pub fn radial(center: [f32; 2], radius: f32, stops: Vec<GradientStop>) -> Self {
+        Self::RadialGradient {
+            center,
+            radius,
+            stops,
+        }
+    }
+
+    pub fn conic(center: [f32; 2], angle: f32, stops: Vec<GradientStop>) -> Self {
+        Self::ConicGradient {
+            center,
+            angle,
+            stops,
+        }
+    }
+
+    /// Empty for a solid fill
+    pub fn stops(&self) -> &[GradientStop] {
+        match self {
+            Fill::Solid(_) => &[],
+            Fill::LinearGradient { stops, .. }
+            | Fill::RadialGradient { stops, .. }
+            | Fill::ConicGradient { stops, .. } => stops,
+        }
+    }
+
+    fn stops_mut(&mut self) -> Option<&mut Vec<GradientStop>> {
+        match self {
+            Fill::Solid(_) => None,
+            Fill::LinearGradient { stops, .. }
+            | Fill::RadialGradient { stops, .. }
+            | Fill::ConicGradient { stops, .. } => Some(stops),
+        }
+    }
+
+    /// If anything would be drawn
+    pub fn is_visible(&self) -> bool {
+        match self {
+            Fill::Solid(color) => color.a > 0.,
+            _ => self.stops().iter().any(|stop| stop.color.a > 0.),
+        }
+    }
+
+    /// The color at `t` along the stops, not premultiplied
+    pub fn color_at(&self, t: f32) -> Color {
+        let stops = match self {
+            Fill::Solid(color) => return *color,
+            _ => self.stops(),
+        };
+        let Some(next) = stops.iter().position(|stop| stop.offset >= t) else {
+            return stops.last().map_or(Color::TRANSPARENT, |stop| stop.color);
+        };
+        if next == 0 {
+            return stops[0].color;
+        }
+
+        let (from, to) = (&stops[next - 1], &stops[next]);
+        let span = to.offset - from.offset;
+        let time = if span > 0. {
+            (t - from.offset) / span
+        } else {
+            1.
+        };
+        from.color.lerp(&to.color, time as f64)
+    }
+
+    /// `0` solid, `1` linear, `2` radial and `3` conic, the same as `FillKind` in the shader
+    fn kind(&self) -> i32 {
+        match self {
+            Fill::Solid(_) => 0,
+            Fill::LinearGradient { .. } => 1,
+            Fill::RadialGradient { .. } => 2,
+            Fill::ConicGradient { .. } => 3,
+        }
+    }
+
+    /// The gradient of the same kind as `like` with the color of a solid fill
+    fn as_gradient(color: Color, like: &Fill) -> Fill {
+        let mut fill = like.clone();
+        if let Some(stops) = fill.stops_mut() {
+            *stops = vec![GradientStop::new(0., color), GradientStop::new(1., color)];
+        }
+        fill
+    }
+}
+
+/// Stops with the same count move, otherwise the colors are blended at the offsets of both
+fn lerp_stops(from: &[GradientStop], to: &[GradientStop], time: f64) -> Vec<GradientStop> {
+    if from.len() == to.len() {
+        return from
+            .iter()
+            .zip(to)
+            .map(|(from, to)| GradientStop {
+                offset: lerp(from.offset, to.offset, time),
+                color: from.color.lerp(&to.color, time),
+            })
+            .collect();
+    }
+
+    let mut offsets = from
+        .iter()
+        .chain(to)
+        .map(|stop| stop.offset)
+        .collect::<Vec<_>>();
+    offsets.sort_by(f32::total_cmp);
+    offsets.dedup();
+
+    let (from, to) = (
+        Fill::linear([0.; 2], [0.; 2], from.to_vec()),
+        Fill::linear([0.; 2], [0.; 2], to.to_vec()),
+    );
+    offsets
+        .into_iter()
+        .map(|offset| GradientStop {
+            offset,
+            color: from.color_at(offset).lerp(&to.color_at(offset), time),
+        })
+        .collect()
+}
+
+/// Gradients of the same kind interpolate the geometry and the stops, a solid fill becomes a gradient of one color
+/// between different kinds of gradients the fill switches at the half
+impl Lerp for Fill {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        match (self, to) {
+            (Fill::Solid(from), Fill::Solid(to)) => Fill::Solid(from.lerp(to, time)),
+            (Fill::Solid(color), _) => Fill::as_gradient(*color, to).lerp(to, time),
+            (_, Fill::Solid(color)) => self.lerp(&Fill::as_gradient(*color, self), time),
+            (
+                Fill::LinearGradient { start, end, stops },
+                Fill::LinearGradient {
+                    start: to_start,
+                    end: to_end,
+                    stops: to_stops,
+                },
+            ) => Fill::LinearGradient {
+                start: start.lerp(to_start, time),
+                end: end.lerp(to_end, time),
+                stops: lerp_stops(stops, to_stops, time),
+            },
+            (
+                Fill::RadialGradient {
+                    center,
+                    radius,
+                    stops,
+                },
+                Fill::RadialGradient {
+                    center: to_center,
+                    radius: to_radius,
+                    stops: to_stops,
+                },
+            ) => Fill::RadialGradient {
+                center: center.lerp(to_center, time),
+                radius: lerp(*radius, *to_radius, time),
+                stops: lerp_stops(stops, to_stops, time),
+            },
+            (
+                Fill::ConicGradient {
+                    center,
+                    angle,
+                    stops,
+                },
+                Fill::ConicGradient {
+                    center: to_center,
+                    angle: to_angle,
+                    stops: to_stops,
+                },
+            ) => Fill::ConicGradient {
+                center: center.lerp(to_center, time),
+                angle: lerp(*angle, *to_angle, time),
+                stops: lerp_stops(stops, to_stops, time),
+            },
+            _ => {
+                if time < 0.5 {
+                    self.clone()
+                } else {
+                    to.clone()
+                }
+            }
+        }
+    }
+}
+
+/// The premultiplied color ramp of a fill on the gpu
+pub(crate) struct FillRamp {
+    texture: Texture,
+}
+
+impl FillRamp {
+    pub(crate) fn new(gcx: &GCX, fill: &Fill) -> Self {
+        let texture = gcx.create_texture(
+            TextureType::Tex2D,
+            TextureTarget::Tex2D,
+            0,
+            InternalFormat::RGBA8,
+            RAMP_WIDTH as i32,
+            1,
+            Format::RGBA,
+            DataType::U8,
+            &Self::texels(fill),
+        );
+        texture.set_filter(TextureFilter::Linear);
+        Self { texture }
+    }
+
+    /// Uploads the ramp again, does nothing for solid fills
+    pub(crate) fn update(&self, fill: &Fill) {
+        if !matches!(fill, Fill::Solid(_)) {
+            self.texture.update(0, &Self::texels(fill));
+        }
+    }
+
+    fn texels(fill: &Fill) -> Vec<[u8; 4]> {
+        (0..RAMP_WIDTH)
+            .map(|i| {
+                let color = fill.color_at(i as f32 / (RAMP_WIDTH - 1) as f32);
+                let alpha = color.a.clamp(0., 1.);
+                let byte = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+                [
+                    byte(color.r * alpha),
+                    byte(color.g * alpha),
+                    byte(color.b * alpha),
+                    byte(alpha),
+                ]
+            })
+            .collect()
+    }
+
+    /// Binds the ramp to `unit` and sets the uniforms of `FILL_GLSL`
+    pub(crate) fn set_uniforms(&self, shader: &Shader, fill: &Fill, unit: u32) {
+        self.texture.activate(unit);
+        let _ = shader.set_uniform("FILL_RAMP", unit as i32);
+        let _ = shader.set_uniform("FillKind", fill.kind());
+
+        let geometry = match fill {
+            Fill::Solid(color) => {
+                let _ = shader.set_uniform("FillColor", (color.r, color.g, color.b, color.a));
+                return;
+            }
+            Fill::LinearGradient { start, end, .. } => (start[0], start[1], end[0], end[1]),
+            Fill::RadialGradient { center, radius, .. } => (center[0], center[1], *radius, 0.),
+            Fill::ConicGradient { center, angle, .. } => (center[0], center[1], *angle, 0.),
+        };
+        let _ = shader.set_uniform("FillGeometry", geometry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Color = Color::new(0., 0., 0., 1.);
+    const GREY: Color = Color::new(0.5, 0.5, 0.5, 1.);
+
+    fn gradient(stops: &[(f32, Color)]) -> Fill {
+        Fill::linear(
+            [0.; 2],
+            [1., 0.],
+            stops
+                .iter()
+                .map(|(offset, color)| GradientStop::new(*offset, *color))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn color_at_blends_between_stops() {
+        let fill = gradient(&[(0., BLACK), (0.5, Color::WHITE), (1., Color::RED)]);
+        assert_eq!(fill.color_at(0.), BLACK);
+        assert_eq!(fill.color_at(0.25), GREY);
+        assert_eq!(fill.color_at(0.5), Color::WHITE);
+        assert_eq!(fill.color_at(0.75), Color::new(1., 0.5, 0.5, 1.));
+        assert_eq!(fill.color_at(1.), Color::RED);
+    }
+
+    #[test]
+    fn color_at_clamps_outside_the_stops() {
+        let fill = gradient(&[(0.25, BLACK), (0.75, Color::WHITE)]);
+        assert_eq!(fill.color_at(-1.), BLACK);
+        assert_eq!(fill.color_at(0.), BLACK);
+        assert_eq!(fill.color_at(1.), Color::WHITE);
+        assert_eq!(fill.color_at(2.), Color::WHITE);
+
+        assert_eq!(gradient(&[]).color_at(0.5), Color::TRANSPARENT);
+        assert_eq!(Fill::Solid(GREY).color_at(0.5), GREY);
+    }
+
+    #[test]
+    fn lerp_stops_with_the_same_count_move() {
+        let from = [
+            GradientStop::new(0., BLACK),
+            GradientStop::new(1., Color::WHITE),
+        ];
+        let to = [
+            GradientStop::new(0.5, Color::WHITE),
+            GradientStop::new(1., BLACK),
+        ];
+        assert_eq!(
+            lerp_stops(&from, &to, 0.5),
+            vec![GradientStop::new(0.25, GREY), GradientStop::new(1., GREY)]
+        );
+        assert_eq!(lerp_stops(&from, &to, 0.), from.to_vec());
+        assert_eq!(lerp_stops(&from, &to, 1.), to.to_vec());
+    }
+
+    #[test]
+    fn lerp_stops_with_different_counts_blend_at_both_offsets() {
+        let from = [
+            GradientStop::new(0., BLACK),
+            GradientStop::new(1., Color::WHITE),
+        ];
+        let to = [
+            GradientStop::new(0., Color::WHITE),
+            GradientStop::new(0.5, BLACK),
+            GradientStop::new(1., Color::WHITE),
+        ];
+        assert_eq!(
+            lerp_stops(&from, &to, 0.5),
+            vec![
+                GradientStop::new(0., Color::new(0.5, 0.5, 0.5, 1.)),
+                GradientStop::new(0.5, Color::new(0.25, 0.25, 0.25, 1.)),
+                GradientStop::new(1., Color::WHITE),
+            ]
+        );
+    }
+
+    #[test]
+    fn solid_fill_lerps_to_a_gradient() {
+        let to = gradient(&[(0., Color::WHITE), (1., BLACK)]);
+        assert_eq!(
+            Fill::Solid(BLACK).lerp(&to, 0.5),
+            gradient(&[(0., GREY), (1., BLACK)])
+        );
+
+        let radial = Fill::radial([0.; 2], 1., vec![GradientStop::new(0., BLACK)]);
+        assert_eq!(to.lerp(&radial, 0.25), to);
+        assert_eq!(to.lerp(&radial, 0.75), radial);
+    }
+}
@@ -0,0 +1,139 @@
+use std::ffi::{c_void, CStr};
+
+use GL::HasContext;
+
+use super::{GCX, GL};
+
+/// `GL_OVERLAY_KHR` from `KHR_blend_equation_advanced`, glow does not export it
+const OVERLAY_KHR: u32 = 0x9296;
+/// `GL_BLEND_ADVANCED_COHERENT_KHR` from `KHR_blend_equation_advanced_coherent`
+const BLEND_ADVANCED_COHERENT_KHR: u32 = 0x9285;
+
+/// Every node outputs premultiplied alpha, so every mode here expects `color.rgb` to be already multiplied by `color.a`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// `src + dst * (1 - src.a)`
+    #[default]
+    Normal,
+    /// `src + dst`
+    Add,
+    /// `src * dst + dst * (1 - src.a)`
+    Multiply,
+    /// `src + dst * (1 - src)`
+    Screen,
+    /// Needs `KHR_blend_equation_advanced` or GLES 3.2, if is not supported will fallback to `Normal`
+    /// without `KHR_blend_equation_advanced_coherent` it also needs `GCX::with_loader` for the barrier
+    /// that is issued before every draw, so the primitives of one draw should not overlap
+    Overlay,
+}
+
+/// Where the advanced blend equations come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Advanced {
+    Extension,
+    /// GLES 3.2
+    Core,
+}
+
+/// `glBlendBarrier`, glow does not load it
+pub(super) type BlendBarrier = unsafe extern "system" fn();
+
+pub(super) fn supports_advanced(gl: &GL::Context) -> Option<Advanced> {
+    let version = gl.version();
+    if gl
+        .supported_extensions()
+        .contains("GL_KHR_blend_equation_advanced")
+    {
+        Some(Advanced::Extension)
+    } else if version.is_embedded && (version.major, version.minor) >= (3, 2) {
+        Some(Advanced::Core)
+    } else {
+        None
+    }
+}
+
+/// The fragment stage needs to declare that it supports the advanced blend equations
+/// `ShaderBuilder` will insert this after the `#version` line of every fragment shader
+pub(super) fn preamble(advanced: Option<Advanced>) -> &'static str {
+    match advanced {
+        Some(Advanced::Extension) => {
+            "#extension GL_KHR_blend_equation_advanced : enable\nlayout(blend_support_all_equations) out;\n"
+        }
+        Some(Advanced::Core) => "layout(blend_support_all_equations) out;\n",
+        None => "",
+    }
+}
+
+/// The core or the KHR name of the barrier
+pub(super) fn load_barrier(mut loader: impl FnMut(&CStr) -> *const c_void) -> Option<BlendBarrier> {
+    [c"glBlendBarrier", c"glBlendBarrierKHR"]
+        .into_iter()
+        .map(&mut loader)
+        .find(|address| !address.is_null())
+        // SAFETY: both take nothing and return nothing
+        .map(|address| unsafe { std::mem::transmute::<*const c_void, BlendBarrier>(address) })
+}
+
+pub(super) fn supports_advanced_coherent(gl: &GL::Context) -> bool {
+    gl.supported_extensions()
+        .contains("GL_KHR_blend_equation_advanced_coherent")
+}
+
+pub(super) fn enable(gl: &GL::Context, coherent: bool) {
+    unsafe {
+        gl.enable(GL::BLEND);
+        if coherent {
+            gl.enable(BLEND_ADVANCED_COHERENT_KHR);
+        }
+    }
+}
+
+pub(super) fn apply(gl: &GL::Context, mode: BlendMode, advanced: bool) {
+    unsafe {
+        match mode {
+            BlendMode::Normal => {
+                gl.blend_equation(GL::FUNC_ADD);
+                gl.blend_func(GL::ONE, GL::ONE_MINUS_SRC_ALPHA);
+            }
+            BlendMode::Add => {
+                gl.blend_equation(GL::FUNC_ADD);
+                gl.blend_func(GL::ONE, GL::ONE);
+            }
+            BlendMode::Multiply => {
+                gl.blend_equation(GL::FUNC_ADD);
+                gl.blend_func_separate(
+                    GL::DST_COLOR,
+                    GL::ONE_MINUS_SRC_ALPHA,
+                    GL::ONE,
+                    GL::ONE_MINUS_SRC_ALPHA,
+                );
+            }
+            BlendMode::Screen => {
+                gl.blend_equation(GL::FUNC_ADD);
+                gl.blend_func_separate(
+                    GL::ONE,
+                    GL::ONE_MINUS_SRC_COLOR,
+                    GL::ONE,
+                    GL::ONE_MINUS_SRC_ALPHA,
+                );
+            }
+            BlendMode::Overlay if advanced => {
+                gl.blend_equation(OVERLAY_KHR);
+            }
+            BlendMode::Overlay => apply(gl, BlendMode::Normal, advanced),
+        }
+    }
+}
+
+/// Created with `GCX::blend`
+/// When is dropped the previous `BlendMode` will be restored
+pub struct BlendGuard<'a> {
+    pub(super) gcx: &'a GCX,
+    pub(super) previous: BlendMode,
+}
+
+impl<'a> Drop for BlendGuard<'a> {
+    fn drop(&mut self) {
+        self.gcx.set_blend_mode(self.previous);
+    }
+}
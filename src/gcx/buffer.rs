@@ -39,6 +39,17 @@ impl Buffer {
         }
     }
 
+    /// Reallocates the buffer, use this when `data` has a different size
+    pub fn set_data<T: bytemuck::NoUninit>(&mut self, data: &[T], usage: BufferUsage) {
+        let gl = &self.inner.gl;
+        let ty = self.inner.ty as u32;
+        unsafe {
+            gl.bind_buffer(ty, Some(self.inner.buffer));
+            gl.buffer_data_u8_slice(ty, bytemuck::cast_slice(data), usage.bits());
+            gl.bind_buffer(ty, None);
+        }
+    }
+
     pub fn read(&mut self, offset: i32, length: i32, read: impl FnOnce(MapRead)) {
         let gl = &self.inner.gl;
         let ty = self.inner.ty as u32;
@@ -78,6 +89,42 @@ impl Buffer {
         }
     }
 
+    /// Binds a uniform or storage buffer to the `binding` of the blocks in the shaders
+    pub fn bind_base(&self, binding: u32) {
+        self.assert_block();
+        unsafe {
+            self.inner
+                .gl
+                .bind_buffer_base(self.inner.ty as u32, binding, Some(self.inner.buffer));
+        }
+    }
+
+    /// Binds `size` bytes from `offset` like `bind_base`, the offset needs
+    /// the alignment of `GL_UNIFORM_BUFFER_OFFSET_ALIGNMENT` for uniform buffers
+    pub fn bind_range(&self, binding: u32, offset: i32, size: i32) {
+        self.assert_block();
+        unsafe {
+            self.inner.gl.bind_buffer_range(
+                self.inner.ty as u32,
+                binding,
+                Some(self.inner.buffer),
+                offset,
+                size,
+            );
+        }
+    }
+
+    fn assert_block(&self) {
+        assert!(
+            matches!(
+                self.inner.ty,
+                BufferType::UniformBuffer | BufferType::ShaderStorage
+            ),
+            "A {:?} has no binding point",
+            self.inner.ty
+        );
+    }
+
     pub fn ty(&self) -> BufferType {
         self.inner.ty
     }
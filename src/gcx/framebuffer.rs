@@ -0,0 +1,180 @@
+use std::{num::NonZeroU32, rc::Rc};
+
+use GL::HasContext;
+
+use super::{
+    texture::{
+        Format, InternalFormat, Texture, TextureFilter, TextureInner, TextureTarget, TextureType,
+    },
+    DataType, GL,
+};
+
+/// A target to draw in to that is not the screen, the color is in a RGBA8 texture
+#[derive(Clone)]
+pub struct Framebuffer {
+    pub(super) inner: Rc<FramebufferInner>,
+}
+
+impl core::fmt::Debug for Framebuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl Framebuffer {
+    pub fn texture(&self) -> &Texture {
+        &self.inner.texture
+    }
+
+    pub fn width(&self) -> i32 {
+        self.inner.texture.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.inner.texture.height()
+    }
+
+    pub fn has_stencil(&self) -> bool {
+        self.inner.stencil.is_some()
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct FramebufferInner {
+    pub(super) gl: Rc<GL::Context>,
+    pub(super) framebuffer: GL::Framebuffer,
+    pub(super) texture: Texture,
+    pub(super) stencil: Option<GL::Renderbuffer>,
+}
+
+impl Drop for FramebufferInner {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.delete_framebuffer(self.framebuffer);
+            if let Some(stencil) = self.stencil {
+                self.gl.delete_renderbuffer(stencil);
+            }
+        }
+    }
+}
+
+/// How the stencil buffer is used when drawing
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Stencil {
+    #[default]
+    Off,
+    /// Writes the value where is drawn, the color is not written
+    Write(u8),
+    /// Draws only where the stencil has the value
+    Equal(u8),
+    /// Draws only where the stencil does not have the value
+    NotEqual(u8),
+}
+
+pub(super) fn create(gl: &Rc<GL::Context>, width: i32, height: i32, stencil: bool) -> Framebuffer {
+    unsafe {
+        let row = gl.create_texture().unwrap();
+        gl.bind_texture(GL::TEXTURE_2D, Some(row));
+        gl.tex_image_2d(
+            GL::TEXTURE_2D,
+            0,
+            GL::RGBA8 as i32,
+            width,
+            height,
+            0,
+            GL::RGBA,
+            GL::UNSIGNED_BYTE,
+            None,
+        );
+        gl.bind_texture(GL::TEXTURE_2D, None);
+        let texture = Texture {
+            inner: Rc::new(TextureInner {
+                gl: gl.clone(),
+                row,
+                format: Format::RGBA,
+                internal_format: InternalFormat::RGBA8,
+                ty: TextureType::Tex2D,
+                data_ty: DataType::U8,
+                target: TextureTarget::Tex2D,
+                width,
+                height,
+                depth: 1,
+            }),
+        };
+        texture.set_filter(TextureFilter::Linear);
+
+        let framebuffer = gl.create_framebuffer().unwrap();
+        let previous = bound(gl);
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(framebuffer));
+        gl.framebuffer_texture_2d(
+            GL::FRAMEBUFFER,
+            GL::COLOR_ATTACHMENT0,
+            GL::TEXTURE_2D,
+            Some(row),
+            0,
+        );
+
+        let stencil = stencil.then(|| {
+            let renderbuffer = gl.create_renderbuffer().unwrap();
+            gl.bind_renderbuffer(GL::RENDERBUFFER, Some(renderbuffer));
+            gl.renderbuffer_storage(GL::RENDERBUFFER, GL::DEPTH24_STENCIL8, width, height);
+            gl.bind_renderbuffer(GL::RENDERBUFFER, None);
+            gl.framebuffer_renderbuffer(
+                GL::FRAMEBUFFER,
+                GL::DEPTH_STENCIL_ATTACHMENT,
+                GL::RENDERBUFFER,
+                Some(renderbuffer),
+            );
+            renderbuffer
+        });
+
+        let status = gl.check_framebuffer_status(GL::FRAMEBUFFER);
+        if status != GL::FRAMEBUFFER_COMPLETE {
+            eprintln!("The framebuffer is not complete: 0x{status:x}");
+        }
+        gl.bind_framebuffer(GL::FRAMEBUFFER, previous);
+
+        Framebuffer {
+            inner: Rc::new(FramebufferInner {
+                gl: gl.clone(),
+                framebuffer,
+                texture,
+                stencil,
+            }),
+        }
+    }
+}
+
+/// The framebuffer that is bound now, `None` is the default one
+pub(super) fn bound(gl: &GL::Context) -> Option<GL::Framebuffer> {
+    let id = unsafe { gl.get_parameter_i32(GL::FRAMEBUFFER_BINDING) };
+    NonZeroU32::new(id as u32).map(GL::NativeFramebuffer)
+}
+
+pub(super) fn apply_stencil(gl: &GL::Context, stencil: Stencil) {
+    unsafe {
+        match stencil {
+            Stencil::Off => {
+                gl.disable(GL::STENCIL_TEST);
+                gl.color_mask(true, true, true, true);
+            }
+            Stencil::Write(value) => {
+                gl.enable(GL::STENCIL_TEST);
+                gl.stencil_func(GL::ALWAYS, value as i32, 0xff);
+                gl.stencil_op(GL::KEEP, GL::KEEP, GL::REPLACE);
+                gl.color_mask(false, false, false, false);
+            }
+            Stencil::Equal(value) | Stencil::NotEqual(value) => {
+                let func = if matches!(stencil, Stencil::Equal(_)) {
+                    GL::EQUAL
+                } else {
+                    GL::NOTEQUAL
+                };
+                gl.enable(GL::STENCIL_TEST);
+                gl.stencil_func(func, value as i32, 0xff);
+                gl.stencil_op(GL::KEEP, GL::KEEP, GL::KEEP);
+                gl.color_mask(true, true, true, true);
+            }
+        }
+    }
+}
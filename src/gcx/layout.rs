@@ -0,0 +1,131 @@
+//! Types with the layout of std140 and std430 blocks, for the Pod structs of uniform and storage buffers
+//!
+//! A `float`, `vec2` and `vec4` are the same as `f32`, `[f32; 2]` and `[f32; 4]`
+//! if the fields are ordered from the biggest alignment, the others need these types.
+//! `Vec3` and `Mat3` are the same in both layouts, `ArrayFloat` and `ArrayVec2` are only for std140
+//! because std430 packs the arrays of a `float` and a `vec2` like `[f32; N]` and `[[f32; 2]; N]`.
+//! `Shader::check_uniform_block` only compares the size of a struct with the one of the block
+
+/// A `vec3`, aligned like a `vec4`
+/// a `float` right after it in the block is in its padding, so that needs `value` and a `f32` instead
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Vec3 {
+    pub value: [f32; 3],
+    _padding: f32,
+}
+
+impl Vec3 {
+    pub const fn new(value: [f32; 3]) -> Self {
+        Self {
+            value,
+            _padding: 0.,
+        }
+    }
+}
+
+impl From<[f32; 3]> for Vec3 {
+    fn from(value: [f32; 3]) -> Self {
+        Self::new(value)
+    }
+}
+
+/// A `mat3`, every column is a `Vec3`
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Mat3 {
+    pub columns: [Vec3; 3],
+}
+
+/// From column major order, like the `mat3` of `Shader::set_uniform`
+impl From<[f32; 9]> for Mat3 {
+    fn from(value: [f32; 9]) -> Self {
+        Self {
+            columns: [0, 1, 2]
+                .map(|i| Vec3::new([value[i * 3], value[i * 3 + 1], value[i * 3 + 2]])),
+        }
+    }
+}
+
+/// A `float` in an array of a std140 block, where every element takes `16` bytes
+/// in a std430 block the array is a `[f32; N]`
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ArrayFloat {
+    pub value: f32,
+    _padding: [f32; 3],
+}
+
+impl From<f32> for ArrayFloat {
+    fn from(value: f32) -> Self {
+        Self {
+            value,
+            _padding: [0.; 3],
+        }
+    }
+}
+
+/// A `vec2` in an array of a std140 block, where every element takes `16` bytes
+/// in a std430 block the array is a `[[f32; 2]; N]`
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ArrayVec2 {
+    pub value: [f32; 2],
+    _padding: [f32; 2],
+}
+
+impl From<[f32; 2]> for ArrayVec2 {
+    fn from(value: [f32; 2]) -> Self {
+        Self {
+            value,
+            _padding: [0.; 2],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::{offset_of, size_of};
+
+    use super::*;
+
+    #[test]
+    fn std140_sizes() {
+        assert_eq!(size_of::<Vec3>(), 16);
+        assert_eq!(size_of::<Mat3>(), 48);
+        assert_eq!(size_of::<ArrayFloat>(), 16);
+        assert_eq!(size_of::<ArrayVec2>(), 16);
+        assert_eq!(size_of::<[ArrayFloat; 4]>(), 64);
+        assert_eq!(size_of::<[ArrayVec2; 4]>(), 64);
+    }
+
+    #[test]
+    fn std140_block_offsets() {
+        // layout(std140) uniform Block { mat3 a; vec3 b; float c; float d[2]; vec2 e[2]; };
+        #[repr(C)]
+        struct Block {
+            a: Mat3,
+            b: [f32; 3],
+            c: f32,
+            d: [ArrayFloat; 2],
+            e: [ArrayVec2; 2],
+        }
+        assert_eq!(offset_of!(Block, b), 48);
+        assert_eq!(offset_of!(Block, c), 60);
+        assert_eq!(offset_of!(Block, d), 64);
+        assert_eq!(offset_of!(Block, e), 96);
+        assert_eq!(size_of::<Block>(), 128);
+    }
+
+    #[test]
+    fn mat3_from_columns() {
+        let mat = Mat3::from([1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+        assert_eq!(mat.columns[0].value, [1., 2., 3.]);
+        assert_eq!(mat.columns[1].value, [4., 5., 6.]);
+        assert_eq!(mat.columns[2].value, [7., 8., 9.]);
+        assert_eq!(
+            bytemuck::cast::<Mat3, [f32; 12]>(mat)[4..8],
+            [4., 5., 6., 0.]
+        );
+    }
+}
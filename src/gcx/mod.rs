@@ -1,9 +1,16 @@
+pub mod blend;
 pub mod buffer;
+pub mod framebuffer;
+pub mod layout;
 pub mod shader;
 pub mod texture;
 pub mod vertex_array;
 
-use std::rc::Rc;
+use std::{
+    cell::Cell,
+    ffi::{c_void, CStr},
+    rc::Rc,
+};
 
 pub use glow as GL;
 use glow::HasContext;
@@ -11,10 +18,12 @@ use glow::HasContext;
 use crate::color::Color;
 
 use self::{
+    blend::{Advanced, BlendBarrier, BlendGuard, BlendMode},
     buffer::{Buffer, BufferInner, BufferType, BufferUsage},
+    framebuffer::{Framebuffer, Stencil},
     shader::{Shader, ShaderBuilder},
     texture::{Format, InternalFormat, Texture, TextureInner, TextureTarget},
-    vertex_array::{Fields, VertexArray, VertexArrayBuilder},
+    vertex_array::{Fields, IndexType, VertexArray, VertexArrayBuilder},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -52,11 +61,65 @@ pub enum PrimitiveType {
 #[derive(Debug, Clone)]
 pub struct GCX {
     pub gl: Rc<glow::Context>,
+
+    blend_mode: Rc<Cell<BlendMode>>,
+    advanced_blend: Option<Advanced>,
+    /// `Some` if the advanced blend equations are not coherent
+    blend_barrier: Option<BlendBarrier>,
+    viewport: Rc<Cell<[i32; 4]>>,
 }
 
 impl GCX {
+    /// This will enable premultiplied alpha blending with `BlendMode::Normal`
+    /// the advanced blend modes are only used if they are coherent, see `with_loader`
     pub fn new(gl: Rc<GL::Context>) -> Self {
-        Self { gl }
+        Self::create(gl, None)
+    }
+
+    /// Like `new` but loads the blend barrier with `loader`,
+    /// so the advanced blend modes can be used when they are not coherent
+    pub fn with_loader(gl: Rc<GL::Context>, loader: impl FnMut(&CStr) -> *const c_void) -> Self {
+        Self::create(gl, Some(blend::load_barrier(loader)).flatten())
+    }
+
+    fn create(gl: Rc<GL::Context>, barrier: Option<BlendBarrier>) -> Self {
+        let coherent = blend::supports_advanced_coherent(&gl);
+        // overlapping draws without a barrier or coherence are undefined
+        let advanced_blend =
+            blend::supports_advanced(&gl).filter(|_| coherent || barrier.is_some());
+        blend::enable(&gl, advanced_blend.is_some() && coherent);
+        blend::apply(&gl, BlendMode::Normal, advanced_blend.is_some());
+
+        let mut viewport = [0; 4];
+        unsafe { gl.get_parameter_i32_slice(GL::VIEWPORT, &mut viewport) };
+
+        Self {
+            gl,
+            blend_mode: Rc::new(Cell::new(BlendMode::Normal)),
+            advanced_blend,
+            blend_barrier: barrier.filter(|_| advanced_blend.is_some() && !coherent),
+            viewport: Rc::new(Cell::new(viewport)),
+        }
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode.get()
+    }
+
+    pub fn set_blend_mode(&self, mode: BlendMode) {
+        if self.blend_mode.replace(mode) != mode {
+            blend::apply(&self.gl, mode, self.advanced_blend.is_some());
+        }
+    }
+
+    /// Sets the `BlendMode` until the guard is dropped
+    pub fn blend(&self, mode: BlendMode) -> BlendGuard<'_> {
+        let previous = self.blend_mode();
+        self.set_blend_mode(mode);
+        BlendGuard {
+            gcx: self,
+            previous,
+        }
     }
 
     pub fn clear_color(&self, color: impl Into<Color>) {
@@ -71,9 +134,45 @@ impl GCX {
     }
 
     pub fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.viewport.set([x, y, width, height]);
         unsafe { self.gl.viewport(x, y, width, height) }
     }
 
+    /// The width and height of the last `viewport`
+    pub fn viewport_size(&self) -> [i32; 2] {
+        let [_, _, width, height] = self.viewport.get();
+        [width, height]
+    }
+
+    /// An offscreen target with a RGBA8 color texture, `stencil` adds a stencil buffer
+    pub fn create_framebuffer(&self, width: i32, height: i32, stencil: bool) -> Framebuffer {
+        framebuffer::create(&self.gl, width, height, stencil)
+    }
+
+    /// Draws in to the framebuffer with a viewport of the same size
+    /// the last framebuffer and viewport are used again after
+    pub fn use_framebuffer<O>(&self, framebuffer: &Framebuffer, run: impl FnOnce(&GCX) -> O) -> O {
+        let previous = framebuffer::bound(&self.gl);
+        let [x, y, width, height] = self.viewport.get();
+
+        unsafe {
+            self.gl
+                .bind_framebuffer(GL::FRAMEBUFFER, Some(framebuffer.inner.framebuffer));
+        }
+        self.viewport(0, 0, framebuffer.width(), framebuffer.height());
+
+        let out = run(self);
+
+        unsafe { self.gl.bind_framebuffer(GL::FRAMEBUFFER, previous) }
+        self.viewport(x, y, width, height);
+        out
+    }
+
+    /// The framebuffer needs a stencil buffer for this to do something
+    pub fn set_stencil(&self, stencil: Stencil) {
+        framebuffer::apply_stencil(&self.gl, stencil);
+    }
+
     pub fn use_shader<O>(&self, shader: &Shader, run: impl FnOnce(GCXShaded) -> O) {
         unsafe {
             self.gl.use_program(Some(shader.program));
@@ -94,6 +193,9 @@ impl GCX {
         VertexArrayBuilder {
             array_buffer,
             attribs: Vec::new(),
+            divisor: 0,
+            buffers: Vec::new(),
+            index_buffer: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -161,12 +263,66 @@ impl GCX {
                 ty,
                 width,
                 height,
+                depth: 1,
                 target,
                 data_ty,
             }),
         }
     }
 
+    /// A `TEXTURE_3D` with linear filtering and without mipmaps, the data goes along x then y then z
+    pub fn create_texture_3d<T: bytemuck::NoUninit>(
+        &self,
+        internal_format: InternalFormat,
+        [width, height, depth]: [i32; 3],
+        format: Format,
+        data_ty: DataType,
+        data: &[T],
+    ) -> Texture {
+        let gl = &self.gl;
+        let row;
+        unsafe {
+            row = gl.create_texture().unwrap();
+
+            gl.bind_texture(GL::TEXTURE_3D, Some(row));
+            gl.tex_image_3d(
+                GL::TEXTURE_3D,
+                0,
+                internal_format as i32,
+                width,
+                height,
+                depth,
+                0,
+                format as u32,
+                data_ty as u32,
+                Some(bytemuck::cast_slice(data)),
+            );
+            gl.bind_texture(GL::TEXTURE_3D, None);
+        }
+
+        let texture = Texture {
+            inner: Rc::new(TextureInner {
+                gl: gl.clone(),
+                row,
+                format,
+                internal_format,
+                ty: texture::TextureType::Tex3D,
+                width,
+                height,
+                depth,
+                target: TextureTarget::Tex3D,
+                data_ty,
+            }),
+        };
+        texture.set_filter(texture::TextureFilter::Linear);
+        texture
+    }
+
+    /// The biggest width and height of a texture, at least `2048`
+    pub fn max_texture_size(&self) -> i32 {
+        unsafe { self.gl.get_parameter_i32(GL::MAX_TEXTURE_SIZE) }
+    }
+
     pub fn flush(&self) {
         unsafe {
             self.gl.flush();
@@ -193,13 +349,18 @@ impl<'a> std::ops::Deref for GCXShaded<'a> {
 impl<'a> GCXShaded<'a> {
     pub fn use_vertex_array<O>(&self, va: &VertexArray, run: impl FnOnce(GCXFinal) -> O) {
         unsafe { self.gl.bind_vertex_array(Some(va.vao)) }
-        run(GCXFinal { gcx: self });
+        run(GCXFinal {
+            gcx: self,
+            index_type: va.index_type(),
+        });
         unsafe { self.gl.bind_vertex_array(None) }
     }
 }
 
 pub struct GCXFinal<'a> {
     gcx: &'a GCXShaded<'a>,
+    /// Of the index buffer of the vertex array
+    index_type: Option<IndexType>,
 }
 
 impl<'a> std::ops::Deref for GCXFinal<'a> {
@@ -211,22 +372,57 @@ impl<'a> std::ops::Deref for GCXFinal<'a> {
 }
 
 impl<'a> GCXFinal<'a> {
+    /// Overlapping draws with an advanced blend equation need it when they are not coherent
+    fn blend_barrier(&self) {
+        if let (Some(barrier), BlendMode::Overlay) = (self.gcx.blend_barrier, self.blend_mode()) {
+            unsafe { barrier() }
+        }
+    }
+
     pub fn draw_arrays(&self, primitive: PrimitiveType, first: i32, count: i32) {
+        self.blend_barrier();
         unsafe { self.gl.draw_arrays(primitive as u32, first, count) }
     }
 
-    pub fn draw_arrays_instanced(&self, primitive: PrimitiveType, first: i32, count: i32) {
+    /// Draws the `count` vertices `instance_count` times
+    pub fn draw_arrays_instanced(
+        &self,
+        primitive: PrimitiveType,
+        first: i32,
+        count: i32,
+        instance_count: i32,
+    ) {
+        self.blend_barrier();
         unsafe {
             self.gl
-                .draw_arrays_instanced(primitive as u32, first, count, count - first)
+                .draw_arrays_instanced(primitive as u32, first, count, instance_count)
         }
     }
 
-    /// You should have GL_ELEMENT_ARRAY_BUFFER
+    /// Draws the first `count` indices of the index buffer of the vertex array
     pub fn draw_elements(&self, primitive: PrimitiveType, count: i32) {
+        let ty = self.index_type();
+        self.blend_barrier();
+        unsafe { self.gl.draw_elements(primitive as u32, count, ty as u32, 0) }
+    }
+
+    /// Draws the first `count` indices of the index buffer `instance_count` times
+    pub fn draw_elements_instanced(
+        &self,
+        primitive: PrimitiveType,
+        count: i32,
+        instance_count: i32,
+    ) {
+        let ty = self.index_type();
+        self.blend_barrier();
         unsafe {
             self.gl
-                .draw_elements(primitive as u32, count, GL::UNSIGNED_INT, 0)
+                .draw_elements_instanced(primitive as u32, count, ty as u32, 0, instance_count)
         }
     }
+
+    fn index_type(&self) -> IndexType {
+        self.index_type
+            .expect("The vertex array has no index buffer")
+    }
 }
@@ -3,7 +3,7 @@ use std::rc::Rc;
 use after_drop::AfterDropBoxed;
 use GL::HasContext;
 
-use super::{GCX, GL};
+use super::{blend, GCX, GL};
 
 #[derive(Debug)]
 pub struct Shader {
@@ -87,6 +87,13 @@ impl SetUniform for (f32, f32, f32, f32) {
     }
 }
 
+/// A `mat3` in column major order
+impl SetUniform for [f32; 9] {
+    fn set_uniform(self, gl: &GL::Context, location: &GL::NativeUniformLocation) {
+        unsafe { gl.uniform_matrix_3_f32_slice(Some(location), false, &self) };
+    }
+}
+
 // impl SetUniform for &[i32] {
 //     fn set_uniform(self, gl: &GL::Context, location: &GL::NativeUniformLocation) {
 //         unsafe { gl.uniform }
@@ -104,6 +111,51 @@ impl Shader {
         }
         Ok(())
     }
+
+    /// The uniform block `name` reads the buffer bound with `Buffer::bind_base` at `binding`
+    ///
+    /// storage blocks can not be bound from here in GLES, they need `layout(std430, binding = N)`
+    pub fn bind_uniform_block(&self, name: &str, binding: u32) -> Result<(), ShaderError> {
+        let index = self.uniform_block_index(name)?;
+        unsafe {
+            self.gl.uniform_block_binding(self.program, index, binding);
+        }
+        Ok(())
+    }
+
+    /// The size in bytes of the uniform block `name`
+    pub fn uniform_block_size(&self, name: &str) -> Result<usize, ShaderError> {
+        let index = self.uniform_block_index(name)?;
+        let size = unsafe {
+            self.gl.get_active_uniform_block_parameter_i32(
+                self.program,
+                index,
+                GL::UNIFORM_BLOCK_DATA_SIZE,
+            )
+        };
+        Ok(size as usize)
+    }
+
+    /// If `T` has the size of the uniform block `name`, a missing padding makes it smaller
+    ///
+    /// only the whole size is compared, the offsets of the members are not checked
+    /// so members in the wrong order or a padding in the wrong place with the same size are not found
+    pub fn check_uniform_block<T: bytemuck::Pod>(&self, name: &str) -> Result<(), ShaderError> {
+        let size = self.uniform_block_size(name)?;
+        if size != std::mem::size_of::<T>() {
+            return Err(ShaderError::BlockSize(
+                name.to_string(),
+                size,
+                std::mem::size_of::<T>(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn uniform_block_index(&self, name: &str) -> Result<u32, ShaderError> {
+        unsafe { self.gl.get_uniform_block_index(self.program, name) }
+            .ok_or_else(|| ShaderError::MissingBlock(name.to_string()))
+    }
 }
 
 impl Drop for Shader {
@@ -147,6 +199,10 @@ pub enum ShaderError {
     CreateShader(String),
     CompileError(ShaderStage, String),
     LinkError(String),
+    /// The shader has no active uniform block with the name
+    MissingBlock(String),
+    /// The name of the block, its size and the size of the type
+    BlockSize(String, usize, usize),
 }
 
 impl ShaderBuilder {
@@ -163,6 +219,7 @@ impl ShaderBuilder {
         self
     }
 
+    /// The advanced blend preamble is inserted after the `#version` line when it is built
     pub fn fragment(mut self, src: impl Into<String>) -> Self {
         self.fragment = Some(ShaderFragment { src: src.into() });
         self
@@ -210,7 +267,18 @@ impl ShaderBuilder {
             }
 
             if let Some(fragment_shader) = &self.fragment {
-                let shader = create_shader(gl, ShaderStage::Fragment, &fragment_shader.src)?;
+                // the same advanced blend equations that the context uses
+                let mut src = fragment_shader.src.clone();
+                let at = src
+                    .find("#version")
+                    .map(|version| {
+                        src[version..]
+                            .find('\n')
+                            .map_or(src.len(), |end| version + end + 1)
+                    })
+                    .unwrap_or(0);
+                src.insert_str(at, blend::preamble(gcx.advanced_blend));
+                let shader = create_shader(gl, ShaderStage::Fragment, &src)?;
                 gl.attach_shader(program, shader);
 
                 defers.push(AfterDropBoxed::new(move || {
@@ -90,6 +90,14 @@ pub enum TextureTarget {
     CubeMapNegativeZ = GL::TEXTURE_CUBE_MAP_NEGATIVE_Z,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Linear,
+    /// Linear between the texels and the mipmap levels
+    Trilinear,
+}
+
 #[derive(Clone)]
 pub struct Texture {
     pub(super) inner: Rc<TextureInner>,
@@ -122,6 +130,10 @@ impl Texture {
         self.inner.height
     }
 
+    pub fn depth(&self) -> i32 {
+        self.inner.depth
+    }
+
     pub fn ty(&self) -> TextureType {
         self.inner.ty
     }
@@ -142,22 +154,79 @@ impl Texture {
 
         unsafe {
             gl.bind_texture(target as u32, Some(row));
-            gl.tex_image_2d(
-                target as u32,
-                level,
-                internal_format as i32,
-                width,
-                height,
-                0,
-                format as u32,
-                data_ty as u32,
-                Some(bytemuck::cast_slice(data)),
-            );
+            match self.ty() {
+                TextureType::Tex2D => gl.tex_image_2d(
+                    target as u32,
+                    level,
+                    internal_format as i32,
+                    width,
+                    height,
+                    0,
+                    format as u32,
+                    data_ty as u32,
+                    Some(bytemuck::cast_slice(data)),
+                ),
+                TextureType::Tex3D => gl.tex_image_3d(
+                    target as u32,
+                    level,
+                    internal_format as i32,
+                    width,
+                    height,
+                    self.depth(),
+                    0,
+                    format as u32,
+                    data_ty as u32,
+                    Some(bytemuck::cast_slice(data)),
+                ),
+            }
             gl.generate_mipmap(target as u32);
             gl.bind_texture(target as u32, None);
         }
     }
 
+    /// Replaces the `rows` from `y` of the first level of a 2D texture, the mipmaps are not updated
+    pub fn update_rows<T: bytemuck::NoUninit>(&self, y: i32, rows: i32, data: &[T]) {
+        let gl = &self.inner.gl;
+        let target = self.target() as u32;
+
+        unsafe {
+            gl.bind_texture(target, Some(self.inner.row));
+            gl.tex_sub_image_2d(
+                target,
+                0,
+                0,
+                y,
+                self.width(),
+                rows,
+                self.format() as u32,
+                self.data_ty() as u32,
+                GL::PixelUnpackData::Slice(bytemuck::cast_slice(data)),
+            );
+            gl.bind_texture(target, None);
+        }
+    }
+
+    /// Sets the min and mag filter and clamps to the edge, `Nearest` and `Linear` will not sample the mipmaps
+    pub fn set_filter(&self, filter: TextureFilter) {
+        let (min, mag) = match filter {
+            TextureFilter::Nearest => (GL::NEAREST, GL::NEAREST),
+            TextureFilter::Linear => (GL::LINEAR, GL::LINEAR),
+            TextureFilter::Trilinear => (GL::LINEAR_MIPMAP_LINEAR, GL::LINEAR),
+        };
+        let gl = &self.inner.gl;
+        let target = self.target() as u32;
+
+        unsafe {
+            gl.bind_texture(target, Some(self.inner.row));
+            gl.tex_parameter_i32(target, GL::TEXTURE_MIN_FILTER, min as i32);
+            gl.tex_parameter_i32(target, GL::TEXTURE_MAG_FILTER, mag as i32);
+            gl.tex_parameter_i32(target, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+            gl.tex_parameter_i32(target, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+            gl.tex_parameter_i32(target, GL::TEXTURE_WRAP_R, GL::CLAMP_TO_EDGE as i32);
+            gl.bind_texture(target, None);
+        }
+    }
+
     pub fn activate(&self, unit: u32) {
         unsafe {
             self.inner.gl.active_texture(GL::TEXTURE0 + unit);
@@ -179,6 +248,8 @@ pub(super) struct TextureInner {
     pub(super) target: TextureTarget,
     pub(super) width: i32,
     pub(super) height: i32,
+    /// `1` if it is not a 3D texture
+    pub(super) depth: i32,
 }
 
 impl Drop for TextureInner {
@@ -1,7 +1,7 @@
 use GL::HasContext;
 
 use super::{
-    buffer::{Buffer, BufferType},
+    buffer::{Buffer, BufferType, BufferUsage},
     GCX, GL,
 };
 use std::rc::Rc;
@@ -13,6 +13,63 @@ pub struct VertexArray {
 
     // pub textures: Vec<Texture>,
     pub array_buffer: Buffer,
+    /// The buffers of `add_array_buffer` in the order they were added
+    pub buffers: Vec<Buffer>,
+    /// The indices of `draw_elements`
+    pub index_buffer: Option<Buffer>,
+    pub(super) index_type: IndexType,
+    /// The pointers of `array_buffer`, for `set_first`
+    pub(super) attribs: Vec<AttribPointer>,
+}
+
+impl VertexArray {
+    /// Replaces the indices, they can have another type than before
+    /// the vertex array that is used is unbound, so do not call it while another one is used
+    pub fn set_indices<I: Index>(&mut self, data: &[I], usage: BufferUsage) {
+        let Some(buffer) = &mut self.index_buffer else {
+            panic!("The vertex array has no index buffer")
+        };
+        // the element array buffer is a part of the vertex array that is bound,
+        // the upload would unbind it from that one
+        unsafe { self.gl.bind_vertex_array(None) };
+        buffer.set_data(data, usage);
+        self.index_type = I::TYPE;
+    }
+
+    pub fn index_type(&self) -> Option<IndexType> {
+        self.index_buffer.as_ref().map(|_| self.index_type)
+    }
+
+    /// Moves the attributes of `array_buffer` to start at the element `first`,
+    /// like the base instance of a draw that GLES does not have
+    /// do not call it while another vertex array is used
+    pub fn set_first(&self, first: i32) {
+        unsafe {
+            self.gl.bind_vertex_array(Some(self.vao));
+            self.array_buffer.bind();
+            for (index, attrib) in self.attribs.iter().enumerate() {
+                pointer(&self.gl, index as u32, attrib, first);
+            }
+            self.gl.bind_vertex_array(None);
+        }
+    }
+}
+
+/// Sets the attribute at `index` to the element `first` of the array buffer that is bound
+unsafe fn pointer(gl: &glow::Context, index: u32, attrib: &AttribPointer, first: i32) {
+    let offset = attrib.offset + first * attrib.stride;
+    if attrib.ty.is_integer() {
+        gl.vertex_attrib_pointer_i32(index, attrib.size, attrib.ty as u32, attrib.stride, offset);
+    } else {
+        gl.vertex_attrib_pointer_f32(
+            index,
+            attrib.size,
+            attrib.ty as u32,
+            attrib.normalized,
+            attrib.stride,
+            offset,
+        );
+    }
 }
 
 impl Drop for VertexArray {
@@ -27,6 +84,41 @@ impl Drop for VertexArray {
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub enum DataType {
     F32 = GL::FLOAT,
+    I32 = GL::INT,
+    U32 = GL::UNSIGNED_INT,
+}
+
+impl DataType {
+    /// Integer attributes are `int` or `uint` in the shader, not converted to `float`
+    pub fn is_integer(&self) -> bool {
+        matches!(self, DataType::I32 | DataType::U32)
+    }
+}
+
+/// The type of the indices in an element array buffer
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexType {
+    U8 = GL::UNSIGNED_BYTE,
+    U16 = GL::UNSIGNED_SHORT,
+    U32 = GL::UNSIGNED_INT,
+}
+
+/// A type that the indices of an element array buffer can have
+pub trait Index: bytemuck::NoUninit {
+    const TYPE: IndexType;
+}
+
+impl Index for u8 {
+    const TYPE: IndexType = IndexType::U8;
+}
+
+impl Index for u16 {
+    const TYPE: IndexType = IndexType::U16;
+}
+
+impl Index for u32 {
+    const TYPE: IndexType = IndexType::U32;
 }
 
 pub struct Field {
@@ -35,6 +127,8 @@ pub struct Field {
     pub size: i32,
     pub gl_size: i32,
     pub base: DataType,
+    /// `0` advances once per vertex, `n` once every `n` instances
+    pub divisor: u32,
 }
 
 impl Field {
@@ -45,7 +139,37 @@ impl Field {
             size: core::mem::size_of::<T>() as i32,
             gl_size: T::size(),
             base: T::base(),
+            divisor: 0,
+        }
+    }
+
+    /// Advances once every `divisor` instances
+    pub fn with_divisor(mut self, divisor: u32) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
+    /// The pointers of tightly packed `fields`, a field without a divisor gets `divisor`
+    fn pointers(fields: Vec<Field>, divisor: u32) -> Vec<AttribPointer> {
+        let mut pointers = Vec::with_capacity(fields.len());
+        let mut stride = 0;
+        for field in fields {
+            pointers.push(
+                AttribPointer::new(field.base, field.gl_size, 0, false, stride).with_divisor(
+                    if field.divisor != 0 {
+                        field.divisor
+                    } else {
+                        divisor
+                    },
+                ),
+            );
+            stride += field.size;
+        }
+
+        for pointer in pointers.iter_mut() {
+            pointer.stride = stride;
         }
+        pointers
     }
 }
 
@@ -61,6 +185,9 @@ impl<const SIZE: usize> Fields for [f32; SIZE] {
     }
 }
 
+/// The attributes of a vertex, the field at `i` is read by `layout(location = i)` in the vertex shader
+/// the fields of the buffers of `VertexArrayBuilder::add_array_buffer` come after the ones before them
+/// the names are only for debugging, the shader can use others
 pub trait Fields {
     fn fields() -> Vec<Field>;
 }
@@ -90,12 +217,55 @@ impl<const SIZE: usize> GLType for [f32; SIZE] {
     }
 }
 
+impl GLType for i32 {
+    fn base() -> DataType {
+        DataType::I32
+    }
+
+    fn size() -> i32 {
+        1
+    }
+}
+
+impl<const SIZE: usize> GLType for [i32; SIZE] {
+    fn base() -> DataType {
+        DataType::I32
+    }
+
+    fn size() -> i32 {
+        SIZE as i32
+    }
+}
+
+impl GLType for u32 {
+    fn base() -> DataType {
+        DataType::U32
+    }
+
+    fn size() -> i32 {
+        1
+    }
+}
+
+impl<const SIZE: usize> GLType for [u32; SIZE] {
+    fn base() -> DataType {
+        DataType::U32
+    }
+
+    fn size() -> i32 {
+        SIZE as i32
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct AttribPointer {
     pub ty: DataType,
     pub size: i32,
     pub normalized: bool,
     pub stride: i32,
     pub offset: i32,
+    /// `0` advances once per vertex, `n` once every `n` instances
+    pub divisor: u32,
 }
 
 impl AttribPointer {
@@ -106,9 +276,15 @@ impl AttribPointer {
             normalized,
             stride,
             offset,
+            divisor: 0,
         }
     }
 
+    pub fn with_divisor(mut self, divisor: u32) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
     pub fn stride(&self) -> i32 {
         self.stride
     }
@@ -118,27 +294,70 @@ pub struct VertexArrayBuilder<T: Fields> {
     pub(super) array_buffer: Buffer,
 
     pub(super) attribs: Vec<AttribPointer>,
+    pub(super) divisor: u32,
+    /// The other array buffers with their attributes
+    pub(super) buffers: Vec<(Buffer, Vec<AttribPointer>)>,
+    pub(super) index_buffer: Option<(Buffer, IndexType)>,
     pub(super) _marker: core::marker::PhantomData<T>,
 }
 
 impl<T: Fields> VertexArrayBuilder<T> {
+    /// Replaces the array buffer or sets the index buffer with `u32` indices
     pub fn add_buffer(mut self, buffer: Buffer) -> Self {
         match buffer.ty() {
             BufferType::ArrayBuffer => {
                 self.array_buffer = buffer;
             }
-            BufferType::ElementArrayBuffer => todo!(),
-            BufferType::UniformBuffer => todo!(),
-            BufferType::ShaderStorage => todo!(),
+            BufferType::ElementArrayBuffer => {
+                self.index_buffer = Some((buffer, IndexType::U32));
+            }
+            ty @ (BufferType::UniformBuffer | BufferType::ShaderStorage) => {
+                panic!("A {ty:?} is bound to a binding point, not to a vertex array")
+            }
         }
         self
     }
 
+    /// The indices of `draw_elements` with the type `I`
+    pub fn add_index_buffer<I: Index>(mut self, buffer: Buffer) -> Self {
+        assert_eq!(
+            buffer.ty(),
+            BufferType::ElementArrayBuffer,
+            "The indices need an element array buffer"
+        );
+        self.index_buffer = Some((buffer, I::TYPE));
+        self
+    }
+
     pub fn add_attrib(mut self, attrib: AttribPointer) -> Self {
         self.attribs.push(attrib);
         self
     }
 
+    /// The attributes of `T` without a divisor advance once per instance instead of once per vertex
+    pub fn per_instance(mut self) -> Self {
+        self.divisor = 1;
+        self
+    }
+
+    /// Another buffer with the attributes of `U`, they come after the ones of the buffers before
+    /// the fields of `U` without a divisor get `divisor`
+    pub fn add_array_buffer<U: Fields>(mut self, buffer: Buffer, divisor: u32) -> Self {
+        assert_eq!(
+            buffer.ty(),
+            BufferType::ArrayBuffer,
+            "The attributes need an array buffer"
+        );
+        let pointers = Field::pointers(U::fields(), divisor);
+        assert!(
+            !pointers.is_empty(),
+            "No attribute pointer and no valid type"
+        );
+        self.buffers.push((buffer, pointers));
+        self
+    }
+
+    /// The attributes get the locations in the order that they were added, see `Fields`
     pub fn build(mut self, gcx: &GCX) -> VertexArray {
         unsafe {
             let array_buffer = self.array_buffer;
@@ -150,49 +369,53 @@ impl<T: Fields> VertexArrayBuilder<T> {
             array_buffer.bind();
 
             if self.attribs.is_empty() {
-                let mut stride = 0;
-                for field in T::fields() {
-                    println!(
-                        "Field: {}, Size: {}, GlSize: {}",
-                        field.name, field.size, field.gl_size
-                    );
-                    self.attribs.push(AttribPointer::new(
-                        field.base,
-                        field.gl_size,
-                        0,
-                        false,
-                        stride,
-                    ));
-                    stride += field.size;
-                }
+                self.attribs = Field::pointers(T::fields(), self.divisor);
 
-                for attrib in self.attribs.iter_mut() {
-                    attrib.stride = stride;
-                }
-
-                if stride == 0 {
+                if self.attribs.first().map_or(0, AttribPointer::stride) == 0 {
                     panic!("No attribute pointer and no valid type");
                 }
             }
 
-            for (i, attrib) in self.attribs.into_iter().enumerate() {
-                gl.enable_vertex_attrib_array(i as u32);
-                gl.vertex_attrib_pointer_f32(
-                    i as u32,
-                    attrib.size,
-                    attrib.ty as u32,
-                    attrib.normalized,
-                    attrib.stride,
-                    attrib.offset,
-                );
+            let mut index = 0;
+            let mut set = |attribs: &[AttribPointer]| {
+                for attrib in attribs {
+                    gl.enable_vertex_attrib_array(index);
+                    pointer(gl, index, attrib, 0);
+                    if attrib.divisor != 0 {
+                        gl.vertex_attrib_divisor(index, attrib.divisor);
+                    }
+                    index += 1;
+                }
+            };
+            set(&self.attribs);
+
+            let mut buffers = Vec::with_capacity(self.buffers.len());
+            for (buffer, attribs) in self.buffers {
+                // the pointers use the buffer that is bound when they are set
+                buffer.bind();
+                set(&attribs);
+                buffers.push(buffer);
             }
 
+            // the vertex array keeps the index buffer that is bound
+            let (index_buffer, index_type) = match self.index_buffer {
+                Some((buffer, ty)) => {
+                    buffer.bind();
+                    (Some(buffer), ty)
+                }
+                None => (None, IndexType::U32),
+            };
+
             gl.bind_vertex_array(None);
 
             VertexArray {
                 gl: gl.clone(),
                 vao,
                 array_buffer,
+                buffers,
+                index_buffer,
+                index_type,
+                attribs: self.attribs,
             }
         }
     }
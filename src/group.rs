@@ -0,0 +1,505 @@
+use std::collections::HashSet;
+
+use lyon::math::{Angle, Transform};
+
+use crate::{
+    camera::{CameraView, NCamera},
+    effect::{Effect, NPostProcess},
+    gcx::shader::Shader,
+    info::Origin,
+    mask::Mask,
+    node::{union, Bounds, NodeBounds},
+    scene::SceneTask,
+    signal::{create_signal, NSignal, RawSignal, Signal},
+};
+
+/// A handle to a group, the nodes that are spawned in it are drawn relative to the group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GroupId(usize);
+
+impl GroupId {
+    /// The group that has every other group, it never moves
+    pub const ROOT: Self = Self(0);
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupBuilder {
+    pub(super) position: [f32; 2],
+    pub(super) rotation: f32,
+    pub(super) scale: [f32; 2],
+    pub(super) opacity: f32,
+    pub(super) mask: Option<Mask>,
+    pub(super) effects: Vec<Effect>,
+}
+
+impl Default for GroupBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GroupBuilder {
+    pub fn new() -> Self {
+        Self {
+            position: [0.; 2],
+            rotation: 0.,
+            scale: [1.; 2],
+            opacity: 1.,
+            mask: None,
+            effects: Vec::new(),
+        }
+    }
+
+    /// The origin of the children in the parent
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// In radians counter clockwise on the screen around the position
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: [f32; 2]) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Multiplies the opacity of every child
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Every child is clipped by the mask, a child can use a different mask with its own `with_mask`
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The children are drawn in a layer and the effects are applied to it in order
+    /// a group with effects in this group is drawn with its own effects first and then with these
+    pub fn with_effects(mut self, effects: Vec<Effect>) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// The transform from the children to the parent, `up` is the one of `World`
+    fn local(&self, up: f32) -> Transform {
+        let [x, y] = self.position;
+        let [sx, sy] = self.scale;
+        Transform::scale(sx, sy)
+            .then_rotate(Angle::radians(self.rotation * up))
+            .then_translate([x, y].into())
+    }
+}
+
+pub struct Group<'a> {
+    scene: &'a SceneTask,
+    id: GroupId,
+
+    pub position: Signal<'a, [f32; 2]>,
+    pub rotation: Signal<'a, f32>,
+    pub scale: Signal<'a, [f32; 2]>,
+    pub opacity: Signal<'a, f32>,
+    pub effects: Signal<'a, Vec<Effect>>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Group<'a> {
+    /// Use this as the parent in `SceneTask::spawn_in` or `SceneTask::group_in`
+    pub fn id(&self) -> GroupId {
+        self.id
+    }
+
+    /// The children that are still alive will keep the last transform of the group
+    /// when none is left the group is removed and its id can not be used as a parent
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+
+    pub(crate) fn new(
+        id: GroupId,
+        builder: &GroupBuilder,
+        raw: RawGroup,
+        scene: &'a SceneTask,
+    ) -> Self {
+        Self {
+            scene,
+            id,
+            dropped: false,
+            position: Signal::new(raw.position, scene, builder.position),
+            rotation: Signal::new(raw.rotation, scene, builder.rotation),
+            scale: Signal::new(raw.scale, scene, builder.scale),
+            opacity: Signal::new(raw.opacity, scene, builder.opacity),
+            effects: Signal::new(raw.effects, scene, builder.effects.clone()),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+impl<'a> Bounds for Group<'a> {
+    fn bounds(&self) -> NodeBounds {
+        NodeBounds::Group(self.id)
+    }
+}
+
+impl<'a> Drop for Group<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Group when you are done with it!");
+        std::process::abort();
+    }
+}
+
+/// The transform and the opacity of a group after the ones of the parents are applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct World {
+    /// From the group to NDC
+    pub transform: Transform,
+    pub opacity: f32,
+    /// `1` if y goes up on the screen and `-1` if it goes down
+    /// nodes that have an orientation like text use it to not be upside down
+    pub up: f32,
+    /// The mask of the closest group that has one
+    pub mask: Option<Mask>,
+    /// The closest group that has effects, the children are drawn in its layer
+    pub layer: Option<GroupId>,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self {
+            transform: Transform::identity(),
+            opacity: 1.,
+            up: 1.,
+            mask: None,
+            layer: None,
+        }
+    }
+}
+
+impl World {
+    /// The transform as a column major `mat3`
+    pub fn matrix(&self) -> [f32; 9] {
+        let t = &self.transform;
+        [t.m11, t.m12, 0., t.m21, t.m22, 0., t.m31, t.m32, 1.]
+    }
+
+    /// Sets the `mat3 World`, the `float WorldOpacity` and the `float WorldUp` uniforms
+    pub fn set_uniforms(&self, shader: &Shader) {
+        let _ = shader.set_uniform("World", self.matrix());
+        let _ = shader.set_uniform("WorldOpacity", self.opacity);
+        let _ = shader.set_uniform("WorldUp", self.up);
+    }
+}
+
+#[derive(Debug)]
+pub struct NGroup {
+    parent: GroupId,
+    builder: GroupBuilder,
+    /// `None` for the root and for dropped groups
+    inner: Option<NGroupInner>,
+    world: World,
+    /// Dropped and nothing is in it anymore, it is skipped and nothing can be added to it
+    dead: bool,
+}
+
+#[derive(Debug)]
+pub struct NGroupInner {
+    drop: NSignal<()>,
+    position: NSignal<[f32; 2]>,
+    rotation: NSignal<f32>,
+    scale: NSignal<[f32; 2]>,
+    opacity: NSignal<f32>,
+    effects: NSignal<Vec<Effect>>,
+}
+
+pub struct RawGroup {
+    drop: RawSignal<()>,
+    position: RawSignal<[f32; 2]>,
+    rotation: RawSignal<f32>,
+    scale: RawSignal<[f32; 2]>,
+    opacity: RawSignal<f32>,
+    effects: RawSignal<Vec<Effect>>,
+}
+
+impl NGroup {
+    pub(crate) fn create(parent: GroupId, builder: GroupBuilder) -> (NGroup, RawGroup) {
+        let (nposition, position) = create_signal();
+        let (nrotation, rotation) = create_signal();
+        let (nscale, scale) = create_signal();
+        let (nopacity, opacity) = create_signal();
+        let (neffects, effects) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        let group = NGroup {
+            parent,
+            builder,
+            inner: Some(NGroupInner {
+                drop,
+                position,
+                rotation,
+                scale,
+                opacity,
+                effects,
+            }),
+            world: World::default(),
+            dead: false,
+        };
+        let raw = RawGroup {
+            drop: ndrop,
+            position: nposition,
+            rotation: nrotation,
+            scale: nscale,
+            opacity: nopacity,
+            effects: neffects,
+        };
+        (group, raw)
+    }
+}
+
+/// The groups of the engine, the world of every group is computed again before a frame is rendered
+#[derive(Debug)]
+pub struct SceneGraph {
+    /// A parent is always before the children, because the ids are not reused
+    /// a dead group only keeps its slot
+    groups: Vec<NGroup>,
+    camera: CameraView,
+    camera_inner: Option<NCamera>,
+    /// The effects of the whole screen
+    effects: Vec<Effect>,
+    post_process: Option<NPostProcess>,
+    time: f64,
+}
+
+impl Default for SceneGraph {
+    fn default() -> Self {
+        Self {
+            groups: vec![NGroup {
+                parent: GroupId::ROOT,
+                builder: GroupBuilder::new(),
+                inner: None,
+                world: World::default(),
+                dead: false,
+            }],
+            camera: CameraView::default(),
+            camera_inner: None,
+            effects: Vec::new(),
+            post_process: None,
+            time: 0.,
+        }
+    }
+}
+
+impl SceneGraph {
+    pub(crate) fn insert(&mut self, group: NGroup) -> GroupId {
+        assert!(
+            group.parent.0 < self.groups.len(),
+            "The parent group does not exist"
+        );
+        assert!(
+            !self.groups[group.parent.0].dead,
+            "The parent group was dropped"
+        );
+        self.groups.push(group);
+        GroupId(self.groups.len() - 1)
+    }
+
+    /// The camera replaces the last one, returns the current view
+    pub(crate) fn set_camera(&mut self, camera: NCamera) -> CameraView {
+        self.camera_inner = Some(camera);
+        self.camera
+    }
+
+    /// The post process replaces the last one, returns the current effects
+    pub(crate) fn set_post_process(&mut self, post_process: NPostProcess) -> Vec<Effect> {
+        self.post_process = Some(post_process);
+        self.effects.clone()
+    }
+
+    pub(crate) fn set_effects(&mut self, effects: Vec<Effect>) {
+        self.effects = effects;
+    }
+
+    pub(crate) fn update(&mut self) {
+        if let Some(camera) = &mut self.camera_inner {
+            camera.update(&mut self.camera);
+        }
+        if let Some(post_process) = &mut self.post_process {
+            post_process.update(&mut self.effects);
+        }
+        for group in self.groups.iter_mut() {
+            let Some(inner) = &mut group.inner else {
+                continue;
+            };
+            if let Some(position) = inner.position.get() {
+                group.builder.position = position;
+            }
+            if let Some(rotation) = inner.rotation.get() {
+                group.builder.rotation = rotation;
+            }
+            if let Some(scale) = inner.scale.get() {
+                group.builder.scale = scale;
+            }
+            if let Some(opacity) = inner.opacity.get() {
+                group.builder.opacity = opacity;
+            }
+            if let Some(effects) = inner.effects.get() {
+                group.builder.effects = effects;
+            }
+            if inner.drop.get().is_some() {
+                group.inner = None;
+            }
+        }
+    }
+
+    /// Kills the dropped groups that have no nodes and no groups that are not dead in them
+    /// `parents` are the groups of the nodes
+    pub(crate) fn remove_empty(&mut self, parents: &HashSet<GroupId>) {
+        let mut used = vec![false; self.groups.len()];
+        // the children are after the parents, so they are checked first
+        for i in (1..self.groups.len()).rev() {
+            let group = &mut self.groups[i];
+            if group.dead {
+                continue;
+            }
+            if group.inner.is_some() || used[i] || parents.contains(&GroupId(i)) {
+                used[group.parent.0] = true;
+            } else {
+                group.dead = true;
+                group.builder = GroupBuilder::new();
+            }
+        }
+    }
+
+    /// The root maps pixels of a `width` by `height` screen with the `origin` to NDC, after the camera
+    pub(crate) fn propagate(&mut self, width: f32, height: f32, origin: Origin) {
+        let mut root = match origin {
+            Origin::Center => World {
+                transform: Transform::scale(2. / width, 2. / height),
+                ..World::default()
+            },
+            Origin::TopLeft => World {
+                transform: Transform::scale(2. / width, -2. / height)
+                    .then_translate([-1., 1.].into()),
+                up: -1.,
+                ..World::default()
+            },
+        };
+        root.transform = self
+            .camera
+            .transform(origin.center(width, height), root.up)
+            .then(&root.transform);
+        self.groups[0].world = root;
+
+        for i in 1..self.groups.len() {
+            if self.groups[i].dead {
+                continue;
+            }
+            let parent = self.groups[self.groups[i].parent.0].world;
+            let group = &mut self.groups[i];
+            group.world = World {
+                transform: group.builder.local(parent.up).then(&parent.transform),
+                opacity: group.builder.opacity * parent.opacity,
+                up: parent.up,
+                mask: group.builder.mask.or(parent.mask),
+                layer: if group.builder.effects.is_empty() {
+                    parent.layer
+                } else {
+                    Some(GroupId(i))
+                },
+            };
+        }
+    }
+
+    /// Called once for every frame that is rendered
+    pub(crate) fn tick(&mut self, delta: f64) {
+        self.time += delta;
+    }
+
+    /// The seconds of the frames rendered before this one
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// The effects of the group
+    pub fn effects(&self, id: GroupId) -> &[Effect] {
+        &self.groups[id.0].builder.effects
+    }
+
+    /// The effects of the whole screen
+    pub fn screen_effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    pub fn world(&self, id: GroupId) -> World {
+        self.groups[id.0].world
+    }
+
+    /// The group with effects that is right in `outer` and has the `layer` in it
+    /// `None` if the `layer` is `outer`, the screen is `None`
+    pub fn layer_in(&self, mut layer: Option<GroupId>, outer: Option<GroupId>) -> Option<GroupId> {
+        while layer != outer {
+            let group = layer?;
+            let parent = self.groups[self.groups[group.0].parent.0].world.layer;
+            if parent == outer {
+                return Some(group);
+            }
+            layer = parent;
+        }
+        None
+    }
+
+    /// The transform from the group to the root group with the values of now, the camera is not in it
+    pub fn to_root(&self, mut id: GroupId) -> Transform {
+        let up = self.groups[0].world.up;
+        let mut transform = Transform::identity();
+        while id != GroupId::ROOT {
+            let group = &self.groups[id.0];
+            transform = transform.then(&group.builder.local(up));
+            id = group.parent;
+        }
+        transform
+    }
+
+    /// If `id` is the `group` or one of its children
+    pub fn contains(&self, group: GroupId, mut id: GroupId) -> bool {
+        loop {
+            if id == group {
+                return true;
+            }
+            if id == GroupId::ROOT {
+                return false;
+            }
+            id = self.groups[id.0].parent;
+        }
+    }
+
+    /// Maps bounds in the group to the bounds in the root group that have every corner in them
+    pub fn root_bounds(&self, id: GroupId, [min, max]: [[f32; 2]; 2]) -> [[f32; 2]; 2] {
+        let transform = self.to_root(id);
+        [
+            [min[0], min[1]],
+            [max[0], min[1]],
+            [max[0], max[1]],
+            [min[0], max[1]],
+        ]
+        .map(|[x, y]| {
+            let point = transform.transform_point([x, y].into());
+            [[point.x, point.y]; 2]
+        })
+        .into_iter()
+        .reduce(union)
+        .unwrap()
+    }
+}
@@ -0,0 +1,657 @@
+use std::{io::Cursor, path::Path, sync::Arc};
+
+use ::image::{DynamicImage, ImageDecoder, ImageReader};
+
+use crate::{
+    color::Color,
+    gcx::{
+        blend::BlendMode,
+        buffer::{BufferType, BufferUsage},
+        shader::Shader,
+        texture::{Format, InternalFormat, Texture, TextureFilter, TextureTarget, TextureType},
+        vertex_array::{Field, Fields, VertexArray},
+        DataType, PrimitiveType, GCX,
+    },
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    node::{rotated, Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    shadow::{Glow, Shadow},
+    signal::{create_signal, NSignal, RawSignal, Signal},
+};
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    Decode(::image::ImageError),
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io(err) => write!(f, "Cannot read image: {err}"),
+            ImageError::Decode(err) => write!(f, "Cannot decode image: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<::image::ImageError> for ImageError {
+    fn from(value: ::image::ImageError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+/// Decoded RGBA8 pixels with premultiplied alpha, the first row is the top of the image
+/// is cheap to clone
+#[derive(Clone)]
+pub struct ImageData {
+    width: u32,
+    height: u32,
+    pixels: Arc<[u8]>,
+}
+
+impl std::fmt::Debug for ImageData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImageData")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+impl ImageData {
+    /// Decodes a PNG, JPEG or WebP, the format is guessed from the data
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ImageError> {
+        Self::decode(ImageReader::new(Cursor::new(data)).with_guessed_format()?)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Self::decode(ImageReader::open(path)?.with_guessed_format()?)
+    }
+
+    fn decode<R: std::io::BufRead + std::io::Seek>(
+        reader: ImageReader<R>,
+    ) -> Result<Self, ImageError> {
+        let mut decoder = reader.into_decoder()?;
+        // the EXIF orientation of photos taken with a rotated camera
+        let orientation = decoder.orientation()?;
+        let mut image = DynamicImage::from_decoder(decoder)?;
+        image.apply_orientation(orientation);
+
+        let image = image.into_rgba8();
+        Ok(Self::from_rgba(
+            image.width(),
+            image.height(),
+            image.into_raw(),
+        ))
+    }
+
+    /// RGBA8 pixels without premultiplied alpha, the first row is the top
+    pub fn from_rgba(width: u32, height: u32, mut pixels: Vec<u8>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * 4,
+            "The pixels do not have the size of the image"
+        );
+        for pixel in pixels.chunks_exact_mut(4) {
+            let alpha = pixel[3] as u32;
+            for channel in &mut pixel[..3] {
+                *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels: pixels.into(),
+        }
+    }
+
+    /// A texture with mipmaps and trilinear filtering
+    pub(crate) fn upload(&self, gcx: &GCX) -> Texture {
+        let texture = gcx.create_texture(
+            TextureType::Tex2D,
+            TextureTarget::Tex2D,
+            0,
+            InternalFormat::RGBA8,
+            self.width as i32,
+            self.height as i32,
+            Format::RGBA,
+            DataType::U8,
+            &self.pixels,
+        );
+        texture.set_filter(TextureFilter::Trilinear);
+        texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// How the image is placed in its box
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageFit {
+    /// The whole image is visible and keeps its aspect ratio
+    #[default]
+    Contain,
+    /// The image fills the box and keeps its aspect ratio, what does not fit is cropped
+    Cover,
+    /// The image fills the box
+    Stretch,
+}
+
+#[derive(Debug)]
+pub struct ImageBuilder {
+    pub(super) image: ImageData,
+    pub(super) position: [f32; 2],
+    pub(super) size: [f32; 2],
+    pub(super) rotation: f32,
+    pub(super) opacity: f32,
+    pub(super) crop: [f32; 4],
+    pub(super) fit: ImageFit,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) shadow: Shadow,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl ImageBuilder {
+    /// `size` is the half size of the box like the one of a rect
+    pub fn new(image: ImageData, size: [f32; 2]) -> Self {
+        Self {
+            image,
+            position: [0.; 2],
+            size,
+            rotation: 0.,
+            opacity: 1.,
+            crop: [0., 0., 1., 1.],
+            fit: ImageFit::Contain,
+            blend: BlendMode::Normal,
+            z_index: 0,
+            shadow: Shadow::default(),
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// In radians counter clockwise around the position
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// The visible part of the image as `[x, y, width, height]` from `0` to `1`, `y` goes down from the top
+    pub fn with_crop(mut self, crop: [f32; 4]) -> Self {
+        self.crop = crop;
+        self
+    }
+
+    pub fn with_fit(mut self, fit: ImageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// A shadow behind the node, `offset` and `blur` are in pixels of the screen
+    pub fn with_shadow(mut self, color: impl Into<Color>, offset: [f32; 2], blur: f32) -> Self {
+        self.shadow.color = color.into();
+        self.shadow.offset = offset;
+        self.shadow.blur = blur;
+        self
+    }
+
+    /// A glow around the node, `radius` is in pixels of the screen
+    pub fn with_glow(mut self, color: impl Into<Color>, radius: f32) -> Self {
+        self.shadow.glow = Glow {
+            color: color.into(),
+            radius,
+        };
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+pub struct Image<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+
+    pub position: Signal<'a, [f32; 2]>,
+    pub size: Signal<'a, [f32; 2]>,
+    pub rotation: Signal<'a, f32>,
+    pub opacity: Signal<'a, f32>,
+    pub crop: Signal<'a, [f32; 4]>,
+    pub fit: Signal<'a, ImageFit>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+    pub shadow_color: Signal<'a, Color>,
+    pub shadow_offset: Signal<'a, [f32; 2]>,
+    pub shadow_blur: Signal<'a, f32>,
+    pub glow: Signal<'a, Glow>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Image<'a> {
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+}
+
+impl<'a> Bounds for Image<'a> {
+    fn bounds(&self) -> NodeBounds {
+        NodeBounds::Local(
+            self.parent,
+            rotated(
+                *self.position.value(),
+                *self.size.value(),
+                *self.rotation.value(),
+            ),
+        )
+    }
+}
+
+impl<'a> Drop for Image<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Image when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for ImageBuilder {
+    type Node<'a> = Image<'a>;
+    type NodeManager = ImageNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawImage,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        Image {
+            scene,
+            parent,
+            dropped: false,
+            position: Signal::new(raw.position, scene, self.position),
+            size: Signal::new(raw.size, scene, self.size),
+            rotation: Signal::new(raw.rotation, scene, self.rotation),
+            opacity: Signal::new(raw.opacity, scene, self.opacity),
+            crop: Signal::new(raw.crop, scene, self.crop),
+            fit: Signal::new(raw.fit, scene, self.fit),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            shadow_color: Signal::new(raw.shadow_color, scene, self.shadow.color),
+            shadow_offset: Signal::new(raw.shadow_offset, scene, self.shadow.offset),
+            shadow_blur: Signal::new(raw.shadow_blur, scene, self.shadow.blur),
+            glow: Signal::new(raw.glow, scene, self.shadow.glow),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+pub struct NImage {
+    texture: Texture,
+    parent: GroupId,
+    builder: ImageBuilder,
+    inner: NImageInner,
+}
+
+pub struct NImageInner {
+    drop: NSignal<()>,
+    position: NSignal<[f32; 2]>,
+    size: NSignal<[f32; 2]>,
+    rotation: NSignal<f32>,
+    opacity: NSignal<f32>,
+    crop: NSignal<[f32; 4]>,
+    fit: NSignal<ImageFit>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+    shadow_color: NSignal<Color>,
+    shadow_offset: NSignal<[f32; 2]>,
+    shadow_blur: NSignal<f32>,
+    glow: NSignal<Glow>,
+}
+
+pub struct RawImage {
+    drop: RawSignal<()>,
+    position: RawSignal<[f32; 2]>,
+    size: RawSignal<[f32; 2]>,
+    rotation: RawSignal<f32>,
+    opacity: RawSignal<f32>,
+    crop: RawSignal<[f32; 4]>,
+    fit: RawSignal<ImageFit>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+    shadow_color: RawSignal<Color>,
+    shadow_offset: RawSignal<[f32; 2]>,
+    shadow_blur: RawSignal<f32>,
+    glow: RawSignal<Glow>,
+}
+
+#[derive(Default)]
+pub struct ImageNodeManager {
+    pub(super) images: Vec<NImage>,
+    pub(super) shader: Option<Shader>,
+    /// A quad from `-1` to `1` shared by every image
+    quad: Option<VertexArray>,
+
+    pending: Option<NImageInner>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ImageVertex {
+    corner: [f32; 2],
+}
+
+impl Fields for ImageVertex {
+    fn fields() -> Vec<Field> {
+        vec![Field::new::<[f32; 2]>("corner")]
+    }
+}
+
+impl NodeManager for ImageNodeManager {
+    type NodeBuilder = ImageBuilder;
+    type RawNode = RawImage;
+
+    fn init(&mut self, gcx: &GCX) {
+        let shader = gcx
+            .create_shader()
+            .vertex(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                layout(location = 0) in vec2 corner;
+
+                uniform vec2 Position;
+                // half size in pixels
+                uniform vec2 Half;
+                uniform float Rotation;
+                uniform vec2 UVMin;
+                uniform vec2 UVMax;
+                uniform mat3 World;
+                uniform float WorldUp;
+
+                out vec2 UV;
+
+                void main(){
+                    vec2 pos = corner * Half;
+                    float c = cos(Rotation);
+                    float s = sin(Rotation);
+                    pos = vec2(pos.x * c - pos.y * s, pos.x * s + pos.y * c);
+                    gl_Position = vec4((World * vec3(Position + pos * vec2(1.0, WorldUp), 1.0)).xy, 0.0, 1.0);
+                    UV = mix(UVMin, UVMax, vec2(corner.x, -corner.y) * 0.5 + 0.5);
+                }
+            "#,
+            )
+            .fragment(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                uniform sampler2D IMAGE;
+                uniform float Opacity;
+                uniform float WorldOpacity;
+
+                in vec2 UV;
+                out vec4 color;
+                void main(){
+                    color = texture(IMAGE, UV) * Opacity * WorldOpacity;
+                }
+                "#,
+            )
+            .build(gcx)
+            .unwrap();
+
+        let buffer = gcx.create_buffer(
+            BufferType::ArrayBuffer,
+            &[[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(|corner| ImageVertex { corner }),
+            BufferUsage::DRAW_STATIC,
+        );
+        self.quad = Some(gcx.create_vertex_array::<ImageVertex>(buffer).build(gcx));
+        self.shader.replace(shader);
+    }
+
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
+        self.images.push(NImage {
+            texture: builder.image.upload(gcx),
+            parent,
+            builder,
+            inner: self.pending.take().unwrap(),
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.images
+            .iter()
+            .map(|image| DrawItem {
+                z_index: image.builder.z_index,
+                parent: image.parent,
+                mask: image.builder.mask,
+                mask_source: image.builder.mask_source,
+                shadow: Some(image.builder.shadow).filter(Shadow::is_visible),
+            })
+            .collect()
+    }
+
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.images
+            .iter()
+            .map(|image| {
+                let builder = &image.builder;
+                rotated(builder.position, builder.size, builder.rotation)
+            })
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let Some(shader) = &self.shader else { panic!() };
+        let Some(quad) = &self.quad else { panic!() };
+        gcx.use_shader(shader, |gcx| {
+            let _ = shader.set_uniform("IMAGE", 0);
+
+            for image in items.iter().map(|&i| &self.images[i]) {
+                let builder = &image.builder;
+                let (half, uv_min, uv_max) = Self::fit(builder, builder.size);
+
+                image.texture.activate(0);
+                let _ = shader.set_uniform("Position", (builder.position[0], builder.position[1]));
+                let _ = shader.set_uniform("Half", (half[0], half[1]));
+                let _ = shader.set_uniform("Rotation", builder.rotation);
+                let _ = shader.set_uniform("UVMin", (uv_min[0], uv_min[1]));
+                let _ = shader.set_uniform("UVMax", (uv_max[0], uv_max[1]));
+                let _ = shader.set_uniform("Opacity", builder.opacity.clamp(0., 1.));
+                graph.world(image.parent).set_uniforms(shader);
+                let _blend = gcx.blend(builder.blend);
+                gcx.use_vertex_array(quad, |gcx| {
+                    gcx.draw_arrays(PrimitiveType::TrianglesFan, 0, 4);
+                });
+            }
+        });
+    }
+
+    fn create_node(&mut self) -> RawImage {
+        let (nposition, position) = create_signal();
+        let (nsize, size) = create_signal();
+        let (nrotation, rotation) = create_signal();
+        let (nopacity, opacity) = create_signal();
+        let (ncrop, crop) = create_signal();
+        let (nfit, fit) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (nshadow_color, shadow_color) = create_signal();
+        let (nshadow_offset, shadow_offset) = create_signal();
+        let (nshadow_blur, shadow_blur) = create_signal();
+        let (nglow, glow) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NImageInner {
+            drop,
+            position,
+            size,
+            rotation,
+            opacity,
+            crop,
+            fit,
+            blend,
+            z_index,
+            shadow_color,
+            shadow_offset,
+            shadow_blur,
+            glow,
+        });
+
+        RawImage {
+            drop: ndrop,
+            position: nposition,
+            size: nsize,
+            rotation: nrotation,
+            opacity: nopacity,
+            crop: ncrop,
+            fit: nfit,
+            blend: nblend,
+            z_index: nz_index,
+            shadow_color: nshadow_color,
+            shadow_offset: nshadow_offset,
+            shadow_blur: nshadow_blur,
+            glow: nglow,
+        }
+    }
+
+    fn update(&mut self) {
+        self.images.retain_mut(|image| {
+            if let Some(position) = image.inner.position.get() {
+                image.builder.position = position;
+            }
+            if let Some(size) = image.inner.size.get() {
+                image.builder.size = size;
+            }
+            if let Some(rotation) = image.inner.rotation.get() {
+                image.builder.rotation = rotation;
+            }
+            if let Some(opacity) = image.inner.opacity.get() {
+                image.builder.opacity = opacity;
+            }
+            if let Some(crop) = image.inner.crop.get() {
+                image.builder.crop = crop;
+            }
+            if let Some(fit) = image.inner.fit.get() {
+                image.builder.fit = fit;
+            }
+            if let Some(blend) = image.inner.blend.get() {
+                image.builder.blend = blend;
+            }
+            if let Some(z_index) = image.inner.z_index.get() {
+                image.builder.z_index = z_index;
+            }
+            if let Some(color) = image.inner.shadow_color.get() {
+                image.builder.shadow.color = color;
+            }
+            if let Some(offset) = image.inner.shadow_offset.get() {
+                image.builder.shadow.offset = offset;
+            }
+            if let Some(blur) = image.inner.shadow_blur.get() {
+                image.builder.shadow.blur = blur;
+            }
+            if let Some(glow) = image.inner.glow.get() {
+                image.builder.shadow.glow = glow;
+            }
+
+            image.inner.drop.get().is_none()
+        });
+    }
+}
+
+impl ImageNodeManager {
+    /// The half size in pixels and the uv range of the image in a box of `box_half` pixels
+    fn fit(builder: &ImageBuilder, box_half: [f32; 2]) -> ([f32; 2], [f32; 2], [f32; 2]) {
+        let [x, y, w, h] = builder.crop;
+        let (mut uv_min, mut uv_max) = ([x, y], [x + w, y + h]);
+
+        let image_width = w.abs() * builder.image.width as f32;
+        let image_height = h.abs() * builder.image.height as f32;
+        if image_width <= 0. || image_height <= 0. || box_half[1] == 0. {
+            return (box_half, uv_min, uv_max);
+        }
+        let image_aspect = image_width / image_height;
+        let box_aspect = box_half[0] / box_half[1];
+
+        match builder.fit {
+            ImageFit::Stretch => (box_half, uv_min, uv_max),
+            ImageFit::Contain => {
+                let half = if image_aspect > box_aspect {
+                    [box_half[0], box_half[0] / image_aspect]
+                } else {
+                    [box_half[1] * image_aspect, box_half[1]]
+                };
+                (half, uv_min, uv_max)
+            }
+            ImageFit::Cover => {
+                // the part of the crop that is visible on the cropped axis, centered
+                let (axis, visible) = if image_aspect > box_aspect {
+                    (0, box_aspect / image_aspect)
+                } else {
+                    (1, image_aspect / box_aspect)
+                };
+                let center = (uv_min[axis] + uv_max[axis]) * 0.5;
+                let half = (uv_max[axis] - uv_min[axis]) * visible * 0.5;
+                uv_min[axis] = center - half;
+                uv_max[axis] = center + half;
+                (box_half, uv_min, uv_max)
+            }
+        }
+    }
+}
@@ -1,9 +1,20 @@
 use std::num::NonZeroU32;
 
+/// Where the point `[0, 0]` is on the screen, the units of the nodes are pixels
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Origin {
+    /// In the center and y goes up
+    #[default]
+    Center,
+    /// In the top left corner and y goes down
+    TopLeft,
+}
+
 pub struct Info {
     pub delta: f64,
     pub width: NonZeroU32,
     pub height: NonZeroU32,
+    pub origin: Origin,
 }
 
 impl Info {
@@ -11,3 +22,21 @@ impl Info {
         (1. / self.delta).round() as usize
     }
 }
+
+impl Origin {
+    /// Where the center of a `width` by `height` screen is
+    pub fn center(&self, width: f32, height: f32) -> [f32; 2] {
+        match self {
+            Origin::Center => [0., 0.],
+            Origin::TopLeft => [width / 2., height / 2.],
+        }
+    }
+
+    /// The `WorldUp` of the root group, `1` if y goes up and `-1` if it goes down
+    pub fn up(&self) -> f32 {
+        match self {
+            Origin::Center => 1.,
+            Origin::TopLeft => -1.,
+        }
+    }
+}
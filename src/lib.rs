@@ -1,12 +1,28 @@
+pub mod camera;
+pub mod circle;
 pub mod color;
+pub mod effect;
 pub mod engine;
 pub mod engine_message;
+pub mod fill;
 pub mod gcx;
+pub mod group;
+pub mod image;
 pub mod info;
+pub mod line;
+pub mod mask;
+pub mod morph;
 pub mod node;
+pub mod particle;
+pub mod path;
 pub mod rect;
 pub mod scene;
+pub mod shader_quad;
+pub mod shadow;
 pub mod signal;
+pub mod stroke;
+pub mod svg;
+pub mod text;
 pub mod tween;
 
 pub type ORecv<T> = tokio::sync::oneshot::Receiver<T>;
@@ -0,0 +1,447 @@
+use crate::{
+    color::Color,
+    gcx::{
+        blend::BlendMode,
+        buffer::{BufferType, BufferUsage},
+        shader::Shader,
+        vertex_array::VertexArray,
+        PrimitiveType, GCX,
+    },
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    node::{Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    signal::{create_signal, NSignal, RawSignal, Signal},
+    stroke::{self, ArrowHead, LineCap, LineJoin, Stroke},
+};
+
+#[derive(Debug)]
+pub struct LineBuilder {
+    pub(super) points: Vec<[f32; 2]>,
+    pub(super) closed: bool,
+    pub(super) stroke: Stroke,
+    pub(super) color: Color,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl LineBuilder {
+    pub fn new(points: Vec<[f32; 2]>, width: f32, color: impl Into<Color>) -> Self {
+        Self {
+            points,
+            closed: false,
+            stroke: Stroke::new(width),
+            color: color.into(),
+            blend: BlendMode::Normal,
+            z_index: 0,
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    /// The last point will be connected with the first point
+    pub fn with_closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.stroke.cap = cap;
+        self
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.stroke.join = join;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.stroke.miter_limit = miter_limit;
+        self
+    }
+
+    /// Alternating dash and gap lengths
+    pub fn with_dash(mut self, dash: Vec<f32>) -> Self {
+        self.stroke.dash = dash;
+        self
+    }
+
+    pub fn with_dash_offset(mut self, dash_offset: f32) -> Self {
+        self.stroke.dash_offset = dash_offset;
+        self
+    }
+
+    pub fn with_start_arrow(mut self, arrow: ArrowHead) -> Self {
+        self.stroke.start_arrow = Some(arrow);
+        self
+    }
+
+    pub fn with_end_arrow(mut self, arrow: ArrowHead) -> Self {
+        self.stroke.end_arrow = Some(arrow);
+        self
+    }
+
+    /// The visible part of the stroke from `0` to `1` along the arc length
+    pub fn with_trim(mut self, start: f32, end: f32) -> Self {
+        self.stroke.trim_start = start;
+        self.stroke.trim_end = end;
+        self
+    }
+
+    pub fn with_trim_offset(mut self, offset: f32) -> Self {
+        self.stroke.trim_offset = offset;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+pub struct Line<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+
+    pub points: Signal<'a, Vec<[f32; 2]>>,
+    pub width: Signal<'a, f32>,
+    pub color: Signal<'a, Color>,
+    pub dash_offset: Signal<'a, f32>,
+    pub trim_start: Signal<'a, f32>,
+    pub trim_end: Signal<'a, f32>,
+    pub trim_offset: Signal<'a, f32>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Line<'a> {
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+}
+
+impl<'a> Bounds for Line<'a> {
+    fn bounds(&self) -> NodeBounds {
+        NodeBounds::Local(
+            self.parent,
+            line_bounds(self.points.value(), *self.width.value()),
+        )
+    }
+}
+
+fn line_bounds(points: &[[f32; 2]], width: f32) -> [[f32; 2]; 2] {
+    let half = width / 2.;
+    let Some(&[x, y]) = points.first() else {
+        return [[0.; 2]; 2];
+    };
+
+    let [mut min, mut max] = [[x, y]; 2];
+    for &[x, y] in points.iter() {
+        min = [min[0].min(x), min[1].min(y)];
+        max = [max[0].max(x), max[1].max(y)];
+    }
+    [
+        [min[0] - half, min[1] - half],
+        [max[0] + half, max[1] + half],
+    ]
+}
+
+impl<'a> Drop for Line<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Line when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for LineBuilder {
+    type Node<'a> = Line<'a>;
+    type NodeManager = LineNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawLine,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        Line {
+            scene,
+            parent,
+            dropped: false,
+            points: Signal::new(raw.points, scene, self.points.clone()),
+            width: Signal::new(raw.width, scene, self.stroke.width),
+            color: Signal::new(raw.color, scene, self.color),
+            dash_offset: Signal::new(raw.dash_offset, scene, self.stroke.dash_offset),
+            trim_start: Signal::new(raw.trim_start, scene, self.stroke.trim_start),
+            trim_end: Signal::new(raw.trim_end, scene, self.stroke.trim_end),
+            trim_offset: Signal::new(raw.trim_offset, scene, self.stroke.trim_offset),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+pub struct NLine {
+    va: VertexArray,
+    count: i32,
+    parent: GroupId,
+    builder: LineBuilder,
+    inner: NLineInner,
+}
+
+pub struct NLineInner {
+    drop: NSignal<()>,
+    points: NSignal<Vec<[f32; 2]>>,
+    width: NSignal<f32>,
+    color: NSignal<Color>,
+    dash_offset: NSignal<f32>,
+    trim_start: NSignal<f32>,
+    trim_end: NSignal<f32>,
+    trim_offset: NSignal<f32>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+}
+
+pub struct RawLine {
+    drop: RawSignal<()>,
+    points: RawSignal<Vec<[f32; 2]>>,
+    width: RawSignal<f32>,
+    color: RawSignal<Color>,
+    dash_offset: RawSignal<f32>,
+    trim_start: RawSignal<f32>,
+    trim_end: RawSignal<f32>,
+    trim_offset: RawSignal<f32>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+}
+
+#[derive(Default)]
+pub struct LineNodeManager {
+    pub(super) lines: Vec<NLine>,
+    pub(super) shader: Option<Shader>,
+
+    pending: Option<NLineInner>,
+}
+
+impl NodeManager for LineNodeManager {
+    type NodeBuilder = LineBuilder;
+    type RawNode = RawLine;
+
+    fn init(&mut self, gcx: &GCX) {
+        let shader = gcx
+            .create_shader()
+            .vertex(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                layout(location = 0) in vec2 pos;
+
+                uniform mat3 World;
+
+                void main(){
+                    gl_Position = vec4((World * vec3(pos, 1.0)).xy, 0.0, 1.0);
+                }
+            "#,
+            )
+            .fragment(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                uniform vec4 Color;
+                uniform float WorldOpacity;
+
+                out vec4 color;
+                void main(){
+                    color = vec4(Color.rgb * Color.a, Color.a) * WorldOpacity;
+                }
+                "#,
+            )
+            .build(gcx)
+            .unwrap();
+
+        self.shader.replace(shader);
+    }
+
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
+        let mesh = Self::build_mesh(&builder);
+        let buffer = gcx.create_buffer(BufferType::ArrayBuffer, &mesh, BufferUsage::DRAW_DYNAMIC);
+        let va = gcx.create_vertex_array::<[f32; 2]>(buffer).build(gcx);
+        self.lines.push(NLine {
+            va,
+            count: mesh.len() as i32,
+            parent,
+            builder,
+            inner: self.pending.take().unwrap(),
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.lines
+            .iter()
+            .map(|line| DrawItem {
+                z_index: line.builder.z_index,
+                parent: line.parent,
+                mask: line.builder.mask,
+                mask_source: line.builder.mask_source,
+                shadow: None,
+            })
+            .collect()
+    }
+
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.lines
+            .iter()
+            .map(|line| line_bounds(&line.builder.points, line.builder.stroke.width))
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let Some(shader) = &self.shader else { panic!() };
+        gcx.use_shader(shader, |gcx| {
+            for line in items.iter().map(|&i| &self.lines[i]) {
+                if line.count == 0 {
+                    continue;
+                }
+
+                let color = line.builder.color;
+                let _ = shader.set_uniform("Color", (color.r, color.g, color.b, color.a));
+                graph.world(line.parent).set_uniforms(shader);
+                let _blend = gcx.blend(line.builder.blend);
+                gcx.use_vertex_array(&line.va, |gcx| {
+                    gcx.draw_arrays(PrimitiveType::Triangles, 0, line.count);
+                });
+            }
+        });
+    }
+
+    fn create_node(&mut self) -> RawLine {
+        let (npoints, points) = create_signal();
+        let (nwidth, width) = create_signal();
+        let (ncolor, color) = create_signal();
+        let (ndash_offset, dash_offset) = create_signal();
+        let (ntrim_start, trim_start) = create_signal();
+        let (ntrim_end, trim_end) = create_signal();
+        let (ntrim_offset, trim_offset) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NLineInner {
+            drop,
+            points,
+            width,
+            color,
+            dash_offset,
+            trim_start,
+            trim_end,
+            trim_offset,
+            blend,
+            z_index,
+        });
+
+        RawLine {
+            drop: ndrop,
+            points: npoints,
+            width: nwidth,
+            color: ncolor,
+            dash_offset: ndash_offset,
+            trim_start: ntrim_start,
+            trim_end: ntrim_end,
+            trim_offset: ntrim_offset,
+            blend: nblend,
+            z_index: nz_index,
+        }
+    }
+
+    fn update(&mut self) {
+        self.lines.retain_mut(|line| {
+            let mut rebuild = false;
+            if let Some(points) = line.inner.points.get() {
+                line.builder.points = points;
+                rebuild = true;
+            }
+            if let Some(width) = line.inner.width.get() {
+                line.builder.stroke.width = width;
+                rebuild = true;
+            }
+            if let Some(dash_offset) = line.inner.dash_offset.get() {
+                line.builder.stroke.dash_offset = dash_offset;
+                rebuild = true;
+            }
+            if let Some(trim_start) = line.inner.trim_start.get() {
+                line.builder.stroke.trim_start = trim_start;
+                rebuild = true;
+            }
+            if let Some(trim_end) = line.inner.trim_end.get() {
+                line.builder.stroke.trim_end = trim_end;
+                rebuild = true;
+            }
+            if let Some(trim_offset) = line.inner.trim_offset.get() {
+                line.builder.stroke.trim_offset = trim_offset;
+                rebuild = true;
+            }
+            if let Some(color) = line.inner.color.get() {
+                line.builder.color = color;
+            }
+            if let Some(blend) = line.inner.blend.get() {
+                line.builder.blend = blend;
+            }
+            if let Some(z_index) = line.inner.z_index.get() {
+                line.builder.z_index = z_index;
+            }
+
+            if line.inner.drop.get().is_some() {
+                return false;
+            }
+
+            if rebuild {
+                let mesh = LineNodeManager::build_mesh(&line.builder);
+                line.va
+                    .array_buffer
+                    .set_data(&mesh, BufferUsage::DRAW_DYNAMIC);
+                line.count = mesh.len() as i32;
+            }
+            true
+        });
+    }
+}
+
+impl LineNodeManager {
+    fn build_mesh(builder: &LineBuilder) -> Vec<[f32; 2]> {
+        let mut mesh = Vec::new();
+        stroke::tessellate(&builder.points, builder.closed, &builder.stroke, &mut mesh);
+        mesh
+    }
+}
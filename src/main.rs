@@ -64,8 +64,11 @@ fn main() -> Result<(), Box<dyn Error>> {
 
             let fps = scene.fps(); // = 60
 
-            // we create a rect bigger as the screen with the red color!
-            let mut rect = scene.spawn(RectBuilder::new([1., 1.], Color::RED)).await;
+            // we create a rect as big as the screen with the red color!
+            // the units are pixels and the size is the half of the width and height
+            let mut rect = scene
+                .spawn(RectBuilder::new([960., 540.], Color::RED))
+                .await;
 
             // `scene.present()` will render that many frames!
             //this is to see the red rectangle
@@ -77,17 +80,23 @@ fn main() -> Result<(), Box<dyn Error>> {
             scene.present(1).await;
 
             let mut rect2 = scene
-                .spawn(RectBuilder::new([0.5, 0.5], Color::BLUE).with_position([-0.5, -0.5]))
+                .spawn(RectBuilder::new([270., 270.], Color::BLUE).with_position([-480., -270.]))
                 .await;
 
             scene.present(1).await;
 
             //                       from          to      time
-            rect2.position.tween([-0.5, -0.5], [0.5, -0.5], 1.0).await;
-            rect2.position.tween([0.5, -0.5], [0.5, 0.5], 1.0).await;
-            rect2.position.tween([0.5, 0.5], [-0.5, 0.5], 1.0).await;
-            rect2.position.tween([-0.5, 0.5], [-0.5, -0.5], 1.0).await;
-            rect2.position.tween([-0.5, -0.5], [0., 0.], 1.0).await;
+            rect2
+                .position
+                .tween([-480., -270.], [480., -270.], 1.0)
+                .await;
+            rect2.position.tween([480., -270.], [480., 270.], 1.0).await;
+            rect2.position.tween([480., 270.], [-480., 270.], 1.0).await;
+            rect2
+                .position
+                .tween([-480., 270.], [-480., -270.], 1.0)
+                .await;
+            rect2.position.tween([-480., -270.], [0., 0.], 1.0).await;
 
             // Play a video if is avalibile!
             if let Ok(mut media) = Media::new("video.mkv") {
@@ -98,25 +107,25 @@ fn main() -> Result<(), Box<dyn Error>> {
                     .spawn(AudioBuilder::new(media.audio(0).unwrap()))
                     .await;
 
-                video.size.tween([0., 0.], [1., 1.], 1.0).await;
+                video.size.tween([0., 0.], [960., 540.], 1.0).await;
 
                 while media.next() {
                     scene.present(1).await;
                 }
 
-                video.size.tween([1., 1.], [0.1, 0.1], 1.0).await;
+                video.size.tween([960., 540.], [96., 54.], 1.0).await;
 
                 audio.drop().await;
                 video.drop().await;
             }
 
-            rect2.size.tween([0.5, 0.5], [0., 0.], 1.0).await;
+            rect2.size.tween([270., 270.], [0., 0.], 1.0).await;
             // this is a custom drop that will send a drop signal to the node manager then i will call `scene.update()`
             //  this will remove the node from the node manager, and will be allow to safely drop
             // if this is not called the engine will panic or abort!
             rect2.drop().await;
 
-            rect.size.tween([1., 1.], [0., 0.], 1.0).await;
+            rect.size.tween([960., 540.], [0., 0.], 1.0).await;
             rect.drop().await;
         })
     });
@@ -192,7 +201,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let (event_loop, window, config, context, surface, gl) =
         make_context(WindowBuilder::new().with_title("Motion Man"))?;
-    let gcx = GCX::new(Rc::new(gl));
+    let gcx = GCX::with_loader(Rc::new(gl), |name| context.display().get_proc_address(name));
     _ = window.request_inner_size(LogicalSize::new(width.get(), height.get()));
     surface.resize(&context, width, height);
     gcx.viewport(0, 0, width.get() as i32, height.get() as i32);
@@ -360,12 +369,16 @@ fn make_context(
 mod video {
     use motion_man::{
         gcx::{
+            blend::BlendMode,
             buffer::{BufferType, BufferUsage},
             shader::{Shader, ShaderBuilder},
             texture::{Format, InternalFormat, Texture, TextureTarget, TextureType},
             vertex_array::{Field, Fields, VertexArray},
             DataType, GCX,
         },
+        group::{GroupId, SceneGraph},
+        mask::{Mask, MaskId},
+        node::DrawItem,
         node::NodeBuilder,
         node::NodeManager,
         scene::SceneTask,
@@ -377,6 +390,8 @@ mod video {
     pub struct Video<'a> {
         pub position: Signal<'a, [f32; 2]>,
         pub size: Signal<'a, [f32; 2]>,
+        pub blend: Signal<'a, BlendMode>,
+        pub z_index: Signal<'a, i32>,
 
         scene: &'a SceneTask,
 
@@ -387,6 +402,8 @@ mod video {
     pub struct RawVideo {
         position: RawSignal<[f32; 2]>,
         size: RawSignal<[f32; 2]>,
+        blend: RawSignal<BlendMode>,
+        z_index: RawSignal<i32>,
 
         drop: RawSignal<()>,
     }
@@ -412,16 +429,46 @@ mod video {
         stream: Box<dyn Stream>,
         size: [f32; 2],
         pos: [f32; 2],
+        blend: BlendMode,
+        z_index: i32,
+        mask: Option<Mask>,
+        mask_source: Option<MaskId>,
     }
 
     impl VideoBuilder {
         pub fn new(stream: Box<dyn Stream>) -> Self {
             Self {
                 stream,
-                size: [1., 1.],
+                size: [960., 540.],
                 pos: [0., 0.],
+                blend: BlendMode::Normal,
+                z_index: 0,
+                mask: None,
+                mask_source: None,
             }
         }
+
+        pub fn with_blend(mut self, blend: BlendMode) -> Self {
+            self.blend = blend;
+            self
+        }
+
+        pub fn with_z_index(mut self, z_index: i32) -> Self {
+            self.z_index = z_index;
+            self
+        }
+
+        /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+        pub fn with_mask(mut self, mask: Mask) -> Self {
+            self.mask = Some(mask);
+            self
+        }
+
+        /// The node is not drawn, it is the shape of the mask with the `id`
+        pub fn into_mask(mut self, id: MaskId) -> Self {
+            self.mask_source = Some(id);
+            self
+        }
     }
 
     impl NodeBuilder for VideoBuilder {
@@ -433,8 +480,11 @@ mod video {
             RawVideo {
                 position,
                 size,
+                blend,
+                z_index,
                 drop,
             }: RawVideo,
+            _parent: GroupId,
             scene: &'a SceneTask,
         ) -> Self::Node<'a> {
             Video {
@@ -442,6 +492,8 @@ mod video {
                 dropped: false,
                 position: Signal::new(position, scene, self.pos),
                 size: Signal::new(size, scene, self.size),
+                blend: Signal::new(blend, scene, self.blend),
+                z_index: Signal::new(z_index, scene, self.z_index),
                 drop: Signal::new(drop, scene, ()),
             }
         }
@@ -474,6 +526,7 @@ mod video {
 
     struct RVideo {
         va: VertexArray,
+        parent: GroupId,
         builder: VideoBuilder,
         texture: Option<Texture>,
         stream: Box<dyn Stream>,
@@ -481,9 +534,16 @@ mod video {
     }
 
     impl RVideo {
-        pub fn new(inner: RVideoInner, va: VertexArray, _gcx: &GCX, builder: VideoBuilder) -> Self {
+        pub fn new(
+            inner: RVideoInner,
+            va: VertexArray,
+            parent: GroupId,
+            _gcx: &GCX,
+            builder: VideoBuilder,
+        ) -> Self {
             Self {
                 va,
+                parent,
                 stream: builder.stream.clone_ref(),
                 builder,
                 texture: None,
@@ -495,6 +555,8 @@ mod video {
     pub struct RVideoInner {
         position: NSignal<[f32; 2]>,
         size: NSignal<[f32; 2]>,
+        blend: NSignal<BlendMode>,
+        z_index: NSignal<i32>,
         drop: NSignal<()>,
     }
 
@@ -517,13 +579,16 @@ mod video {
                         r#"#version 320 es
                 precision highp float;
 
-                in vec2 pos;
-                in vec2 uv;
+                layout(location = 0) in vec2 pos;
+                layout(location = 1) in vec2 uv;
+                uniform mat3 World;
+                uniform float WorldUp;
                 out vec2 UV;
 
                 void main(){
-                    gl_Position = vec4(pos, 0.0, 1.0);
-                    UV = uv;
+                    gl_Position = vec4((World * vec3(pos, 1.0)).xy, 0.0, 1.0);
+                    // the first row is at the top of the screen also when y goes down
+                    UV = vec2(uv.x, WorldUp > 0.0 ? uv.y : 1.0 - uv.y);
                 }
                 "#,
                     )
@@ -532,13 +597,15 @@ mod video {
                 precision highp float;
 
                 uniform sampler2D IMAGE;
+                uniform float WorldOpacity;
 
                 out vec4 color;
 
                 in vec2 UV;
 
                 void main(){
-                    color = vec4(texture(IMAGE, UV));
+                    vec4 image = texture(IMAGE, UV);
+                    color = vec4(image.rgb * image.a, image.a) * WorldOpacity;
                 }"#,
                     )
                     .build(gcx)
@@ -546,7 +613,12 @@ mod video {
             );
         }
 
-        fn init_node(&mut self, gcx: &motion_man::gcx::GCX, builder: Self::NodeBuilder) {
+        fn init_node(
+            &mut self,
+            gcx: &motion_man::gcx::GCX,
+            builder: Self::NodeBuilder,
+            parent: GroupId,
+        ) {
             let buffer = gcx.create_buffer(
                 BufferType::ArrayBuffer,
                 &create_mesh(&builder),
@@ -554,24 +626,35 @@ mod video {
             );
             let va = gcx.create_vertex_array::<Vertex>(buffer).build(gcx);
 
-            self.videos
-                .push(RVideo::new(self.pending.take().unwrap(), va, gcx, builder));
+            self.videos.push(RVideo::new(
+                self.pending.take().unwrap(),
+                va,
+                parent,
+                gcx,
+                builder,
+            ));
         }
 
         fn create_node(&mut self) -> RawVideo {
             let (sposition, position) = create_signal();
             let (ssize, size) = create_signal();
+            let (sblend, blend) = create_signal();
+            let (sz_index, z_index) = create_signal();
             let (sdrop, drop) = create_signal();
 
             self.pending = Some(RVideoInner {
                 position,
                 size,
+                blend,
+                z_index,
                 drop,
             });
 
             RawVideo {
                 position: sposition,
                 size: ssize,
+                blend: sblend,
+                z_index: sz_index,
                 drop: sdrop,
             }
         }
@@ -590,6 +673,14 @@ mod video {
                     rebuild = true;
                 }
 
+                if let Some(blend) = video.inner.blend.get() {
+                    video.builder.blend = blend;
+                }
+
+                if let Some(z_index) = video.inner.z_index.get() {
+                    video.builder.z_index = z_index;
+                }
+
                 if video.inner.drop.get().is_some() {
                     return false;
                 }
@@ -605,10 +696,26 @@ mod video {
             });
         }
 
-        fn render(&mut self, gcx: &motion_man::gcx::GCX) {
+        fn draw_items(&self) -> Vec<DrawItem> {
+            self.videos
+                .iter()
+                .map(|video| DrawItem {
+                    z_index: video.builder.z_index,
+                    parent: video.parent,
+                    mask: video.builder.mask,
+                    mask_source: video.builder.mask_source,
+                    shadow: None,
+                })
+                .collect()
+        }
+
+        fn render(&mut self, gcx: &motion_man::gcx::GCX, graph: &SceneGraph, items: &[usize]) {
             let shader = self.shader.as_ref().unwrap();
             gcx.use_shader(shader, |gcx| {
-                for video in self.videos.iter_mut() {
+                for &i in items {
+                    let video = &mut self.videos[i];
+                    graph.world(video.parent).set_uniforms(shader);
+                    let _blend = gcx.blend(video.builder.blend);
                     gcx.use_vertex_array(&video.va, |gcx| {
                         if let Some(data) = video.stream.data(0) {
                             if let Some(texture) = &mut video.texture {
@@ -1182,6 +1289,7 @@ mod media {
 
 mod audio {
     use motion_man::{
+        group::GroupId,
         node::NodeBuilder,
         node::NodeManager,
         signal::{create_signal, NSignal, RawSignal, Signal},
@@ -1231,6 +1339,7 @@ mod audio {
         fn create_node_ref<'a>(
             &self,
             RawAudio { drop }: RawAudio,
+            _parent: GroupId,
             scene: &'a motion_man::scene::SceneTask,
         ) -> Self::Node<'a> {
             Audio {
@@ -1250,7 +1359,12 @@ mod audio {
         type NodeBuilder = AudioBuilder;
         type RawNode = RawAudio;
 
-        fn init_node(&mut self, _gcx: &motion_man::gcx::GCX, builder: Self::NodeBuilder) {
+        fn init_node(
+            &mut self,
+            _gcx: &motion_man::gcx::GCX,
+            builder: Self::NodeBuilder,
+            _parent: GroupId,
+        ) {
             let drop = self.pending.take().unwrap();
             self.audios.push((drop, builder.stream, Vec::new(), 0));
         }
@@ -0,0 +1,226 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::gcx::{
+    blend::BlendMode,
+    buffer::{BufferType, BufferUsage},
+    framebuffer::{Framebuffer, Stencil},
+    shader::Shader,
+    vertex_array::{Field, Fields, VertexArray},
+    BufferBit, PrimitiveType, GCX,
+};
+
+/// The name of a mask, a node becomes the shape of the mask with `into_mask`
+/// and other nodes or groups are clipped by it with `with_mask`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaskId(usize);
+
+impl Default for MaskId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MaskId {
+    /// Every call gives a different id
+    pub fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        Self(COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// The content is multiplied by the alpha of the mask, soft edges and opacity are kept
+    pub fn alpha(self) -> Mask {
+        Mask {
+            id: self,
+            mode: MaskMode::Alpha,
+            inverted: false,
+        }
+    }
+
+    /// The content is cut where the triangles of the mask are drawn, the colors of the mask are ignored
+    pub fn stencil(self) -> Mask {
+        Mask {
+            id: self,
+            mode: MaskMode::Stencil,
+            inverted: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaskMode {
+    Alpha,
+    Stencil,
+}
+
+/// How the content is clipped by the mask with the `id`
+///
+/// The masked nodes are drawn in a layer that is put on the screen after,
+/// so the blend mode of a masked node only blends with the nodes of the same layer.
+/// A mask that has no node is empty, nothing is drawn if it is not inverted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mask {
+    pub id: MaskId,
+    pub mode: MaskMode,
+    pub inverted: bool,
+}
+
+impl Mask {
+    /// Draws the content only outside of the mask
+    pub fn inverted(mut self) -> Self {
+        self.inverted = !self.inverted;
+        self
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LayerVertex {
+    corner: [f32; 2],
+}
+
+impl Fields for LayerVertex {
+    fn fields() -> Vec<Field> {
+        vec![Field::new::<[f32; 2]>("corner")]
+    }
+}
+
+/// The vertex shader of a quad that covers the viewport, `UV` is from `0` to `1`
+pub(crate) const LAYER_VERTEX: &str = r#"
+    #version 320 es
+
+    precision highp float;
+
+    layout(location = 0) in vec2 corner;
+
+    out vec2 UV;
+
+    void main(){
+        gl_Position = vec4(corner, 0.0, 1.0);
+        UV = corner * 0.5 + 0.5;
+    }
+"#;
+
+/// The quad of `LAYER_VERTEX`
+pub(crate) fn layer_quad(gcx: &GCX) -> VertexArray {
+    let buffer = gcx.create_buffer(
+        BufferType::ArrayBuffer,
+        &[[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(|corner| LayerVertex { corner }),
+        BufferUsage::DRAW_STATIC,
+    );
+    gcx.create_vertex_array::<LayerVertex>(buffer).build(gcx)
+}
+
+/// The offscreen framebuffers that the masked nodes are drawn in, they have the size of the viewport
+pub(crate) struct MaskLayers {
+    content: Framebuffer,
+    mask: Framebuffer,
+    shader: Shader,
+    quad: VertexArray,
+}
+
+impl MaskLayers {
+    pub(crate) fn new(gcx: &GCX) -> Self {
+        let shader = gcx
+            .create_shader()
+            .vertex(LAYER_VERTEX)
+            .fragment(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                uniform sampler2D CONTENT;
+                uniform sampler2D MASK;
+                // 0 no mask, 1 the mask, -1 the inverted mask
+                uniform float Masked;
+
+                in vec2 UV;
+                out vec4 color;
+
+                void main(){
+                    float alpha = texture(MASK, UV).a;
+                    float factor = Masked > 0.5 ? alpha : Masked < -0.5 ? 1.0 - alpha : 1.0;
+                    color = texture(CONTENT, UV) * factor;
+                }
+            "#,
+            )
+            .build(gcx)
+            .unwrap();
+
+        let [width, height] = gcx.viewport_size();
+        Self {
+            content: gcx.create_framebuffer(width, height, true),
+            mask: gcx.create_framebuffer(width, height, false),
+            shader,
+            quad: layer_quad(gcx),
+        }
+    }
+
+    /// Makes the framebuffers again if the viewport has a different size
+    pub(crate) fn resize(&mut self, gcx: &GCX) {
+        let [width, height] = gcx.viewport_size();
+        if self.content.width() != width || self.content.height() != height {
+            self.content = gcx.create_framebuffer(width, height, true);
+            self.mask = gcx.create_framebuffer(width, height, false);
+        }
+    }
+
+    /// Draws `source` as the mask and `content` clipped by it on the current framebuffer
+    pub(crate) fn draw<S>(
+        &self,
+        gcx: &GCX,
+        mask: Mask,
+        state: &mut S,
+        source: impl FnOnce(&GCX, &mut S),
+        content: impl FnOnce(&GCX, &mut S),
+    ) {
+        let masked = match mask.mode {
+            MaskMode::Alpha => {
+                gcx.use_framebuffer(&self.mask, |gcx| {
+                    gcx.clear_color(0);
+                    gcx.clear(BufferBit::COLOR);
+                    source(gcx, state);
+                });
+                gcx.use_framebuffer(&self.content, |gcx| {
+                    gcx.clear_color(0);
+                    gcx.clear(BufferBit::COLOR);
+                    content(gcx, state);
+                });
+                if mask.inverted {
+                    -1.
+                } else {
+                    1.
+                }
+            }
+            MaskMode::Stencil => {
+                gcx.use_framebuffer(&self.content, |gcx| {
+                    gcx.clear_color(0);
+                    gcx.clear(BufferBit::COLOR);
+                    gcx.clear(BufferBit::STENCIL);
+                    gcx.set_stencil(Stencil::Write(1));
+                    source(gcx, state);
+                    gcx.set_stencil(if mask.inverted {
+                        Stencil::NotEqual(1)
+                    } else {
+                        Stencil::Equal(1)
+                    });
+                    content(gcx, state);
+                    gcx.set_stencil(Stencil::Off);
+                });
+                0.
+            }
+        };
+
+        let _blend = gcx.blend(BlendMode::Normal);
+        gcx.use_shader(&self.shader, |gcx| {
+            let _ = self.shader.set_uniform("CONTENT", 0);
+            let _ = self.shader.set_uniform("MASK", 1);
+            let _ = self.shader.set_uniform("Masked", masked);
+            self.content.texture().activate(0);
+            self.mask.texture().activate(1);
+            gcx.use_vertex_array(&self.quad, |gcx| {
+                gcx.draw_arrays(PrimitiveType::TrianglesFan, 0, 4);
+            });
+        });
+    }
+}
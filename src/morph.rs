@@ -0,0 +1,257 @@
+use crate::{
+    path::{PathData, Polyline},
+    signal::{lerp, Executor, Lerp, Signal},
+    stroke,
+};
+
+/// The minimum and maximum count of points that a sub path is resampled to
+const MIN_SAMPLES: usize = 32;
+const MAX_SAMPLES: usize = 1024;
+
+/// Precomputed interpolation between two `PathData`
+///
+/// Every sub path of both paths is resampled to the same count of points, closed sub paths are aligned
+/// to the same winding and start point, so the morph will not twist
+/// If the paths have a different count of sub paths the missing ones will grow from or shrink in to a point
+#[derive(Debug, Clone)]
+pub struct Morph {
+    from: PathData,
+    to: PathData,
+    pairs: Vec<(Polyline, Polyline)>,
+}
+
+impl Morph {
+    pub fn new(from: &PathData, to: &PathData) -> Self {
+        let a = contours(from);
+        let b = contours(to);
+
+        let pairs = (0..a.len().max(b.len()))
+            .map(|i| match (a.get(i), b.get(i)) {
+                (Some(a), Some(b)) => pair(a, b),
+                (Some(a), None) => pair(a, &collapsed(a)),
+                (None, Some(b)) => pair(&collapsed(b), b),
+                (None, None) => unreachable!(),
+            })
+            .collect();
+
+        Self {
+            from: from.clone(),
+            to: to.clone(),
+            pairs,
+        }
+    }
+
+    /// `0` is `from` and `1` is `to`
+    pub fn at(&self, time: f64) -> PathData {
+        if time <= 0. {
+            return self.from.clone();
+        }
+        if time >= 1. {
+            return self.to.clone();
+        }
+
+        let mut builder = PathData::builder();
+        for (a, b) in self.pairs.iter() {
+            let mut points = a
+                .points
+                .iter()
+                .zip(b.points.iter())
+                .map(|(a, b)| [lerp(a[0], b[0], time), lerp(a[1], b[1], time)]);
+
+            let Some(first) = points.next() else {
+                continue;
+            };
+            builder = points.fold(builder.move_to(first), |builder, p| builder.line_to(p));
+            if a.closed || b.closed {
+                builder = builder.close();
+            }
+        }
+        builder.build()
+    }
+}
+
+impl PathData {
+    pub fn morph(&self, to: &PathData) -> Morph {
+        Morph::new(self, to)
+    }
+}
+
+impl<'a> Signal<'a, PathData> {
+    /// Like `tween` but the `Morph` is built once, not on every frame
+    pub fn morph(&mut self, from: PathData, to: PathData, time: f64) -> Executor<'_> {
+        let morph = Morph::new(&from, &to);
+        self.tween_with(move |t| morph.at(t), time)
+    }
+}
+
+/// Resamples every time, for tweens use `Signal::morph`
+impl Lerp for PathData {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        if time <= 0. {
+            return self.clone();
+        }
+        if time >= 1. {
+            return to.clone();
+        }
+        Morph::new(self, to).at(time)
+    }
+}
+
+fn contours(path: &PathData) -> Vec<Polyline> {
+    path.polylines()
+        .into_iter()
+        .filter(|polyline| polyline.points.len() >= 2)
+        .collect()
+}
+
+/// A contour with every point at the center of `polyline`
+fn collapsed(polyline: &Polyline) -> Polyline {
+    let count = polyline.points.len() as f32;
+    let center = polyline
+        .points
+        .iter()
+        .fold([0., 0.], |c, p| [c[0] + p[0] / count, c[1] + p[1] / count]);
+
+    Polyline {
+        points: vec![center; 2],
+        closed: polyline.closed,
+    }
+}
+
+fn pair(a: &Polyline, b: &Polyline) -> (Polyline, Polyline) {
+    let count = a
+        .points
+        .len()
+        .max(b.points.len())
+        .clamp(MIN_SAMPLES, MAX_SAMPLES);
+
+    let a = resample(a, count);
+    let mut b = resample(b, count);
+
+    if a.closed && b.closed {
+        if signed_area(&a.points).is_sign_negative() != signed_area(&b.points).is_sign_negative() {
+            b.points.reverse();
+        }
+
+        let shift = (0..count)
+            .min_by(|x, y| {
+                let x = shifted_distance(&a.points, &b.points, *x);
+                let y = shifted_distance(&a.points, &b.points, *y);
+                x.total_cmp(&y)
+            })
+            .unwrap_or(0);
+        b.points.rotate_left(shift);
+    }
+
+    (a, b)
+}
+
+/// Sum of the squared distances between the points of `a` and `b` shifted by `shift`
+fn shifted_distance(a: &[[f32; 2]], b: &[[f32; 2]], shift: usize) -> f32 {
+    a.iter()
+        .zip(b.iter().cycle().skip(shift))
+        .map(|(a, b)| (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2))
+        .sum()
+}
+
+fn signed_area(points: &[[f32; 2]]) -> f32 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+        .sum::<f32>()
+        * 0.5
+}
+
+/// `count` points evenly spaced along the arc length
+/// for closed polylines the closing segment is included but the start point is not repeated
+fn resample(polyline: &Polyline, count: usize) -> Polyline {
+    let mut line = polyline.points.clone();
+    if polyline.closed {
+        line.push(line[0]);
+    }
+
+    let mut walked = vec![0.];
+    for w in line.windows(2) {
+        walked.push(walked[walked.len() - 1] + stroke::length(w));
+    }
+    let length = walked[walked.len() - 1];
+    let steps = if polyline.closed { count } else { count - 1 };
+
+    let mut segment = 0;
+    let points = (0..count)
+        .map(|i| {
+            let at = length * i as f32 / steps as f32;
+            while segment + 2 < line.len() && walked[segment + 1] < at {
+                segment += 1;
+            }
+
+            let (a, b) = (line[segment], line[segment + 1]);
+            let len = walked[segment + 1] - walked[segment];
+            let t = if len > 0. {
+                (at - walked[segment]) / len
+            } else {
+                0.
+            };
+            [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+        })
+        .collect();
+
+    Polyline {
+        points,
+        closed: polyline.closed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polyline(points: &[[f32; 2]], closed: bool) -> Polyline {
+        Polyline {
+            points: points.to_vec(),
+            closed,
+        }
+    }
+
+    #[test]
+    fn resample_by_arc_length() {
+        assert_eq!(
+            resample(&polyline(&[[0., 0.], [2., 0.], [10., 0.]], false), 6).points,
+            vec![[0., 0.], [2., 0.], [4., 0.], [6., 0.], [8., 0.], [10., 0.]]
+        );
+        // the closing segment is sampled, the start is not repeated
+        let square = [[0., 0.], [10., 0.], [10., 10.], [0., 10.]];
+        assert_eq!(resample(&polyline(&square, true), 4).points, square);
+    }
+
+    #[test]
+    fn resample_zero_length_segments() {
+        assert_eq!(
+            resample(&polyline(&[[0., 0.], [0., 0.], [10., 0.]], false), 3).points,
+            vec![[0., 0.], [5., 0.], [10., 0.]]
+        );
+    }
+
+    #[test]
+    fn pair_counts() {
+        let (a, b) = pair(
+            &polyline(&[[0., 0.], [1., 0.]], false),
+            &polyline(&[[0., 0.], [1., 0.], [2., 0.], [3., 0.], [4., 0.]], false),
+        );
+        assert_eq!((a.points.len(), b.points.len()), (MIN_SAMPLES, MIN_SAMPLES));
+        assert_eq!(b.points[MIN_SAMPLES - 1], [4., 0.]);
+    }
+
+    #[test]
+    fn pair_aligns_winding_and_start() {
+        // the same square clockwise from another corner
+        let (a, b) = pair(
+            &polyline(&[[0., 0.], [10., 0.], [10., 10.], [0., 10.]], true),
+            &polyline(&[[0., 10.], [10., 10.], [10., 0.], [0., 0.]], true),
+        );
+        for (a, b) in a.points.iter().zip(&b.points) {
+            assert!((a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4);
+        }
+    }
+}
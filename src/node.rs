@@ -1,6 +1,25 @@
 use std::any::{Any, TypeId};
 
-use crate::gcx::GCX;
+use crate::{
+    gcx::GCX,
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    shadow::Shadow,
+};
+
+/// What the engine needs to know to draw a node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawItem {
+    /// Nodes with a bigger z index are drawn above, with the same z index the order of registration is kept
+    pub z_index: i32,
+    pub parent: GroupId,
+    /// Clips the node, if `None` the mask of the group is used
+    pub mask: Option<Mask>,
+    /// The node is not drawn, it is the shape of this mask
+    pub mask_source: Option<MaskId>,
+    /// Drawn behind the node
+    pub shadow: Option<Shadow>,
+}
 
 pub trait NodeManager {
     type NodeBuilder: NodeBuilder;
@@ -8,22 +27,41 @@ pub trait NodeManager {
 
     fn init(&mut self, _gcx: &GCX) {}
 
-    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder);
+    /// `parent` is the group that the node was spawned in, use `SceneGraph::world` when rendering
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId);
     fn create_node(&mut self) -> Self::RawNode;
 
     fn update(&mut self);
-    fn render(&mut self, _gcx: &GCX) {}
+
+    /// Every node that can be drawn, the engine sorts them with the ones of the other managers
+    fn draw_items(&self) -> Vec<DrawItem> {
+        Vec::new()
+    }
+    /// The min and the max corner of every node of `draw_items` in its parent group, in the same order
+    /// a node that is missing is not in the bounds of a group
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        Vec::new()
+    }
+    /// `items` are indices in to `draw_items` in the order that they need to be drawn
+    fn render(&mut self, _gcx: &GCX, _graph: &SceneGraph, _items: &[usize]) {}
     fn audio_process(&mut self, _buffer: &mut [f32]) {}
 }
 
 pub trait AbstractNodeManager {
     fn init(&mut self, gcx: &GCX);
-    fn init_node(&mut self, gcx: &GCX, builder: Box<dyn Any + Send + Sync + 'static>);
+    fn init_node(
+        &mut self,
+        gcx: &GCX,
+        builder: Box<dyn Any + Send + Sync + 'static>,
+        parent: GroupId,
+    );
 
     fn create_node(&mut self) -> Box<dyn Any + Send + Sync + 'static>;
 
     fn update(&mut self);
-    fn render(&mut self, gcx: &GCX);
+    fn draw_items(&self) -> Vec<DrawItem>;
+    fn bounds(&self, graph: &SceneGraph) -> Vec<[[f32; 2]; 2]>;
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]);
     fn audio_process(&mut self, buffer: &mut [f32]);
 
     fn ty_id(&self) -> TypeId;
@@ -34,9 +72,14 @@ impl<T: NodeManager + 'static> AbstractNodeManager for T {
         self.init(gcx)
     }
 
-    fn init_node(&mut self, gcx: &GCX, builder: Box<dyn Any + Send + Sync + 'static>) {
+    fn init_node(
+        &mut self,
+        gcx: &GCX,
+        builder: Box<dyn Any + Send + Sync + 'static>,
+        parent: GroupId,
+    ) {
         let builder = Box::<dyn Any + Send + Sync>::downcast::<T::NodeBuilder>(builder).unwrap();
-        self.init_node(gcx, *builder);
+        self.init_node(gcx, *builder, parent);
     }
 
     fn create_node(&mut self) -> Box<dyn Any + Send + Sync + 'static> {
@@ -47,8 +90,16 @@ impl<T: NodeManager + 'static> AbstractNodeManager for T {
         self.update();
     }
 
-    fn render(&mut self, gcx: &GCX) {
-        self.render(gcx);
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.draw_items()
+    }
+
+    fn bounds(&self, graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.bounds(graph)
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        self.render(gcx, graph, items);
     }
 
     fn ty_id(&self) -> TypeId {
@@ -62,13 +113,67 @@ impl<T: NodeManager + 'static> AbstractNodeManager for T {
 
 use crate::scene::SceneTask;
 
+/// Where a node is, the engine maps it to pixels of the root group with `SceneTask::world_bounds`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeBounds {
+    /// The min and the max corner in the group
+    Local(GroupId, [[f32; 2]; 2]),
+    /// Every node that is drawn in the group or in its children
+    Group(GroupId),
+}
+
+/// A node that knows where it is, like for `Camera::focus_on`
+pub trait Bounds {
+    fn bounds(&self) -> NodeBounds;
+}
+
+/// The bounds of a node that is `size` away from the `position` in every direction
+pub(crate) fn centered([x, y]: [f32; 2], [width, height]: [f32; 2]) -> [[f32; 2]; 2] {
+    [[x - width, y - height], [x + width, y + height]]
+}
+
+/// The bounds of the corners of `centered` after they are rotated by `rotation` radians around the `position`
+pub(crate) fn rotated([x, y]: [f32; 2], [width, height]: [f32; 2], rotation: f32) -> [[f32; 2]; 2] {
+    let (sin, cos) = rotation.sin_cos();
+    [
+        [-width, -height],
+        [width, -height],
+        [width, height],
+        [-width, height],
+    ]
+    .map(|[dx, dy]| [[x + dx * cos - dy * sin, y + dx * sin + dy * cos]; 2])
+    .into_iter()
+    .reduce(union)
+    .unwrap()
+}
+
+/// The bounds of `offsets` from the `position` after the y is multiplied by `up`, like the shaders of the text
+pub(crate) fn offset_bounds(
+    [x, y]: [f32; 2],
+    [[min_x, min_y], [max_x, max_y]]: [[f32; 2]; 2],
+    up: f32,
+) -> [[f32; 2]; 2] {
+    let (a, b) = (y + min_y * up, y + max_y * up);
+    [[x + min_x, a.min(b)], [x + max_x, a.max(b)]]
+}
+
+/// The smallest bounds with `a` and `b` in them
+pub(crate) fn union(a: [[f32; 2]; 2], b: [[f32; 2]; 2]) -> [[f32; 2]; 2] {
+    [
+        [a[0][0].min(b[0][0]), a[0][1].min(b[0][1])],
+        [a[1][0].max(b[1][0]), a[1][1].max(b[1][1])],
+    ]
+}
+
 pub trait NodeBuilder: Send + Sync {
     type Node<'a>;
     type NodeManager: NodeManager;
 
+    /// `parent` is the group that the node is spawned in
     fn create_node_ref<'a>(
         &self,
         raw: <Self::NodeManager as NodeManager>::RawNode,
+        parent: GroupId,
         scene: &'a SceneTask,
     ) -> Self::Node<'a>;
 }
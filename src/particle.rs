@@ -0,0 +1,680 @@
+use crate::{
+    color::Color,
+    gcx::{
+        blend::BlendMode,
+        buffer::{BufferType, BufferUsage},
+        shader::Shader,
+        texture::Texture,
+        vertex_array::{Field, Fields, VertexArray},
+        PrimitiveType, GCX,
+    },
+    group::{GroupId, SceneGraph},
+    image::ImageData,
+    mask::{Mask, MaskId},
+    node::{Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    signal::{create_signal, Lerp, NSignal, RawSignal, Signal},
+};
+
+/// The simulation advances in steps of this many seconds, so it does not depend on the frame rate
+const STEP: f64 = 1. / 120.;
+
+/// What every particle looks like, it is tinted by the color of the particle
+#[derive(Debug, Clone)]
+pub enum ParticleShape {
+    Square,
+    Circle,
+    Image(ImageData),
+}
+
+/// `count` particles emitted at once `time` seconds after the node is first drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Burst {
+    pub time: f32,
+    pub count: u32,
+}
+
+#[derive(Debug)]
+pub struct ParticleBuilder {
+    pub(super) shape: ParticleShape,
+    pub(super) position: [f32; 2],
+    pub(super) rate: f32,
+    pub(super) bursts: Vec<Burst>,
+    pub(super) duration: Option<f32>,
+    pub(super) lifetime: [f32; 2],
+    pub(super) angle: [f32; 2],
+    pub(super) speed: [f32; 2],
+    pub(super) gravity: [f32; 2],
+    pub(super) drag: f32,
+    pub(super) colors: Vec<Color>,
+    pub(super) sizes: Vec<f32>,
+    pub(super) max_particles: usize,
+    pub(super) seed: u64,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl ParticleBuilder {
+    /// White particles of `8` pixels that live `1` second, nothing is emitted without a rate or a burst
+    pub fn new(shape: ParticleShape) -> Self {
+        Self {
+            shape,
+            position: [0.; 2],
+            rate: 0.,
+            bursts: Vec::new(),
+            duration: None,
+            lifetime: [1.; 2],
+            angle: [0., std::f32::consts::TAU],
+            speed: [0.; 2],
+            gravity: [0.; 2],
+            drag: 0.,
+            colors: vec![Color::WHITE],
+            sizes: vec![8.],
+            max_particles: 10_000,
+            seed: 0,
+            blend: BlendMode::Normal,
+            z_index: 0,
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    /// Where the particles are emitted
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Particles per second
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn with_burst(mut self, time: f32, count: u32) -> Self {
+        self.bursts.push(Burst { time, count });
+        self
+    }
+
+    /// The rate stops after `duration` seconds, the bursts are not affected
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Every particle lives a random time between `min` and `max` seconds
+    pub fn with_lifetime(mut self, [min, max]: [f32; 2]) -> Self {
+        self.lifetime = [min, max];
+        self
+    }
+
+    /// The direction of the start velocity is between the angles in radians
+    /// and its length between the speeds in pixels per second
+    pub fn with_velocity(mut self, angle: [f32; 2], speed: [f32; 2]) -> Self {
+        self.angle = angle;
+        self.speed = speed;
+        self
+    }
+
+    /// In pixels per second squared, `y` goes like the one of the `Origin`
+    pub fn with_gravity(mut self, gravity: [f32; 2]) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// How much of the velocity is lost every second, `0` is none
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag;
+        self
+    }
+
+    /// The colors from the birth to the death of a particle, evenly spaced
+    pub fn with_colors(mut self, colors: Vec<Color>) -> Self {
+        assert!(!colors.is_empty(), "A particle needs at least one color");
+        self.colors = colors;
+        self
+    }
+
+    /// The half sizes in pixels from the birth to the death of a particle, evenly spaced
+    pub fn with_sizes(mut self, sizes: Vec<f32>) -> Self {
+        assert!(!sizes.is_empty(), "A particle needs at least one size");
+        self.sizes = sizes;
+        self
+    }
+
+    /// No particle is emitted while there are this many
+    pub fn with_max_particles(mut self, max_particles: usize) -> Self {
+        self.max_particles = max_particles;
+        self
+    }
+
+    /// The same seed gives the same particles on every render
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+pub struct Particles<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+
+    /// Only moves the particles that are emitted after
+    pub position: Signal<'a, [f32; 2]>,
+    pub rate: Signal<'a, f32>,
+    pub gravity: Signal<'a, [f32; 2]>,
+    pub drag: Signal<'a, f32>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Particles<'a> {
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+}
+
+impl<'a> Bounds for Particles<'a> {
+    /// The emitter, the particles are not known by the scene
+    fn bounds(&self) -> NodeBounds {
+        let position = *self.position.value();
+        NodeBounds::Local(self.parent, [position, position])
+    }
+}
+
+impl<'a> Drop for Particles<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Particles when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for ParticleBuilder {
+    type Node<'a> = Particles<'a>;
+    type NodeManager = ParticleNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawParticles,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        Particles {
+            scene,
+            parent,
+            dropped: false,
+            position: Signal::new(raw.position, scene, self.position),
+            rate: Signal::new(raw.rate, scene, self.rate),
+            gravity: Signal::new(raw.gravity, scene, self.gravity),
+            drag: Signal::new(raw.drag, scene, self.drag),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+/// SplitMix64, small and the same on every platform
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Between `min` and `max`
+    fn range(&mut self, [min, max]: [f32; 2]) -> f32 {
+        let unit = (self.next() >> 40) as f32 / (1u64 << 24) as f32;
+        min + (max - min) * unit
+    }
+}
+
+struct Particle {
+    position: [f32; 2],
+    velocity: [f32; 2],
+    age: f32,
+    lifetime: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ParticleInstance {
+    position: [f32; 2],
+    size: f32,
+    color: Color,
+}
+
+impl Fields for ParticleInstance {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new::<[f32; 2]>("position"),
+            Field::new::<f32>("size"),
+            Field::new::<Color>("color"),
+        ]
+    }
+}
+
+pub struct NParticles {
+    /// One `ParticleInstance` per particle
+    va: VertexArray,
+    texture: Option<Texture>,
+    particles: Vec<Particle>,
+    rng: Rng,
+    /// The time of the scene graph when it was first drawn
+    start: Option<f64>,
+    /// Seconds that are simulated
+    simulated: f64,
+    /// Particles of the rate that are not emitted yet
+    pending: f32,
+    /// The bursts before this one are emitted
+    burst: usize,
+    parent: GroupId,
+    builder: ParticleBuilder,
+    inner: NParticlesInner,
+}
+
+pub struct NParticlesInner {
+    drop: NSignal<()>,
+    position: NSignal<[f32; 2]>,
+    rate: NSignal<f32>,
+    gravity: NSignal<[f32; 2]>,
+    drag: NSignal<f32>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+}
+
+pub struct RawParticles {
+    drop: RawSignal<()>,
+    position: RawSignal<[f32; 2]>,
+    rate: RawSignal<f32>,
+    gravity: RawSignal<[f32; 2]>,
+    drag: RawSignal<f32>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+}
+
+impl NParticles {
+    /// Simulates up to `time` seconds after the start
+    fn advance(&mut self, time: f64) {
+        while self.simulated + STEP <= time {
+            self.step();
+        }
+    }
+
+    fn step(&mut self) {
+        let builder = &self.builder;
+        let dt = STEP as f32;
+
+        let mut emit = 0;
+        if builder
+            .duration
+            .is_none_or(|duration| self.simulated < duration as f64)
+        {
+            self.pending += builder.rate.max(0.) * dt;
+            emit += self.pending as u32;
+            self.pending = self.pending.fract();
+        }
+        while let Some(burst) = builder.bursts.get(self.burst) {
+            if burst.time as f64 >= self.simulated + STEP {
+                break;
+            }
+            emit += burst.count;
+            self.burst += 1;
+        }
+        for _ in 0..emit {
+            if self.particles.len() >= builder.max_particles {
+                break;
+            }
+            let lifetime = self.rng.range(builder.lifetime);
+            let angle = self.rng.range(builder.angle);
+            let speed = self.rng.range(builder.speed);
+            self.particles.push(Particle {
+                position: builder.position,
+                velocity: [angle.cos() * speed, angle.sin() * speed],
+                age: 0.,
+                lifetime,
+            });
+        }
+
+        let [gx, gy] = builder.gravity;
+        let drag = (-builder.drag.max(0.) * dt).exp();
+        self.particles.retain_mut(|particle| {
+            particle.age += dt;
+            if particle.age >= particle.lifetime {
+                return false;
+            }
+            let [vx, vy] = &mut particle.velocity;
+            *vx = (*vx + gx * dt) * drag;
+            *vy = (*vy + gy * dt) * drag;
+            particle.position[0] += *vx * dt;
+            particle.position[1] += *vy * dt;
+            true
+        });
+
+        self.simulated += STEP;
+    }
+
+    fn instances(&self) -> Vec<ParticleInstance> {
+        self.particles
+            .iter()
+            .map(|particle| {
+                let life = (particle.age / particle.lifetime.max(f32::EPSILON)).clamp(0., 1.);
+                ParticleInstance {
+                    position: particle.position,
+                    size: over_life(&self.builder.sizes, life),
+                    color: over_life(&self.builder.colors, life),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The value at `life` from `0` to `1` of evenly spaced `values`
+fn over_life<T: Lerp + Clone>(values: &[T], life: f32) -> T {
+    let at = life * (values.len() - 1) as f32;
+    let i = (at as usize).min(values.len() - 1);
+    match values.get(i + 1) {
+        Some(next) => values[i].lerp(next, (at - i as f32) as f64),
+        None => values[i].clone(),
+    }
+}
+
+#[derive(Default)]
+pub struct ParticleNodeManager {
+    pub(super) particles: Vec<NParticles>,
+    shader: Option<Shader>,
+
+    pending: Option<NParticlesInner>,
+}
+
+impl NodeManager for ParticleNodeManager {
+    type NodeBuilder = ParticleBuilder;
+    type RawNode = RawParticles;
+
+    fn init(&mut self, gcx: &GCX) {
+        let shader = gcx
+            .create_shader()
+            .vertex(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                layout(location = 0) in vec2 position;
+                layout(location = 1) in float size;
+                layout(location = 2) in vec4 color;
+
+                uniform mat3 World;
+                uniform float WorldUp;
+
+                out vec2 Corner;
+                out vec4 Color;
+
+                void main(){
+                    // a strip of four vertices without a buffer
+                    vec2 corner = vec2(gl_VertexID & 1, gl_VertexID >> 1) * 2.0 - 1.0;
+                    vec2 pos = position + corner * size * vec2(1.0, WorldUp);
+                    gl_Position = vec4((World * vec3(pos, 1.0)).xy, 0.0, 1.0);
+                    Corner = corner;
+                    Color = vec4(color.rgb * color.a, color.a);
+                }
+            "#,
+            )
+            .fragment(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                // 0 square, 1 circle, 2 image
+                uniform int Shape;
+                uniform sampler2D IMAGE;
+                uniform float WorldOpacity;
+
+                in vec2 Corner;
+                in vec4 Color;
+                out vec4 color;
+
+                void main(){
+                    float alpha = 1.0;
+                    if (Shape == 1) {
+                        float distance = length(Corner);
+                        alpha = 1.0 - smoothstep(1.0 - fwidth(distance), 1.0, distance);
+                    }
+                    vec4 tint = Color * alpha * WorldOpacity;
+                    if (Shape == 2) {
+                        tint *= texture(IMAGE, vec2(Corner.x, -Corner.y) * 0.5 + 0.5);
+                    }
+                    color = tint;
+                }
+            "#,
+            )
+            .build(gcx)
+            .unwrap();
+
+        self.shader.replace(shader);
+    }
+
+    fn init_node(&mut self, gcx: &GCX, mut builder: Self::NodeBuilder, parent: GroupId) {
+        let buffer = gcx.create_buffer::<ParticleInstance>(
+            BufferType::ArrayBuffer,
+            &[],
+            BufferUsage::DRAW_DYNAMIC,
+        );
+        let va = gcx
+            .create_vertex_array::<ParticleInstance>(buffer)
+            .per_instance()
+            .build(gcx);
+        let texture = match &builder.shape {
+            ParticleShape::Image(image) => Some(image.upload(gcx)),
+            _ => None,
+        };
+        builder.bursts.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        self.particles.push(NParticles {
+            va,
+            texture,
+            particles: Vec::new(),
+            rng: Rng(builder.seed),
+            start: None,
+            simulated: 0.,
+            pending: 0.,
+            burst: 0,
+            parent,
+            builder,
+            inner: self.pending.take().unwrap(),
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.particles
+            .iter()
+            .map(|particles| DrawItem {
+                z_index: particles.builder.z_index,
+                parent: particles.parent,
+                mask: particles.builder.mask,
+                mask_source: particles.builder.mask_source,
+                shadow: None,
+            })
+            .collect()
+    }
+
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.particles
+            .iter()
+            .map(|particles| [particles.builder.position; 2])
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let Some(shader) = &self.shader else { panic!() };
+        gcx.use_shader(shader, |gcx| {
+            for &i in items {
+                let particles = &mut self.particles[i];
+                // simulated here because only the scene graph knows the time
+                let start = *particles.start.get_or_insert(graph.time());
+                particles.advance(graph.time() - start);
+                if particles.particles.is_empty() {
+                    continue;
+                }
+                let instances = particles.instances();
+                particles
+                    .va
+                    .array_buffer
+                    .set_data(&instances, BufferUsage::DRAW_DYNAMIC);
+
+                graph.world(particles.parent).set_uniforms(shader);
+                let shape = match &particles.builder.shape {
+                    ParticleShape::Square => 0,
+                    ParticleShape::Circle => 1,
+                    ParticleShape::Image(_) => 2,
+                };
+                let _ = shader.set_uniform("Shape", shape);
+                if let Some(texture) = &particles.texture {
+                    let _ = shader.set_uniform("IMAGE", 0);
+                    texture.activate(0);
+                }
+                let _blend = gcx.blend(particles.builder.blend);
+                gcx.use_vertex_array(&particles.va, |gcx| {
+                    gcx.draw_arrays_instanced(
+                        PrimitiveType::TrianglesStrip,
+                        0,
+                        4,
+                        instances.len() as i32,
+                    );
+                });
+            }
+        });
+    }
+
+    fn create_node(&mut self) -> RawParticles {
+        let (nposition, position) = create_signal();
+        let (nrate, rate) = create_signal();
+        let (ngravity, gravity) = create_signal();
+        let (ndrag, drag) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NParticlesInner {
+            drop,
+            position,
+            rate,
+            gravity,
+            drag,
+            blend,
+            z_index,
+        });
+
+        RawParticles {
+            drop: ndrop,
+            position: nposition,
+            rate: nrate,
+            gravity: ngravity,
+            drag: ndrag,
+            blend: nblend,
+            z_index: nz_index,
+        }
+    }
+
+    fn update(&mut self) {
+        self.particles.retain_mut(|particles| {
+            if let Some(position) = particles.inner.position.get() {
+                particles.builder.position = position;
+            }
+            if let Some(rate) = particles.inner.rate.get() {
+                particles.builder.rate = rate;
+            }
+            if let Some(gravity) = particles.inner.gravity.get() {
+                particles.builder.gravity = gravity;
+            }
+            if let Some(drag) = particles.inner.drag.get() {
+                particles.builder.drag = drag;
+            }
+            if let Some(blend) = particles.inner.blend.get() {
+                particles.builder.blend = blend;
+            }
+            if let Some(z_index) = particles.inner.z_index.get() {
+                particles.builder.z_index = z_index;
+            }
+
+            particles.inner.drop.get().is_none()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let (mut a, mut b) = (Rng(7), Rng(7));
+        for _ in 0..100 {
+            assert_eq!(a.next(), b.next());
+        }
+        assert_ne!(Rng(7).next(), Rng(8).next());
+    }
+
+    #[test]
+    fn rng_range() {
+        let mut rng = Rng(0);
+        let mut sum = 0.;
+        for _ in 0..10_000 {
+            let value = rng.range([-2., 3.]);
+            assert!((-2. ..3.).contains(&value));
+            sum += value;
+        }
+        assert!((sum / 10_000. - 0.5).abs() < 0.1);
+        assert_eq!(rng.range([4., 4.]), 4.);
+    }
+
+    #[test]
+    fn over_life_lerps_between_values() {
+        let values = [0f32, 10., 30.];
+        assert_eq!(over_life(&values, 0.), 0.);
+        assert_eq!(over_life(&values, 0.25), 5.);
+        assert_eq!(over_life(&values, 0.5), 10.);
+        assert_eq!(over_life(&values, 0.75), 20.);
+        assert_eq!(over_life(&values, 1.), 30.);
+        assert_eq!(over_life(&values, 2.), 30.);
+        assert_eq!(over_life(&[8f32], 0.5), 8.);
+    }
+}
@@ -0,0 +1,1103 @@
+use lyon::{
+    algorithms::aabb::bounding_box,
+    geom::{point, vector, Angle, ArcFlags},
+    path::{
+        builder::{SvgPathBuilder, WithSvg},
+        iterator::PathIterator,
+        path::BuilderImpl,
+        PathEvent,
+    },
+    tessellation::{self, BuffersBuilder, FillOptions, FillTessellator, FillVertex, VertexBuffers},
+};
+
+use crate::{
+    color::Color,
+    fill::{Fill, FillRamp, FILL_GLSL},
+    gcx::{
+        blend::BlendMode,
+        buffer::{BufferType, BufferUsage},
+        shader::Shader,
+        vertex_array::{Field, Fields, VertexArray},
+        PrimitiveType, GCX,
+    },
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    node::{Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    shadow::{Glow, Shadow},
+    signal::{create_signal, NSignal, RawSignal, Signal},
+    stroke::{self, Stroke, TOLERANCE},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl From<FillRule> for tessellation::FillRule {
+    fn from(value: FillRule) -> Self {
+        match value {
+            FillRule::NonZero => Self::NonZero,
+            FillRule::EvenOdd => Self::EvenOdd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    /// The byte position and the char that was not expected
+    UnexpectedChar(usize, char),
+    /// The byte position where a number was expected
+    ExpectedNumber(usize),
+    /// The byte position where a arc flag `0` or `1` was expected
+    ExpectedFlag(usize),
+    /// The path data needs to start with `M` or `m`
+    MissingMoveTo,
+}
+
+impl std::fmt::Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathError::UnexpectedChar(at, c) => write!(f, "Unexpected `{c}` at {at}"),
+            PathError::ExpectedNumber(at) => write!(f, "Expected a number at {at}"),
+            PathError::ExpectedFlag(at) => write!(f, "Expected `0` or `1` at {at}"),
+            PathError::MissingMoveTo => write!(f, "The path data needs to start with a move to"),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// A flattened sub path
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<[f32; 2]>,
+    pub closed: bool,
+}
+
+/// The geometry of a `Path` node
+#[derive(Debug, Clone, Default)]
+pub struct PathData {
+    pub(crate) path: lyon::path::Path,
+}
+
+impl PathData {
+    pub fn builder() -> PathDataBuilder {
+        PathDataBuilder {
+            builder: lyon::path::Path::builder().with_svg(),
+        }
+    }
+
+    /// Parses SVG path data like `M 0 0 L 0.5 0 C 0.5 0.5 0 0.5 0 0 Z`
+    pub fn parse(src: &str) -> Result<Self, PathError> {
+        SvgParser {
+            src: src.as_bytes(),
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// A closed circle, the start is on the right of the center
+    pub fn circle(center: [f32; 2], radius: f32) -> Self {
+        let [x, y] = center;
+        Self::builder()
+            .move_to([x + radius, y])
+            .arc_to([radius; 2], 0., false, true, [x - radius, y])
+            .arc_to([radius; 2], 0., false, true, [x + radius, y])
+            .close()
+            .build()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.path.iter().next().is_none()
+    }
+
+    /// Every sub path flattened in to line segments
+    pub fn polylines(&self) -> Vec<Polyline> {
+        let mut polylines = Vec::new();
+        let mut points = Vec::new();
+        for event in self.path.iter().flattened(TOLERANCE) {
+            match event {
+                PathEvent::Begin { at } => {
+                    points.push(at.to_array());
+                }
+                PathEvent::Line { to, .. } => {
+                    points.push(to.to_array());
+                }
+                PathEvent::End { close, .. } => {
+                    polylines.push(Polyline {
+                        points: std::mem::take(&mut points),
+                        closed: close,
+                    });
+                }
+                PathEvent::Quadratic { .. } | PathEvent::Cubic { .. } => {
+                    unreachable!("The path is flattened")
+                }
+            }
+        }
+        polylines
+    }
+}
+
+impl std::str::FromStr for PathData {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Every command will start a new sub path if is needed
+pub struct PathDataBuilder {
+    builder: WithSvg<BuilderImpl>,
+}
+
+impl PathDataBuilder {
+    pub fn move_to(mut self, to: [f32; 2]) -> Self {
+        self.builder.move_to(to.into());
+        self
+    }
+
+    pub fn line_to(mut self, to: [f32; 2]) -> Self {
+        self.builder.line_to(to.into());
+        self
+    }
+
+    pub fn quadratic_to(mut self, ctrl: [f32; 2], to: [f32; 2]) -> Self {
+        self.builder.quadratic_bezier_to(ctrl.into(), to.into());
+        self
+    }
+
+    pub fn cubic_to(mut self, ctrl1: [f32; 2], ctrl2: [f32; 2], to: [f32; 2]) -> Self {
+        self.builder
+            .cubic_bezier_to(ctrl1.into(), ctrl2.into(), to.into());
+        self
+    }
+
+    /// Like the SVG `A` command, `x_rotation` is in degrees
+    pub fn arc_to(
+        mut self,
+        radii: [f32; 2],
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: [f32; 2],
+    ) -> Self {
+        self.builder.arc_to(
+            radii.into(),
+            Angle::degrees(x_rotation),
+            ArcFlags { large_arc, sweep },
+            to.into(),
+        );
+        self
+    }
+
+    /// Connects the current point with the start of the sub path
+    pub fn close(mut self) -> Self {
+        self.builder.close();
+        self
+    }
+
+    pub fn build(self) -> PathData {
+        PathData {
+            path: self.builder.build(),
+        }
+    }
+}
+
+struct SvgParser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SvgParser<'a> {
+    fn skip_separators(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r' | b',') = self.src.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.src.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn number(&mut self) -> Result<f32, PathError> {
+        self.skip_separators();
+        let start = self.pos;
+
+        if let Some(b'+' | b'-') = self.src.get(self.pos) {
+            self.pos += 1;
+        }
+        let mut digits = self.digits();
+        if let Some(b'.') = self.src.get(self.pos) {
+            self.pos += 1;
+            digits += self.digits();
+        }
+        if digits == 0 {
+            self.pos = start;
+            return Err(PathError::ExpectedNumber(start));
+        }
+        if let Some(b'e' | b'E') = self.src.get(self.pos) {
+            let mantissa = self.pos;
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.src.get(self.pos) {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                self.pos = mantissa;
+            }
+        }
+
+        std::str::from_utf8(&self.src[start..self.pos])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or(PathError::ExpectedNumber(start))
+    }
+
+    fn point(&mut self) -> Result<[f32; 2], PathError> {
+        Ok([self.number()?, self.number()?])
+    }
+
+    fn flag(&mut self) -> Result<bool, PathError> {
+        self.skip_separators();
+        let flag = match self.src.get(self.pos) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(PathError::ExpectedFlag(self.pos)),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+
+    fn parse(mut self) -> Result<PathData, PathError> {
+        let mut builder = lyon::path::Path::builder().with_svg();
+        let mut command = None;
+
+        loop {
+            self.skip_separators();
+            let Some(&c) = self.src.get(self.pos) else {
+                break;
+            };
+
+            if c.is_ascii_alphabetic() {
+                if command.is_none() && !matches!(c, b'M' | b'm') {
+                    return Err(PathError::MissingMoveTo);
+                }
+                command = Some(c);
+                self.pos += 1;
+            } else if command.is_none() {
+                return Err(PathError::MissingMoveTo);
+            } else if matches!(command, Some(b'Z' | b'z')) {
+                return Err(PathError::UnexpectedChar(self.pos, c as char));
+            }
+
+            let relative = command.is_some_and(|c| c.is_ascii_lowercase());
+            match command.map(|c| c.to_ascii_uppercase()) {
+                Some(b'M') => {
+                    let [x, y] = self.point()?;
+                    if relative {
+                        builder.relative_move_to(vector(x, y));
+                    } else {
+                        builder.move_to(point(x, y));
+                    }
+                    // the next coordinates are implicit line to commands
+                    command = Some(if relative { b'l' } else { b'L' });
+                }
+                Some(b'L') => {
+                    let [x, y] = self.point()?;
+                    if relative {
+                        builder.relative_line_to(vector(x, y));
+                    } else {
+                        builder.line_to(point(x, y));
+                    }
+                }
+                Some(b'H') => {
+                    let x = self.number()?;
+                    if relative {
+                        builder.relative_horizontal_line_to(x);
+                    } else {
+                        builder.horizontal_line_to(x);
+                    }
+                }
+                Some(b'V') => {
+                    let y = self.number()?;
+                    if relative {
+                        builder.relative_vertical_line_to(y);
+                    } else {
+                        builder.vertical_line_to(y);
+                    }
+                }
+                Some(b'C') => {
+                    let ([x1, y1], [x2, y2], [x, y]) =
+                        (self.point()?, self.point()?, self.point()?);
+                    if relative {
+                        builder.relative_cubic_bezier_to(
+                            vector(x1, y1),
+                            vector(x2, y2),
+                            vector(x, y),
+                        );
+                    } else {
+                        builder.cubic_bezier_to(point(x1, y1), point(x2, y2), point(x, y));
+                    }
+                }
+                Some(b'S') => {
+                    let ([x2, y2], [x, y]) = (self.point()?, self.point()?);
+                    if relative {
+                        builder.smooth_relative_cubic_bezier_to(vector(x2, y2), vector(x, y));
+                    } else {
+                        builder.smooth_cubic_bezier_to(point(x2, y2), point(x, y));
+                    }
+                }
+                Some(b'Q') => {
+                    let ([x1, y1], [x, y]) = (self.point()?, self.point()?);
+                    if relative {
+                        builder.relative_quadratic_bezier_to(vector(x1, y1), vector(x, y));
+                    } else {
+                        builder.quadratic_bezier_to(point(x1, y1), point(x, y));
+                    }
+                }
+                Some(b'T') => {
+                    let [x, y] = self.point()?;
+                    if relative {
+                        builder.smooth_relative_quadratic_bezier_to(vector(x, y));
+                    } else {
+                        builder.smooth_quadratic_bezier_to(point(x, y));
+                    }
+                }
+                Some(b'A') => {
+                    let [rx, ry] = self.point()?;
+                    let x_rotation = Angle::degrees(self.number()?);
+                    let flags = ArcFlags {
+                        large_arc: self.flag()?,
+                        sweep: self.flag()?,
+                    };
+                    let [x, y] = self.point()?;
+                    if relative {
+                        builder.relative_arc_to(vector(rx, ry), x_rotation, flags, vector(x, y));
+                    } else {
+                        builder.arc_to(vector(rx, ry), x_rotation, flags, point(x, y));
+                    }
+                }
+                Some(b'Z') => {
+                    builder.close();
+                }
+                _ => return Err(PathError::UnexpectedChar(self.pos - 1, c as char)),
+            }
+        }
+
+        Ok(PathData {
+            path: builder.build(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PathBuilder {
+    pub(super) data: PathData,
+    pub(super) position: [f32; 2],
+    pub(super) fill: Fill,
+    pub(super) fill_rule: FillRule,
+    pub(super) stroke: Stroke,
+    pub(super) stroke_color: Color,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) shadow: Shadow,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl PathBuilder {
+    /// Without a fill or a stroke nothing will be visible
+    pub fn new(data: PathData) -> Self {
+        Self {
+            data,
+            position: [0.; 2],
+            fill: Fill::default(),
+            fill_rule: FillRule::NonZero,
+            stroke: Stroke::new(0.),
+            stroke_color: Color::TRANSPARENT,
+            blend: BlendMode::Normal,
+            z_index: 0,
+            shadow: Shadow::default(),
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// The gradient points are relative to the position
+    pub fn with_fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.fill = fill.into();
+        self
+    }
+
+    pub fn with_fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    pub fn with_stroke(mut self, width: f32, color: impl Into<Color>) -> Self {
+        self.stroke.width = width;
+        self.stroke_color = color.into();
+        self
+    }
+
+    /// Caps, joins, dashes and arrows of the stroke
+    pub fn with_stroke_style(mut self, stroke: Stroke) -> Self {
+        self.stroke = stroke;
+        self
+    }
+
+    /// The visible part of the stroke from `0` to `1` along the arc length
+    pub fn with_trim(mut self, start: f32, end: f32) -> Self {
+        self.stroke.trim_start = start;
+        self.stroke.trim_end = end;
+        self
+    }
+
+    pub fn with_trim_offset(mut self, offset: f32) -> Self {
+        self.stroke.trim_offset = offset;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// A shadow behind the node, `offset` and `blur` are in pixels of the screen
+    pub fn with_shadow(mut self, color: impl Into<Color>, offset: [f32; 2], blur: f32) -> Self {
+        self.shadow.color = color.into();
+        self.shadow.offset = offset;
+        self.shadow.blur = blur;
+        self
+    }
+
+    /// A glow around the node, `radius` is in pixels of the screen
+    pub fn with_glow(mut self, color: impl Into<Color>, radius: f32) -> Self {
+        self.shadow.glow = Glow {
+            color: color.into(),
+            radius,
+        };
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+pub struct Path<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+
+    pub data: Signal<'a, PathData>,
+    pub position: Signal<'a, [f32; 2]>,
+    pub fill: Signal<'a, Fill>,
+    pub stroke_color: Signal<'a, Color>,
+    pub stroke_width: Signal<'a, f32>,
+    pub dash_offset: Signal<'a, f32>,
+    pub trim_start: Signal<'a, f32>,
+    pub trim_end: Signal<'a, f32>,
+    pub trim_offset: Signal<'a, f32>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+    pub shadow_color: Signal<'a, Color>,
+    pub shadow_offset: Signal<'a, [f32; 2]>,
+    pub shadow_blur: Signal<'a, f32>,
+    pub glow: Signal<'a, Glow>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Path<'a> {
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+}
+
+impl<'a> Bounds for Path<'a> {
+    fn bounds(&self) -> NodeBounds {
+        NodeBounds::Local(
+            self.parent,
+            path_bounds(
+                self.data.value(),
+                *self.position.value(),
+                *self.stroke_width.value(),
+                *self.stroke_color.value(),
+            ),
+        )
+    }
+}
+
+/// Half of the stroke is outside of the path
+fn path_bounds(
+    data: &PathData,
+    [x, y]: [f32; 2],
+    stroke_width: f32,
+    stroke_color: Color,
+) -> [[f32; 2]; 2] {
+    if data.is_empty() {
+        return [[x, y], [x, y]];
+    }
+
+    let bounds = bounding_box(data.path.iter());
+    let half = if stroke_color.a > 0. {
+        stroke_width / 2.
+    } else {
+        0.
+    };
+    [
+        [bounds.min.x + x - half, bounds.min.y + y - half],
+        [bounds.max.x + x + half, bounds.max.y + y + half],
+    ]
+}
+
+impl<'a> Drop for Path<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Path when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for PathBuilder {
+    type Node<'a> = Path<'a>;
+    type NodeManager = PathNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawPath,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        Path {
+            scene,
+            parent,
+            dropped: false,
+            data: Signal::new(raw.data, scene, self.data.clone()),
+            position: Signal::new(raw.position, scene, self.position),
+            fill: Signal::new(raw.fill, scene, self.fill.clone()),
+            stroke_color: Signal::new(raw.stroke_color, scene, self.stroke_color),
+            stroke_width: Signal::new(raw.stroke_width, scene, self.stroke.width),
+            dash_offset: Signal::new(raw.dash_offset, scene, self.stroke.dash_offset),
+            trim_start: Signal::new(raw.trim_start, scene, self.stroke.trim_start),
+            trim_end: Signal::new(raw.trim_end, scene, self.stroke.trim_end),
+            trim_offset: Signal::new(raw.trim_offset, scene, self.stroke.trim_offset),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            shadow_color: Signal::new(raw.shadow_color, scene, self.shadow.color),
+            shadow_offset: Signal::new(raw.shadow_offset, scene, self.shadow.offset),
+            shadow_blur: Signal::new(raw.shadow_blur, scene, self.shadow.blur),
+            glow: Signal::new(raw.glow, scene, self.shadow.glow),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+pub struct NPath {
+    va: VertexArray,
+    ramp: FillRamp,
+    /// Of the indices
+    count: i32,
+    parent: GroupId,
+    builder: PathBuilder,
+    inner: NPathInner,
+}
+
+pub struct NPathInner {
+    drop: NSignal<()>,
+    data: NSignal<PathData>,
+    position: NSignal<[f32; 2]>,
+    fill: NSignal<Fill>,
+    stroke_color: NSignal<Color>,
+    stroke_width: NSignal<f32>,
+    dash_offset: NSignal<f32>,
+    trim_start: NSignal<f32>,
+    trim_end: NSignal<f32>,
+    trim_offset: NSignal<f32>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+    shadow_color: NSignal<Color>,
+    shadow_offset: NSignal<[f32; 2]>,
+    shadow_blur: NSignal<f32>,
+    glow: NSignal<Glow>,
+}
+
+pub struct RawPath {
+    drop: RawSignal<()>,
+    data: RawSignal<PathData>,
+    position: RawSignal<[f32; 2]>,
+    fill: RawSignal<Fill>,
+    stroke_color: RawSignal<Color>,
+    stroke_width: RawSignal<f32>,
+    dash_offset: RawSignal<f32>,
+    trim_start: RawSignal<f32>,
+    trim_end: RawSignal<f32>,
+    trim_offset: RawSignal<f32>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+    shadow_color: RawSignal<Color>,
+    shadow_offset: RawSignal<[f32; 2]>,
+    shadow_blur: RawSignal<f32>,
+    glow: RawSignal<Glow>,
+}
+
+#[derive(Default)]
+pub struct PathNodeManager {
+    pub(super) paths: Vec<NPath>,
+    pub(super) shader: Option<Shader>,
+
+    pending: Option<NPathInner>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PathVertex {
+    position: [f32; 2],
+    color: Color,
+    /// `1` if the color comes from the fill
+    filled: f32,
+}
+
+impl Fields for PathVertex {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new::<[f32; 2]>("position"),
+            Field::new::<Color>("color"),
+            Field::new::<f32>("filled"),
+        ]
+    }
+}
+
+impl NodeManager for PathNodeManager {
+    type NodeBuilder = PathBuilder;
+    type RawNode = RawPath;
+
+    fn init(&mut self, gcx: &GCX) {
+        let shader = gcx
+            .create_shader()
+            .vertex(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                layout(location = 0) in vec2 pos;
+                layout(location = 1) in vec4 color;
+                layout(location = 2) in float filled;
+
+                uniform vec2 Offset;
+                uniform mat3 World;
+
+                out vec2 Local;
+                out vec4 VertexColor;
+                out float Filled;
+
+                void main(){
+                    gl_Position = vec4((World * vec3(pos + Offset, 1.0)).xy, 0.0, 1.0);
+                    Local = pos;
+                    VertexColor = color;
+                    Filled = filled;
+                }
+            "#,
+            )
+            .fragment(format!(
+                r#"
+                #version 320 es
+
+                precision highp float;
+
+                {FILL_GLSL}
+
+                uniform float WorldOpacity;
+
+                in vec2 Local;
+                in vec4 VertexColor;
+                in float Filled;
+                out vec4 color;
+                void main(){{
+                    if (Filled > 0.5) {{
+                        color = fill(Local);
+                    }} else {{
+                        color = vec4(VertexColor.rgb * VertexColor.a, VertexColor.a);
+                    }}
+                    color *= WorldOpacity;
+                }}
+                "#,
+            ))
+            .build(gcx)
+            .unwrap();
+
+        self.shader.replace(shader);
+    }
+
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
+        let (mesh, indices) = Self::build_mesh(&builder);
+        let buffer = gcx.create_buffer(BufferType::ArrayBuffer, &mesh, BufferUsage::DRAW_DYNAMIC);
+        let index_buffer = gcx.create_buffer(
+            BufferType::ElementArrayBuffer,
+            &indices,
+            BufferUsage::DRAW_DYNAMIC,
+        );
+        let va = gcx
+            .create_vertex_array::<PathVertex>(buffer)
+            .add_index_buffer::<u32>(index_buffer)
+            .build(gcx);
+        self.paths.push(NPath {
+            va,
+            ramp: FillRamp::new(gcx, &builder.fill),
+            count: indices.len() as i32,
+            parent,
+            builder,
+            inner: self.pending.take().unwrap(),
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.paths
+            .iter()
+            .map(|path| DrawItem {
+                z_index: path.builder.z_index,
+                parent: path.parent,
+                mask: path.builder.mask,
+                mask_source: path.builder.mask_source,
+                shadow: Some(path.builder.shadow).filter(Shadow::is_visible),
+            })
+            .collect()
+    }
+
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.paths
+            .iter()
+            .map(|path| {
+                let builder = &path.builder;
+                path_bounds(
+                    &builder.data,
+                    builder.position,
+                    builder.stroke.width,
+                    builder.stroke_color,
+                )
+            })
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let Some(shader) = &self.shader else { panic!() };
+        gcx.use_shader(shader, |gcx| {
+            for path in items.iter().map(|&i| &self.paths[i]) {
+                if path.count == 0 {
+                    continue;
+                }
+
+                let [x, y] = path.builder.position;
+                let _ = shader.set_uniform("Offset", (x, y));
+                graph.world(path.parent).set_uniforms(shader);
+                path.ramp.set_uniforms(shader, &path.builder.fill, 0);
+                let _blend = gcx.blend(path.builder.blend);
+                gcx.use_vertex_array(&path.va, |gcx| {
+                    gcx.draw_elements(PrimitiveType::Triangles, path.count);
+                });
+            }
+        });
+    }
+
+    fn create_node(&mut self) -> RawPath {
+        let (ndata, data) = create_signal();
+        let (nposition, position) = create_signal();
+        let (nfill, fill) = create_signal();
+        let (nstroke_color, stroke_color) = create_signal();
+        let (nstroke_width, stroke_width) = create_signal();
+        let (ndash_offset, dash_offset) = create_signal();
+        let (ntrim_start, trim_start) = create_signal();
+        let (ntrim_end, trim_end) = create_signal();
+        let (ntrim_offset, trim_offset) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (nshadow_color, shadow_color) = create_signal();
+        let (nshadow_offset, shadow_offset) = create_signal();
+        let (nshadow_blur, shadow_blur) = create_signal();
+        let (nglow, glow) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NPathInner {
+            drop,
+            data,
+            position,
+            fill,
+            stroke_color,
+            stroke_width,
+            dash_offset,
+            trim_start,
+            trim_end,
+            trim_offset,
+            blend,
+            z_index,
+            shadow_color,
+            shadow_offset,
+            shadow_blur,
+            glow,
+        });
+
+        RawPath {
+            drop: ndrop,
+            data: ndata,
+            position: nposition,
+            fill: nfill,
+            stroke_color: nstroke_color,
+            stroke_width: nstroke_width,
+            dash_offset: ndash_offset,
+            trim_start: ntrim_start,
+            trim_end: ntrim_end,
+            trim_offset: ntrim_offset,
+            blend: nblend,
+            z_index: nz_index,
+            shadow_color: nshadow_color,
+            shadow_offset: nshadow_offset,
+            shadow_blur: nshadow_blur,
+            glow: nglow,
+        }
+    }
+
+    fn update(&mut self) {
+        self.paths.retain_mut(|path| {
+            let mut rebuild = false;
+            if let Some(data) = path.inner.data.get() {
+                path.builder.data = data;
+                rebuild = true;
+            }
+            if let Some(position) = path.inner.position.get() {
+                path.builder.position = position;
+            }
+            if let Some(fill) = path.inner.fill.get() {
+                path.ramp.update(&fill);
+                path.builder.fill = fill;
+                rebuild = true;
+            }
+            if let Some(stroke_color) = path.inner.stroke_color.get() {
+                path.builder.stroke_color = stroke_color;
+                rebuild = true;
+            }
+            if let Some(stroke_width) = path.inner.stroke_width.get() {
+                path.builder.stroke.width = stroke_width;
+                rebuild = true;
+            }
+            if let Some(dash_offset) = path.inner.dash_offset.get() {
+                path.builder.stroke.dash_offset = dash_offset;
+                rebuild = true;
+            }
+            if let Some(trim_start) = path.inner.trim_start.get() {
+                path.builder.stroke.trim_start = trim_start;
+                rebuild = true;
+            }
+            if let Some(trim_end) = path.inner.trim_end.get() {
+                path.builder.stroke.trim_end = trim_end;
+                rebuild = true;
+            }
+            if let Some(trim_offset) = path.inner.trim_offset.get() {
+                path.builder.stroke.trim_offset = trim_offset;
+                rebuild = true;
+            }
+            if let Some(blend) = path.inner.blend.get() {
+                path.builder.blend = blend;
+            }
+            if let Some(z_index) = path.inner.z_index.get() {
+                path.builder.z_index = z_index;
+            }
+            if let Some(color) = path.inner.shadow_color.get() {
+                path.builder.shadow.color = color;
+            }
+            if let Some(offset) = path.inner.shadow_offset.get() {
+                path.builder.shadow.offset = offset;
+            }
+            if let Some(blur) = path.inner.shadow_blur.get() {
+                path.builder.shadow.blur = blur;
+            }
+            if let Some(glow) = path.inner.glow.get() {
+                path.builder.shadow.glow = glow;
+            }
+
+            if path.inner.drop.get().is_some() {
+                return false;
+            }
+
+            if rebuild {
+                let (mesh, indices) = PathNodeManager::build_mesh(&path.builder);
+                path.va
+                    .array_buffer
+                    .set_data(&mesh, BufferUsage::DRAW_DYNAMIC);
+                path.va.set_indices(&indices, BufferUsage::DRAW_DYNAMIC);
+                path.count = indices.len() as i32;
+            }
+            true
+        });
+    }
+}
+
+impl PathNodeManager {
+    /// The vertices and the indices of the triangles
+    fn build_mesh(builder: &PathBuilder) -> (Vec<PathVertex>, Vec<u32>) {
+        let mut mesh = Vec::new();
+        let mut indices = Vec::new();
+
+        if builder.fill.is_visible() {
+            let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+            let options =
+                FillOptions::tolerance(TOLERANCE).with_fill_rule(builder.fill_rule.into());
+            if let Err(err) = FillTessellator::new().tessellate_path(
+                &builder.data.path,
+                &options,
+                &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| {
+                    vertex.position().to_array()
+                }),
+            ) {
+                eprintln!("Cannot tessellate fill: {err:?}");
+            }
+
+            mesh.extend(buffers.vertices.iter().map(|&position| PathVertex {
+                position,
+                color: Color::TRANSPARENT,
+                filled: 1.,
+            }));
+            indices.extend(buffers.indices);
+        }
+
+        if builder.stroke_color.a > 0. {
+            let mut triangles = Vec::new();
+            for polyline in builder.data.polylines() {
+                stroke::tessellate(
+                    &polyline.points,
+                    polyline.closed,
+                    &builder.stroke,
+                    &mut triangles,
+                );
+            }
+
+            // the stroke comes as a list of triangles
+            let color = builder.stroke_color;
+            let first = mesh.len() as u32;
+            indices.extend(first..first + triangles.len() as u32);
+            mesh.extend(triangles.into_iter().map(|position| PathVertex {
+                position,
+                color,
+                filled: 0.,
+            }));
+        }
+
+        (mesh, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(src: &str) -> Vec<Polyline> {
+        PathData::parse(src).unwrap().polylines()
+    }
+
+    #[test]
+    fn absolute_and_relative_lines() {
+        assert_eq!(
+            points("M 0 0 L 10 0 l 0 10 H 0 v -10 Z"),
+            vec![Polyline {
+                points: vec![[0., 0.], [10., 0.], [10., 10.], [0., 10.], [0., 0.]],
+                closed: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn implicit_line_to_after_move_to() {
+        assert_eq!(
+            points("M0,0 10,0 m 1 1 2 0"),
+            vec![
+                Polyline {
+                    points: vec![[0., 0.], [10., 0.]],
+                    closed: false,
+                },
+                Polyline {
+                    points: vec![[11., 1.], [13., 1.]],
+                    closed: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_without_separators() {
+        assert_eq!(
+            points("M-1.5e1.5L0-2"),
+            vec![Polyline {
+                points: vec![[-15., 0.5], [0., -2.]],
+                closed: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn arc_flags_without_separators() {
+        let polylines = points("M0 0A5 5 0 1110 0");
+        let end = *polylines[0].points.last().unwrap();
+        assert!((end[0] - 10.).abs() < 1e-3 && end[1].abs() < 1e-3);
+    }
+
+    #[test]
+    fn empty() {
+        assert!(PathData::parse("").unwrap().is_empty());
+        assert!(PathData::parse("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            PathData::parse("L 0 0").unwrap_err(),
+            PathError::MissingMoveTo
+        );
+        assert_eq!(
+            PathData::parse("M 0").unwrap_err(),
+            PathError::ExpectedNumber(3)
+        );
+        assert_eq!(
+            PathData::parse("M 0 0 A 1 1 0 2 0 1 1").unwrap_err(),
+            PathError::ExpectedFlag(14)
+        );
+        assert_eq!(
+            PathData::parse("M 0 0 Z 1").unwrap_err(),
+            PathError::UnexpectedChar(8, '1')
+        );
+        assert_eq!(
+            PathData::parse("M 0 0 X").unwrap_err(),
+            PathError::UnexpectedChar(6, 'X')
+        );
+    }
+}
@@ -1,29 +1,44 @@
 use crate::{
     color::Color,
+    fill::{Fill, FillRamp, FILL_GLSL},
     gcx::{
+        blend::BlendMode,
         buffer::{BufferType, BufferUsage},
         shader::Shader,
         vertex_array::{Field, Fields, VertexArray},
         PrimitiveType, GCX,
     },
-    node::{NodeBuilder, NodeManager},
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    node::{centered, Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
     scene::SceneTask,
+    shadow::{Glow, Shadow},
     signal::{create_signal, NSignal, RawSignal, Signal},
 };
 
 #[derive(Debug)]
 pub struct RectBuilder {
     pub(super) size: [f32; 2],
-    pub(super) color: Color,
+    pub(super) fill: Fill,
     pub(super) position: [f32; 2],
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) shadow: Shadow,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
 }
 
 impl RectBuilder {
-    pub fn new(size: [f32; 2], color: impl Into<Color>) -> Self {
+    pub fn new(size: [f32; 2], fill: impl Into<Fill>) -> Self {
         Self {
             size,
-            color: color.into(),
+            fill: fill.into(),
             position: [0.; 2],
+            blend: BlendMode::Normal,
+            z_index: 0,
+            shadow: Shadow::default(),
+            mask: None,
+            mask_source: None,
         }
     }
 
@@ -31,14 +46,63 @@ impl RectBuilder {
         self.position = position;
         self
     }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// A shadow behind the node, `offset` and `blur` are in pixels of the screen
+    pub fn with_shadow(mut self, color: impl Into<Color>, offset: [f32; 2], blur: f32) -> Self {
+        self.shadow.color = color.into();
+        self.shadow.offset = offset;
+        self.shadow.blur = blur;
+        self
+    }
+
+    /// A glow around the node, `radius` is in pixels of the screen
+    pub fn with_glow(mut self, color: impl Into<Color>, radius: f32) -> Self {
+        self.shadow.glow = Glow {
+            color: color.into(),
+            radius,
+        };
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
 }
 
 pub struct Rect<'a> {
     scene: &'a SceneTask,
+    parent: GroupId,
 
     pub position: Signal<'a, [f32; 2]>,
     pub size: Signal<'a, [f32; 2]>,
+    pub fill: Signal<'a, Fill>,
+    /// Replaces the fill with a solid one, starts as the color at the start of the fill
+    /// `fill` keeps the value that it had
     pub color: Signal<'a, Color>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+    pub shadow_color: Signal<'a, Color>,
+    pub shadow_offset: Signal<'a, [f32; 2]>,
+    pub shadow_blur: Signal<'a, f32>,
+    pub glow: Signal<'a, Glow>,
 
     drop: Signal<'a, ()>,
     dropped: bool,
@@ -52,6 +116,15 @@ impl<'a> Rect<'a> {
     }
 }
 
+impl<'a> Bounds for Rect<'a> {
+    fn bounds(&self) -> NodeBounds {
+        NodeBounds::Local(
+            self.parent,
+            centered(*self.position.value(), *self.size.value()),
+        )
+    }
+}
+
 impl<'a> Drop for Rect<'a> {
     fn drop(&mut self) {
         if self.dropped {
@@ -67,20 +140,35 @@ impl NodeBuilder for RectBuilder {
     type Node<'a> = Rect<'a>;
     type NodeManager = RectNodeManager;
 
-    fn create_node_ref<'a>(&self, raw: RawRect, scene: &'a SceneTask) -> Self::Node<'a> {
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawRect,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
         Rect {
             scene,
+            parent,
             dropped: false,
             position: Signal::new(raw.position, scene, self.position),
             size: Signal::new(raw.size, scene, self.size),
-            color: Signal::new(raw.color, scene, self.color),
+            fill: Signal::new(raw.fill, scene, self.fill.clone()),
+            color: Signal::new(raw.color, scene, self.fill.color_at(0.)),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            shadow_color: Signal::new(raw.shadow_color, scene, self.shadow.color),
+            shadow_offset: Signal::new(raw.shadow_offset, scene, self.shadow.offset),
+            shadow_blur: Signal::new(raw.shadow_blur, scene, self.shadow.blur),
+            glow: Signal::new(raw.glow, scene, self.shadow.glow),
             drop: Signal::new(raw.drop, scene, ()),
         }
     }
 }
 
 pub struct NRect {
-    va: VertexArray,
+    /// `None` until it is drawn with a gradient
+    ramp: Option<FillRamp>,
+    parent: GroupId,
     builder: RectBuilder,
     inner: NRectInner,
 }
@@ -89,35 +177,58 @@ pub struct NRectInner {
     drop: NSignal<()>,
     position: NSignal<[f32; 2]>,
     size: NSignal<[f32; 2]>,
+    fill: NSignal<Fill>,
     color: NSignal<Color>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+    shadow_color: NSignal<Color>,
+    shadow_offset: NSignal<[f32; 2]>,
+    shadow_blur: NSignal<f32>,
+    glow: NSignal<Glow>,
 }
 
 pub struct RawRect {
     drop: RawSignal<()>,
     position: RawSignal<[f32; 2]>,
     size: RawSignal<[f32; 2]>,
+    fill: RawSignal<Fill>,
     color: RawSignal<Color>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+    shadow_color: RawSignal<Color>,
+    shadow_offset: RawSignal<[f32; 2]>,
+    shadow_blur: RawSignal<f32>,
+    glow: RawSignal<Glow>,
 }
 
 #[derive(Default)]
 pub struct RectNodeManager {
     pub(super) rects: Vec<NRect>,
     pub(super) shader: Option<Shader>,
+    /// One `RectInstance` for every rect in the order of `rects`
+    instances: Option<VertexArray>,
+    /// What is in the instance buffer
+    data: Vec<RectInstance>,
+    /// The instances were compared and uploaded since the last update
+    synced: bool,
 
     pending: Option<NRectInner>,
 }
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct RectVertex {
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RectInstance {
     position: [f32; 2],
+    size: [f32; 2],
+    /// The color of a solid fill, transparent for gradients
     color: Color,
 }
 
-impl Fields for RectVertex {
+impl Fields for RectInstance {
     fn fields() -> Vec<Field> {
         vec![
             Field::new::<[f32; 2]>("position"),
+            Field::new::<[f32; 2]>("size"),
             Field::new::<Color>("color"),
         ]
     }
@@ -136,56 +247,138 @@ impl NodeManager for RectNodeManager {
 
                 precision highp float;
 
-                in vec2 pos;
-                in vec4 color;
+                layout(location = 0) in vec2 position;
+                layout(location = 1) in vec2 size;
+                layout(location = 2) in vec4 color;
 
-                out vec4 VertexColor;
+                uniform mat3 World;
+
+                out vec2 Local;
+                out vec4 Color;
 
                 void main(){
-                    gl_Position = vec4(pos, 0.0, 1.0);
-                    VertexColor = color;
+                    // a strip of four vertices without a buffer
+                    vec2 corner = vec2(gl_VertexID & 1, gl_VertexID >> 1) * 2.0 - 1.0;
+                    Local = corner * size;
+                    gl_Position = vec4((World * vec3(position + Local, 1.0)).xy, 0.0, 1.0);
+                    Color = vec4(color.rgb * color.a, color.a);
                 }
             "#,
             )
-            .fragment(
+            .fragment(format!(
                 r#"
                 #version 320 es
 
                 precision highp float;
 
-                in vec4 VertexColor;
+                {FILL_GLSL}
+
+                uniform float WorldOpacity;
+
+                in vec2 Local;
+                in vec4 Color;
                 out vec4 color;
-                void main(){
-                    color = VertexColor;
-                }
+                void main(){{
+                    // a solid fill comes with the instance
+                    color = (FillKind == 0 ? Color : fill(Local)) * WorldOpacity;
+                }}
                 "#,
-            )
+            ))
             .build(gcx)
             .unwrap();
 
+        let buffer = gcx.create_buffer::<RectInstance>(
+            BufferType::ArrayBuffer,
+            &[],
+            BufferUsage::DRAW_DYNAMIC,
+        );
+        self.instances = Some(
+            gcx.create_vertex_array::<RectInstance>(buffer)
+                .per_instance()
+                .build(gcx),
+        );
         self.shader.replace(shader);
     }
 
-    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder) {
-        let buffer = gcx.create_buffer(
-            BufferType::ArrayBuffer,
-            &Self::build_mesh(&builder),
-            BufferUsage::DRAW_STATIC,
-        );
-        let va = gcx.create_vertex_array::<RectVertex>(buffer).build(gcx);
+    fn init_node(&mut self, _gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
         self.rects.push(NRect {
-            va,
+            ramp: None,
+            parent,
             builder,
             inner: self.pending.take().unwrap(),
         });
+        self.synced = false;
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.rects
+            .iter()
+            .map(|rect| DrawItem {
+                z_index: rect.builder.z_index,
+                parent: rect.parent,
+                mask: rect.builder.mask,
+                mask_source: rect.builder.mask_source,
+                shadow: Some(rect.builder.shadow).filter(Shadow::is_visible),
+            })
+            .collect()
+    }
+
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.rects
+            .iter()
+            .map(|rect| centered(rect.builder.position, rect.builder.size))
+            .collect()
     }
 
-    fn render(&mut self, gcx: &GCX) {
-        let Some(shader) = &self.shader else { panic!() };
+    /// The solid rects next to each other with the same group and blend mode are drawn with one call
+    /// if they were spawned one after the other
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        if !self.synced {
+            self.sync_instances();
+        }
+        let (Some(shader), Some(instances)) = (&self.shader, &self.instances) else {
+            panic!()
+        };
+        for &i in items {
+            let rect = &mut self.rects[i];
+            if !matches!(rect.builder.fill, Fill::Solid(_)) && rect.ramp.is_none() {
+                rect.ramp = Some(FillRamp::new(gcx, &rect.builder.fill));
+            }
+        }
+        let rects = &self.rects;
+        let batchable = |a: usize, b: usize| {
+            let (a, b) = (&rects[a], &rects[b]);
+            a.parent == b.parent
+                && a.builder.blend == b.builder.blend
+                // the blend barrier is only between draws
+                && a.builder.blend != BlendMode::Overlay
+                && matches!(a.builder.fill, Fill::Solid(_))
+                && matches!(b.builder.fill, Fill::Solid(_))
+        };
+
         gcx.use_shader(shader, |gcx| {
-            for rect in self.rects.iter() {
-                gcx.use_vertex_array(&rect.va, |gcx| {
-                    gcx.draw_arrays(PrimitiveType::TrianglesFan, 0, 4);
+            // the instances of a call are next to each other in the buffer
+            for run in items.chunk_by(|&a, &b| b == a + 1 && batchable(a, b)) {
+                instances.set_first(run[0] as i32);
+
+                let rect = &rects[run[0]];
+                graph.world(rect.parent).set_uniforms(shader);
+                match &rect.ramp {
+                    Some(ramp) if !matches!(rect.builder.fill, Fill::Solid(_)) => {
+                        ramp.set_uniforms(shader, &rect.builder.fill, 0)
+                    }
+                    _ => {
+                        let _ = shader.set_uniform("FillKind", 0);
+                    }
+                }
+                let _blend = gcx.blend(rect.builder.blend);
+                gcx.use_vertex_array(instances, |gcx| {
+                    gcx.draw_arrays_instanced(
+                        PrimitiveType::TrianglesStrip,
+                        0,
+                        4,
+                        run.len() as i32,
+                    );
                 });
             }
         });
@@ -194,76 +387,130 @@ impl NodeManager for RectNodeManager {
     fn create_node(&mut self) -> RawRect {
         let (nposition, position) = create_signal();
         let (nsize, size) = create_signal();
+        let (nfill, fill) = create_signal();
         let (ncolor, color) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (nshadow_color, shadow_color) = create_signal();
+        let (nshadow_offset, shadow_offset) = create_signal();
+        let (nshadow_blur, shadow_blur) = create_signal();
+        let (nglow, glow) = create_signal();
         let (ndrop, drop) = create_signal();
 
         self.pending = Some(NRectInner {
             drop,
             position,
             size,
+            fill,
             color,
+            blend,
+            z_index,
+            shadow_color,
+            shadow_offset,
+            shadow_blur,
+            glow,
         });
 
         RawRect {
             drop: ndrop,
             position: nposition,
             size: nsize,
+            fill: nfill,
             color: ncolor,
+            blend: nblend,
+            z_index: nz_index,
+            shadow_color: nshadow_color,
+            shadow_offset: nshadow_offset,
+            shadow_blur: nshadow_blur,
+            glow: nglow,
         }
     }
 
     fn update(&mut self) {
         self.rects.retain_mut(|rect| {
-            let mut rebuild = false;
             if let Some(position) = rect.inner.position.get() {
                 rect.builder.position = position;
-                rebuild = true;
             }
             if let Some(size) = rect.inner.size.get() {
                 rect.builder.size = size;
-                rebuild = true;
             }
             if let Some(color) = rect.inner.color.get() {
-                rect.builder.color = color;
-                rebuild = true;
+                rect.builder.fill = Fill::Solid(color);
             }
-
-            if rect.inner.drop.get().is_some() {
-                return false;
+            if let Some(fill) = rect.inner.fill.get() {
+                if let Some(ramp) = &rect.ramp {
+                    ramp.update(&fill);
+                }
+                rect.builder.fill = fill;
             }
-
-            if rebuild {
-                rect.va
-                    .array_buffer
-                    .update(0, &RectNodeManager::build_mesh(&rect.builder));
+            if let Some(blend) = rect.inner.blend.get() {
+                rect.builder.blend = blend;
+            }
+            if let Some(z_index) = rect.inner.z_index.get() {
+                rect.builder.z_index = z_index;
+            }
+            if let Some(color) = rect.inner.shadow_color.get() {
+                rect.builder.shadow.color = color;
+            }
+            if let Some(offset) = rect.inner.shadow_offset.get() {
+                rect.builder.shadow.offset = offset;
+            }
+            if let Some(blur) = rect.inner.shadow_blur.get() {
+                rect.builder.shadow.blur = blur;
             }
-            true
+            if let Some(glow) = rect.inner.glow.get() {
+                rect.builder.shadow.glow = glow;
+            }
+
+            rect.inner.drop.get().is_none()
         });
+        self.synced = false;
     }
 }
 
 impl RectNodeManager {
-    fn build_mesh(builder: &RectBuilder) -> [RectVertex; 4] {
-        let color = builder.color;
-        let size = builder.size;
-        let position = builder.position;
-        [
-            RectVertex {
-                position: [-size[0] + position[0], -size[1] + position[1]],
-                color,
-            },
-            RectVertex {
-                position: [-size[0] + position[0], size[1] + position[1]],
-                color,
-            },
-            RectVertex {
-                position: [size[0] + position[0], size[1] + position[1]],
-                color,
-            },
-            RectVertex {
-                position: [size[0] + position[0], -size[1] + position[1]],
-                color,
+    /// Uploads the instances of the rects that changed, all of them if a rect was added or removed
+    fn sync_instances(&mut self) {
+        let Some(instances) = &mut self.instances else {
+            panic!()
+        };
+        self.synced = true;
+
+        if self.data.len() != self.rects.len() {
+            self.data = self.rects.iter().map(NRect::instance).collect();
+            instances
+                .array_buffer
+                .set_data(&self.data, BufferUsage::DRAW_DYNAMIC);
+            return;
+        }
+
+        // from the first changed rect to the one after the last
+        let mut changed: Option<[usize; 2]> = None;
+        for (i, rect) in self.rects.iter().enumerate() {
+            let instance = rect.instance();
+            if self.data[i] != instance {
+                self.data[i] = instance;
+                changed = Some(changed.map_or([i, i + 1], |[first, _]| [first, i + 1]));
+            }
+        }
+        if let Some([first, last]) = changed {
+            let size = std::mem::size_of::<RectInstance>();
+            instances
+                .array_buffer
+                .update((first * size) as i32, &self.data[first..last]);
+        }
+    }
+}
+
+impl NRect {
+    fn instance(&self) -> RectInstance {
+        RectInstance {
+            position: self.builder.position,
+            size: self.builder.size,
+            color: match self.builder.fill {
+                Fill::Solid(color) => color,
+                _ => Color::TRANSPARENT,
             },
-        ]
+        }
     }
 }
@@ -3,9 +3,13 @@ use std::sync::Arc;
 
 use tokio::sync::RwLock;
 
+use crate::camera::{Camera, NCamera};
+use crate::effect::{NPostProcess, PostProcess};
 use crate::engine_message::{EngineMessage, Ty};
-use crate::node::{NodeBuilder, NodeManager};
+use crate::group::{Group, GroupBuilder, GroupId, NGroup};
+use crate::node::{NodeBounds, NodeBuilder, NodeManager};
 use crate::ochannel;
+use crate::svg::{Svg, SvgBuilder};
 use crate::tween::{Tween, TweenBuilder};
 use crate::{engine_message::EngineSender, info::Info};
 
@@ -42,6 +46,15 @@ impl SceneTask {
 
     /// this will spawn a Node
     pub async fn spawn<T: NodeBuilder + 'static>(&self, builder: T) -> T::Node<'_> {
+        self.spawn_in(GroupId::ROOT, builder).await
+    }
+
+    /// this will spawn a Node that will move with the `parent` group
+    pub async fn spawn_in<T: NodeBuilder + 'static>(
+        &self,
+        parent: GroupId,
+        builder: T,
+    ) -> T::Node<'_> {
         let (send, recv) = ochannel();
         self.sender
             .send(EngineMessage::CreateRef(Ty::of::<T::NodeManager>(), send))
@@ -52,11 +65,12 @@ impl SceneTask {
             .downcast::<<T::NodeManager as NodeManager>::RawNode>()
             .unwrap();
 
-        let element_ref = builder.create_node_ref(raw_node, self);
+        let element_ref = builder.create_node_ref(raw_node, parent, self);
 
         self.sender
             .send(EngineMessage::CreateNode(
                 Ty::of::<T::NodeManager>(),
+                parent,
                 Box::new(builder),
             ))
             .await;
@@ -64,6 +78,64 @@ impl SceneTask {
         element_ref
     }
 
+    /// this will create a Group, the nodes spawned in it will use the transform and the opacity of it
+    pub async fn group(&self, builder: GroupBuilder) -> Group<'_> {
+        self.group_in(GroupId::ROOT, builder).await
+    }
+
+    /// this will give the Camera that moves everything that is drawn
+    /// only the last Camera that was taken can move the view
+    pub async fn camera(&self) -> Camera<'_> {
+        let (camera, raw) = NCamera::create();
+        let (send, recv) = ochannel();
+        self.sender
+            .send(EngineMessage::CreateCamera(camera, send))
+            .await;
+
+        Camera::new(recv.await.unwrap(), raw, self)
+    }
+
+    /// this will give the effects of the whole screen, they start as the ones of `Engine::with_effects`
+    /// only the last PostProcess that was taken can change them
+    pub async fn post_process(&self) -> PostProcess<'_> {
+        let (post_process, raw) = NPostProcess::create();
+        let (send, recv) = ochannel();
+        self.sender
+            .send(EngineMessage::CreatePostProcess(post_process, send))
+            .await;
+
+        PostProcess::new(recv.await.unwrap(), raw, self)
+    }
+
+    pub async fn group_in(&self, parent: GroupId, builder: GroupBuilder) -> Group<'_> {
+        let (group, raw) = NGroup::create(parent, builder.clone());
+        let (send, recv) = ochannel();
+        self.sender
+            .send(EngineMessage::CreateGroup(group, send))
+            .await;
+
+        Group::new(recv.await.unwrap(), &builder, raw, self)
+    }
+
+    /// this will spawn a group for the box of the document, a group for every `<g>` and a path for every shape
+    pub async fn svg(&self, builder: SvgBuilder) -> Svg<'_> {
+        self.svg_in(GroupId::ROOT, builder).await
+    }
+
+    pub async fn svg_in(&self, parent: GroupId, builder: SvgBuilder) -> Svg<'_> {
+        Svg::spawn(self, parent, builder).await
+    }
+
+    /// Where the node is in pixels of the root group, the camera is not applied
+    /// `None` for a group without nodes
+    pub async fn world_bounds(&self, bounds: NodeBounds) -> Option<[[f32; 2]; 2]> {
+        let (send, recv) = ochannel();
+        self.sender
+            .send(EngineMessage::WorldBounds(bounds, send))
+            .await;
+        recv.await.unwrap()
+    }
+
     pub async fn update(&self) {
         self.sender.send(EngineMessage::Update).await;
     }
@@ -0,0 +1,769 @@
+use crate::{
+    color::Color,
+    gcx::{
+        blend::BlendMode,
+        buffer::{BufferType, BufferUsage},
+        shader::{Shader, ShaderError},
+        texture::Texture,
+        vertex_array::{Field, Fields, VertexArray},
+        PrimitiveType, GCX,
+    },
+    group::{GroupId, SceneGraph},
+    image::ImageData,
+    mask::{Mask, MaskId},
+    node::{centered, Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    signal::{create_signal, NSignal, RawSignal, Signal},
+};
+
+/// The value of a uniform of a `ShaderQuad`
+#[derive(Debug, Clone)]
+pub enum UniformValue {
+    Float(f32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+    /// A `vec4` with premultiplied alpha
+    Color(Color),
+    /// A `sampler2D` with premultiplied alpha, `y` goes down from the top like `uv`
+    Texture(ImageData),
+}
+
+impl From<f32> for UniformValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<[f32; 2]> for UniformValue {
+    fn from(value: [f32; 2]) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<[f32; 3]> for UniformValue {
+    fn from(value: [f32; 3]) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<[f32; 4]> for UniformValue {
+    fn from(value: [f32; 4]) -> Self {
+        Self::Vec4(value)
+    }
+}
+
+impl From<Color> for UniformValue {
+    fn from(value: Color) -> Self {
+        Self::Color(value)
+    }
+}
+
+impl From<ImageData> for UniformValue {
+    fn from(value: ImageData) -> Self {
+        Self::Texture(value)
+    }
+}
+
+impl UniformValue {
+    /// The value of a uniform that is declared in GLSL as `ty`
+    fn zero(ty: &str) -> Option<Self> {
+        Some(match ty {
+            "float" => Self::Float(0.),
+            "vec2" => Self::Vec2([0.; 2]),
+            "vec3" => Self::Vec3([0.; 3]),
+            "vec4" => Self::Vec4([0.; 4]),
+            "sampler2D" => Self::Texture(ImageData::from_rgba(1, 1, vec![0; 4])),
+            _ => return None,
+        })
+    }
+}
+
+/// `source` without the `//` and `/* */` comments, a block comment becomes a space
+fn strip_comments(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    loop {
+        let start = [rest.find("//"), rest.find("/*")]
+            .into_iter()
+            .flatten()
+            .min();
+        match start {
+            Some(start) if rest[start..].starts_with("//") => {
+                out.push_str(&rest[..start]);
+                rest = rest[start..]
+                    .find('\n')
+                    .map_or("", |end| &rest[start + end..]);
+            }
+            Some(start) => {
+                out.push_str(&rest[..start]);
+                out.push(' ');
+                let body = &rest[start + 2..];
+                rest = body.find("*/").map_or("", |end| &body[end + 2..]);
+            }
+            None => {
+                out.push_str(rest);
+                return out;
+            }
+        }
+    }
+}
+
+/// The uniforms that `source` declares, `time` and `resolution` are given by the node
+/// arrays are skipped
+fn declared_uniforms(source: &str) -> Vec<(String, UniformValue)> {
+    let mut uniforms = Vec::new();
+    for statement in strip_comments(source).split([';', '{', '}']) {
+        let mut tokens = statement
+            .split_whitespace()
+            .skip_while(|token| *token != "uniform")
+            .skip(1)
+            .skip_while(|token| matches!(*token, "lowp" | "mediump" | "highp"));
+        let Some(value) = tokens.next().and_then(UniformValue::zero) else {
+            continue;
+        };
+        // like `uniform vec2 a, b`
+        let names = tokens.collect::<String>();
+        for name in names.split(',') {
+            if !name.is_empty() && !name.contains('[') {
+                uniforms.push((name.to_string(), value.clone()));
+            }
+        }
+    }
+    uniforms
+}
+
+/// A rect that is colored by a fragment shader
+///
+/// The source has to define `vec4 shade(vec2 uv)` that returns a premultiplied color,
+/// `uv` is from `0` to `1` from the top left corner.
+/// `uniform float time` has the seconds since the first frame and
+/// `uniform vec2 resolution` has the size of the quad in pixels, they are declared already.
+/// Every other uniform that is a `float`, a `vec2`, a `vec3`, a `vec4` or a `sampler2D`
+/// becomes a signal of `ShaderQuad::uniform`.
+/// The engine panics with the info log if the shader does not compile
+#[derive(Debug)]
+pub struct ShaderQuadBuilder {
+    pub(super) source: String,
+    pub(super) uniforms: Vec<(String, UniformValue)>,
+    pub(super) position: [f32; 2],
+    pub(super) size: [f32; 2],
+    pub(super) opacity: f32,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl ShaderQuadBuilder {
+    /// `size` is the half size like the one of a rect, `source` has no `#version`
+    pub fn new(source: impl Into<String>, size: [f32; 2]) -> Self {
+        let source = source.into();
+        Self {
+            uniforms: declared_uniforms(&source),
+            source,
+            position: [0.; 2],
+            size,
+            opacity: 1.,
+            blend: BlendMode::Normal,
+            z_index: 0,
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    /// The first value of a uniform, a `vec4` can be given as a `Color`
+    pub fn with_uniform(mut self, name: &str, value: impl Into<UniformValue>) -> Self {
+        let value = value.into();
+        match self.uniforms.iter_mut().find(|(n, _)| n == name) {
+            Some((_, old)) => *old = value,
+            None => self.uniforms.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+/// The signal of a uniform with the type of its value
+pub enum UniformSignal<'a> {
+    Float(Signal<'a, f32>),
+    Vec2(Signal<'a, [f32; 2]>),
+    Vec3(Signal<'a, [f32; 3]>),
+    Vec4(Signal<'a, [f32; 4]>),
+    Color(Signal<'a, Color>),
+    Texture(Signal<'a, ImageData>),
+}
+
+/// A type that a uniform of a `ShaderQuad` can have
+pub trait Uniform: Sized {
+    fn signal<'b, 'a>(signal: &'b mut UniformSignal<'a>) -> Option<&'b mut Signal<'a, Self>>;
+}
+
+impl Uniform for f32 {
+    fn signal<'b, 'a>(signal: &'b mut UniformSignal<'a>) -> Option<&'b mut Signal<'a, Self>> {
+        match signal {
+            UniformSignal::Float(signal) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+impl Uniform for [f32; 2] {
+    fn signal<'b, 'a>(signal: &'b mut UniformSignal<'a>) -> Option<&'b mut Signal<'a, Self>> {
+        match signal {
+            UniformSignal::Vec2(signal) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+impl Uniform for [f32; 3] {
+    fn signal<'b, 'a>(signal: &'b mut UniformSignal<'a>) -> Option<&'b mut Signal<'a, Self>> {
+        match signal {
+            UniformSignal::Vec3(signal) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+impl Uniform for [f32; 4] {
+    fn signal<'b, 'a>(signal: &'b mut UniformSignal<'a>) -> Option<&'b mut Signal<'a, Self>> {
+        match signal {
+            UniformSignal::Vec4(signal) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+impl Uniform for Color {
+    fn signal<'b, 'a>(signal: &'b mut UniformSignal<'a>) -> Option<&'b mut Signal<'a, Self>> {
+        match signal {
+            UniformSignal::Color(signal) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+impl Uniform for ImageData {
+    fn signal<'b, 'a>(signal: &'b mut UniformSignal<'a>) -> Option<&'b mut Signal<'a, Self>> {
+        match signal {
+            UniformSignal::Texture(signal) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+pub struct ShaderQuad<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+
+    pub position: Signal<'a, [f32; 2]>,
+    pub size: Signal<'a, [f32; 2]>,
+    pub opacity: Signal<'a, f32>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+    uniforms: Vec<(String, UniformSignal<'a>)>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> ShaderQuad<'a> {
+    /// The signal of the uniform with the `name`, `T` is the type of the value it was created with
+    pub fn uniform<T: Uniform>(&mut self, name: &str) -> &mut Signal<'a, T> {
+        self.uniforms
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, signal)| T::signal(signal))
+            .unwrap_or_else(|| {
+                panic!(
+                    "The ShaderQuad has no uniform `{name}` of type `{}`",
+                    std::any::type_name::<T>()
+                )
+            })
+    }
+
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+}
+
+impl<'a> Bounds for ShaderQuad<'a> {
+    fn bounds(&self) -> NodeBounds {
+        NodeBounds::Local(
+            self.parent,
+            centered(*self.position.value(), *self.size.value()),
+        )
+    }
+}
+
+impl<'a> Drop for ShaderQuad<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on ShaderQuad when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for ShaderQuadBuilder {
+    type Node<'a> = ShaderQuad<'a>;
+    type NodeManager = ShaderQuadNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawShaderQuad,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        let mut uniforms = Vec::new();
+        let mut nuniforms = Vec::new();
+        for (name, value) in &self.uniforms {
+            let (signal, nuniform) = match value.clone() {
+                UniformValue::Float(value) => {
+                    let (nsignal, signal) = create_signal();
+                    (
+                        UniformSignal::Float(Signal::new(nsignal, scene, value)),
+                        NUniform::Float(signal),
+                    )
+                }
+                UniformValue::Vec2(value) => {
+                    let (nsignal, signal) = create_signal();
+                    (
+                        UniformSignal::Vec2(Signal::new(nsignal, scene, value)),
+                        NUniform::Vec2(signal),
+                    )
+                }
+                UniformValue::Vec3(value) => {
+                    let (nsignal, signal) = create_signal();
+                    (
+                        UniformSignal::Vec3(Signal::new(nsignal, scene, value)),
+                        NUniform::Vec3(signal),
+                    )
+                }
+                UniformValue::Vec4(value) => {
+                    let (nsignal, signal) = create_signal();
+                    (
+                        UniformSignal::Vec4(Signal::new(nsignal, scene, value)),
+                        NUniform::Vec4(signal),
+                    )
+                }
+                UniformValue::Color(value) => {
+                    let (nsignal, signal) = create_signal();
+                    (
+                        UniformSignal::Color(Signal::new(nsignal, scene, value)),
+                        NUniform::Color(signal),
+                    )
+                }
+                UniformValue::Texture(value) => {
+                    let (nsignal, signal) = create_signal();
+                    (
+                        UniformSignal::Texture(Signal::new(nsignal, scene, value)),
+                        NUniform::Texture(signal),
+                    )
+                }
+            };
+            uniforms.push((name.clone(), signal));
+            nuniforms.push(nuniform);
+        }
+        // the manager gets them before the node is created
+        raw.uniforms.send_now(nuniforms);
+
+        ShaderQuad {
+            scene,
+            parent,
+            dropped: false,
+            position: Signal::new(raw.position, scene, self.position),
+            size: Signal::new(raw.size, scene, self.size),
+            opacity: Signal::new(raw.opacity, scene, self.opacity),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            uniforms,
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+/// The manager side of a `UniformSignal`
+pub enum NUniform {
+    Float(NSignal<f32>),
+    Vec2(NSignal<[f32; 2]>),
+    Vec3(NSignal<[f32; 3]>),
+    Vec4(NSignal<[f32; 4]>),
+    Color(NSignal<Color>),
+    Texture(NSignal<ImageData>),
+}
+
+impl NUniform {
+    fn get(&mut self) -> Option<UniformValue> {
+        match self {
+            NUniform::Float(signal) => signal.get().map(UniformValue::Float),
+            NUniform::Vec2(signal) => signal.get().map(UniformValue::Vec2),
+            NUniform::Vec3(signal) => signal.get().map(UniformValue::Vec3),
+            NUniform::Vec4(signal) => signal.get().map(UniformValue::Vec4),
+            NUniform::Color(signal) => signal.get().map(UniformValue::Color),
+            NUniform::Texture(signal) => signal.get().map(UniformValue::Texture),
+        }
+    }
+}
+
+struct QuadUniform {
+    name: String,
+    value: UniformValue,
+    /// The texture of a `UniformValue::Texture`, `None` until it is drawn after a change
+    texture: Option<Texture>,
+    signal: NUniform,
+}
+
+pub struct NShaderQuad {
+    shader: Shader,
+    uniforms: Vec<QuadUniform>,
+    parent: GroupId,
+    builder: ShaderQuadBuilder,
+    inner: NShaderQuadInner,
+}
+
+pub struct NShaderQuadInner {
+    drop: NSignal<()>,
+    position: NSignal<[f32; 2]>,
+    size: NSignal<[f32; 2]>,
+    opacity: NSignal<f32>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+    uniforms: NSignal<Vec<NUniform>>,
+}
+
+pub struct RawShaderQuad {
+    drop: RawSignal<()>,
+    position: RawSignal<[f32; 2]>,
+    size: RawSignal<[f32; 2]>,
+    opacity: RawSignal<f32>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+    uniforms: RawSignal<Vec<NUniform>>,
+}
+
+#[derive(Default)]
+pub struct ShaderQuadNodeManager {
+    pub(super) quads: Vec<NShaderQuad>,
+    /// A quad from `-1` to `1` shared by every node
+    quad: Option<VertexArray>,
+
+    pending: Option<NShaderQuadInner>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShaderQuadVertex {
+    corner: [f32; 2],
+}
+
+impl Fields for ShaderQuadVertex {
+    fn fields() -> Vec<Field> {
+        vec![Field::new::<[f32; 2]>("corner")]
+    }
+}
+
+const VERTEX: &str = r#"
+    #version 320 es
+
+    precision highp float;
+
+    layout(location = 0) in vec2 corner;
+
+    uniform vec2 Position;
+    // half size in pixels
+    uniform vec2 Half;
+    uniform mat3 World;
+    uniform float WorldUp;
+
+    out vec2 uv;
+
+    void main(){
+        vec2 pos = Position + corner * Half * vec2(1.0, WorldUp);
+        gl_Position = vec4((World * vec3(pos, 1.0)).xy, 0.0, 1.0);
+        uv = vec2(corner.x, -corner.y) * 0.5 + 0.5;
+    }
+"#;
+
+impl NodeManager for ShaderQuadNodeManager {
+    type NodeBuilder = ShaderQuadBuilder;
+    type RawNode = RawShaderQuad;
+
+    fn init(&mut self, gcx: &GCX) {
+        let buffer = gcx.create_buffer(
+            BufferType::ArrayBuffer,
+            &[[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(|corner| ShaderQuadVertex { corner }),
+            BufferUsage::DRAW_STATIC,
+        );
+        self.quad = Some(
+            gcx.create_vertex_array::<ShaderQuadVertex>(buffer)
+                .build(gcx),
+        );
+    }
+
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
+        let fragment = format!(
+            r#"
+            #version 320 es
+
+            precision highp float;
+
+            uniform float time;
+            uniform vec2 resolution;
+            uniform float Opacity;
+            uniform float WorldOpacity;
+
+            in vec2 uv;
+            out vec4 ShaderQuadColor;
+
+            {}
+
+            void main(){{
+                ShaderQuadColor = shade(uv) * Opacity * WorldOpacity;
+            }}
+            "#,
+            builder.source
+        );
+        let shader = match gcx
+            .create_shader()
+            .vertex(VERTEX)
+            .fragment(fragment)
+            .build(gcx)
+        {
+            Ok(shader) => shader,
+            Err(ShaderError::CompileError(_, log)) => {
+                panic!("The shader of the ShaderQuad does not compile:\n{log}")
+            }
+            Err(err) => panic!("Cannot build the shader of the ShaderQuad: {err:?}"),
+        };
+
+        let mut inner = self.pending.take().unwrap();
+        let signals = inner.uniforms.get().unwrap_or_default();
+        let uniforms = builder
+            .uniforms
+            .iter()
+            .zip(signals)
+            .map(|((name, value), signal)| QuadUniform {
+                name: name.clone(),
+                texture: None,
+                value: value.clone(),
+                signal,
+            })
+            .collect();
+
+        self.quads.push(NShaderQuad {
+            shader,
+            uniforms,
+            parent,
+            builder,
+            inner,
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.quads
+            .iter()
+            .map(|quad| DrawItem {
+                z_index: quad.builder.z_index,
+                parent: quad.parent,
+                mask: quad.builder.mask,
+                mask_source: quad.builder.mask_source,
+                shadow: None,
+            })
+            .collect()
+    }
+
+    fn bounds(&self, _graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.quads
+            .iter()
+            .map(|quad| centered(quad.builder.position, quad.builder.size))
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let Some(vertex_array) = &self.quad else {
+            panic!()
+        };
+        for &i in items {
+            let quad = &mut self.quads[i];
+            let shader = &quad.shader;
+            let builder = &quad.builder;
+            gcx.use_shader(shader, |gcx| {
+                let _ = shader.set_uniform("Position", (builder.position[0], builder.position[1]));
+                let _ = shader.set_uniform("Half", (builder.size[0], builder.size[1]));
+                let _ = shader.set_uniform("Opacity", builder.opacity.clamp(0., 1.));
+                let _ = shader.set_uniform("time", graph.time() as f32);
+                let _ =
+                    shader.set_uniform("resolution", (builder.size[0] * 2., builder.size[1] * 2.));
+                graph.world(quad.parent).set_uniforms(shader);
+
+                let mut unit = 0;
+                for uniform in quad.uniforms.iter_mut() {
+                    let name = uniform.name.as_str();
+                    match &uniform.value {
+                        UniformValue::Float(value) => {
+                            let _ = shader.set_uniform(name, *value);
+                        }
+                        &UniformValue::Vec2([x, y]) => {
+                            let _ = shader.set_uniform(name, (x, y));
+                        }
+                        &UniformValue::Vec3([x, y, z]) => {
+                            let _ = shader.set_uniform(name, (x, y, z));
+                        }
+                        &UniformValue::Vec4([x, y, z, w]) => {
+                            let _ = shader.set_uniform(name, (x, y, z, w));
+                        }
+                        UniformValue::Color(c) => {
+                            let _ =
+                                shader.set_uniform(name, (c.r * c.a, c.g * c.a, c.b * c.a, c.a));
+                        }
+                        UniformValue::Texture(image) => {
+                            // uploaded here because `update` has no context
+                            uniform
+                                .texture
+                                .get_or_insert_with(|| image.upload(&gcx))
+                                .activate(unit);
+                            let _ = shader.set_uniform(name, unit as i32);
+                            unit += 1;
+                        }
+                    }
+                }
+
+                let _blend = gcx.blend(builder.blend);
+                gcx.use_vertex_array(vertex_array, |gcx| {
+                    gcx.draw_arrays(PrimitiveType::TrianglesFan, 0, 4);
+                });
+            });
+        }
+    }
+
+    fn create_node(&mut self) -> RawShaderQuad {
+        let (nposition, position) = create_signal();
+        let (nsize, size) = create_signal();
+        let (nopacity, opacity) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (nuniforms, uniforms) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NShaderQuadInner {
+            drop,
+            position,
+            size,
+            opacity,
+            blend,
+            z_index,
+            uniforms,
+        });
+
+        RawShaderQuad {
+            drop: ndrop,
+            position: nposition,
+            size: nsize,
+            opacity: nopacity,
+            blend: nblend,
+            z_index: nz_index,
+            uniforms: nuniforms,
+        }
+    }
+
+    fn update(&mut self) {
+        self.quads.retain_mut(|quad| {
+            if let Some(position) = quad.inner.position.get() {
+                quad.builder.position = position;
+            }
+            if let Some(size) = quad.inner.size.get() {
+                quad.builder.size = size;
+            }
+            if let Some(opacity) = quad.inner.opacity.get() {
+                quad.builder.opacity = opacity;
+            }
+            if let Some(blend) = quad.inner.blend.get() {
+                quad.builder.blend = blend;
+            }
+            if let Some(z_index) = quad.inner.z_index.get() {
+                quad.builder.z_index = z_index;
+            }
+            for uniform in quad.uniforms.iter_mut() {
+                if let Some(value) = uniform.signal.get() {
+                    if matches!(value, UniformValue::Texture(_)) {
+                        uniform.texture = None;
+                    }
+                    uniform.value = value;
+                }
+            }
+
+            quad.inner.drop.get().is_none()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(source: &str) -> Vec<String> {
+        declared_uniforms(source)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    #[test]
+    fn uniforms() {
+        assert_eq!(
+            names("uniform float a;\nuniform highp vec2 b, c;\nuniform vec4 d,e;"),
+            ["a", "b", "c", "d", "e"]
+        );
+        // not a type of a signal and an array
+        assert_eq!(
+            names("uniform mat3 m;\nuniform float f[2];"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            names("// uniform float a;\nuniform /* float */ vec3 b; /* uniform vec2 c;\n */"),
+            ["b"]
+        );
+        assert_eq!(strip_comments("a/* b */c // d\ne"), "a c \ne");
+    }
+}
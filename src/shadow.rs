@@ -0,0 +1,62 @@
+use crate::{
+    color::Color,
+    signal::{lerp, Lerp},
+};
+
+/// A blurred copy of the silhouette of a node that is drawn around it with `BlendMode::Screen`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glow {
+    pub color: Color,
+    /// The blur in pixels of the screen
+    pub radius: f32,
+}
+
+impl Default for Glow {
+    /// Transparent, so there is no glow
+    fn default() -> Self {
+        Self {
+            color: Color::TRANSPARENT,
+            radius: 0.,
+        }
+    }
+}
+
+impl Lerp for Glow {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        Self {
+            color: self.color.lerp(&to.color, time),
+            radius: lerp(self.radius, to.radius, time),
+        }
+    }
+}
+
+/// The shadow and the glow of a node, they are drawn behind it
+/// by blurring the silhouette of the node in an offscreen pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shadow {
+    /// Transparent if there is no shadow
+    pub color: Color,
+    /// In pixels of the screen, `y` goes like the one of the `Origin`
+    pub offset: [f32; 2],
+    /// The blur in pixels of the screen
+    pub blur: f32,
+    pub glow: Glow,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self {
+            color: Color::TRANSPARENT,
+            offset: [0.; 2],
+            blur: 0.,
+            glow: Glow::default(),
+        }
+    }
+}
+
+impl Shadow {
+    /// If it needs to be drawn
+    pub fn is_visible(&self) -> bool {
+        self.color.a > 0. || self.glow.color.a > 0.
+    }
+}
@@ -10,6 +10,13 @@ pub struct RawSignal<T> {
     sender: Sender<T>,
 }
 
+impl<T> RawSignal<T> {
+    /// Sends without waiting for the scene, for values that are known when the node is created
+    pub(crate) fn send_now(&self, value: T) {
+        let _ = self.sender.try_send(value);
+    }
+}
+
 pub struct Signal<'a, T> {
     inner: RawSignal<T>,
     value: T,
@@ -32,6 +39,11 @@ impl<'a, T> Signal<'a, T> {
         self.value
     }
 
+    /// The last value that was set
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
     pub async fn set(&mut self, value: T)
     where
         T: Clone,
@@ -136,16 +148,42 @@ pub fn lerp(from: f32, to: f32, time: f64) -> f32 {
     (from as f64 * (1. - time) + to as f64 * time) as f32
 }
 
-impl<'a> Signal<'a, [f32; 2]> {
-    pub fn tween(&mut self, from: [f32; 2], to: [f32; 2], time: f64) -> Executor {
+/// A value that can be interpolated by `Signal::tween`
+pub trait Lerp {
+    fn lerp(&self, to: &Self, time: f64) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        lerp(*self, *to, time)
+    }
+}
+
+impl<const SIZE: usize> Lerp for [f32; SIZE] {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        std::array::from_fn(|i| lerp(self[i], to[i], time))
+    }
+}
+
+impl<'a, T: Lerp + Clone + Send + Sync> Signal<'a, T> {
+    pub fn tween(&mut self, from: T, to: T, time: f64) -> Executor<'_> {
+        self.tween_with(move |t| from.lerp(&to, t), time)
+    }
+}
+
+impl<'a, T: Clone + Send + Sync> Signal<'a, T> {
+    /// Sets the value of `at` from `0` to `1` over `time` seconds
+    pub fn tween_with<'b>(
+        &'b mut self,
+        at: impl Fn(f64) -> T + Send + Sync + 'b,
+        time: f64,
+    ) -> Executor<'b> {
         let mut sum = 0.;
         Executor::new(|| Box::pin(self.scene.present(1))).add(move |send| {
             Box::pin(async move {
                 while sum < 1. {
                     sum += self.scene.delta() / time;
-                    let x = lerp(from[0], to[0], sum);
-                    let y = lerp(from[1], to[1], sum);
-                    self.set([x, y]).await;
+                    self.set(at(sum.min(1.))).await;
                     send.send(()).await.unwrap();
                 }
             })
@@ -153,6 +191,7 @@ impl<'a> Signal<'a, [f32; 2]> {
     }
 }
 
+#[derive(Debug)]
 pub struct NSignal<T> {
     receiver: Receiver<T>,
 }
@@ -0,0 +1,425 @@
+use lyon::{
+    math::point,
+    path::Path,
+    tessellation::{
+        self, BuffersBuilder, StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers,
+    },
+};
+
+/// The max distance in pixels between the tessellated round caps/joins and the real curve
+pub const TOLERANCE: f32 = 0.25;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+impl From<LineCap> for tessellation::LineCap {
+    fn from(value: LineCap) -> Self {
+        match value {
+            LineCap::Butt => Self::Butt,
+            LineCap::Round => Self::Round,
+            LineCap::Square => Self::Square,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl From<LineJoin> for tessellation::LineJoin {
+    fn from(value: LineJoin) -> Self {
+        match value {
+            LineJoin::Miter => Self::Miter,
+            LineJoin::Round => Self::Round,
+            LineJoin::Bevel => Self::Bevel,
+        }
+    }
+}
+
+/// A triangle that is placed at the end of a line, the line is shortened by `length` so it will not go over the tip
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArrowHead {
+    pub length: f32,
+    pub width: f32,
+}
+
+impl ArrowHead {
+    pub fn new(length: f32, width: f32) -> Self {
+        Self { length, width }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub miter_limit: f32,
+    /// Alternating dash and gap lengths, if is empty the line is solid
+    /// if has an odd count of elements will be repeated to be even like in SVG
+    pub dash: Vec<f32>,
+    /// How far in to the dash pattern the line starts
+    pub dash_offset: f32,
+    /// Is ignored on closed lines
+    pub start_arrow: Option<ArrowHead>,
+    /// Is ignored on closed lines
+    pub end_arrow: Option<ArrowHead>,
+    /// From `0` to `1` along the arc length, where the visible part of the line starts
+    pub trim_start: f32,
+    /// From `0` to `1` along the arc length, where the visible part of the line ends
+    pub trim_end: f32,
+    /// Moves the visible part along the line, will wrap around
+    pub trim_offset: f32,
+}
+
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: StrokeOptions::DEFAULT_MITER_LIMIT,
+            dash: Vec::new(),
+            dash_offset: 0.,
+            start_arrow: None,
+            end_arrow: None,
+            trim_start: 0.,
+            trim_end: 1.,
+            trim_offset: 0.,
+        }
+    }
+
+    /// The visible arc length ranges in the order of the line
+    /// on closed lines a range can end after `length`, then it continues from the start of the line
+    fn trim_ranges(&self, length: f32, closed: bool) -> Vec<(f32, f32)> {
+        let from = self.trim_start.min(self.trim_end).clamp(0., 1.);
+        let to = self.trim_start.max(self.trim_end).clamp(0., 1.);
+        if to <= from || length <= 0. {
+            return Vec::new();
+        }
+        if to - from >= 1. {
+            return vec![(0., length)];
+        }
+
+        let start = (from + self.trim_offset).rem_euclid(1.);
+        let end = start + (to - from);
+        if closed || end <= 1. {
+            vec![(start * length, end * length)]
+        } else {
+            vec![(0., (end - 1.) * length), (start * length, length)]
+        }
+    }
+
+    fn options(&self) -> StrokeOptions {
+        StrokeOptions::default()
+            .with_tolerance(TOLERANCE)
+            .with_line_width(self.width)
+            .with_line_cap(self.cap.into())
+            .with_line_join(self.join.into())
+            .with_miter_limit(self.miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT))
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self::new(4.)
+    }
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((b[0] - a[0]).powi(2) + (b[1] - a[1]).powi(2)).sqrt()
+}
+
+fn mix(a: [f32; 2], b: [f32; 2], t: f32) -> [f32; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
+
+/// The arc length of the polyline
+pub fn length(points: &[[f32; 2]]) -> f32 {
+    points.windows(2).map(|w| distance(w[0], w[1])).sum()
+}
+
+/// The part of the polyline between the arc lengths `from` and `to`
+pub fn trim(points: &[[f32; 2]], from: f32, to: f32) -> Vec<[f32; 2]> {
+    let mut out = Vec::new();
+    if to < from {
+        return out;
+    }
+
+    let mut walked = 0.;
+    for w in points.windows(2) {
+        let len = distance(w[0], w[1]);
+        let next = walked + len;
+
+        if next >= from && walked <= to {
+            if out.is_empty() {
+                let t = if len > 0. { (from - walked) / len } else { 0. };
+                out.push(mix(w[0], w[1], t.max(0.)));
+            }
+            if next <= to {
+                out.push(w[1]);
+            } else {
+                let t = if len > 0. { (to - walked) / len } else { 1. };
+                out.push(mix(w[0], w[1], t));
+                break;
+            }
+        }
+
+        walked = next;
+    }
+    out
+}
+
+/// The pattern with only positive lengths and an even count, is `None` if the line is solid
+fn dash_pattern(pattern: &[f32]) -> Option<(Vec<f32>, f32)> {
+    let mut pattern = pattern.iter().map(|l| l.max(0.)).collect::<Vec<f32>>();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
+    }
+
+    let total = pattern.iter().sum::<f32>();
+    (!pattern.is_empty() && total > 0.).then_some((pattern, total))
+}
+
+/// The arc length ranges of the dashes that are between `from` and `to`
+fn dash_ranges(from: f32, to: f32, pattern: &[f32], offset: f32) -> Vec<(f32, f32)> {
+    let Some((pattern, total)) = dash_pattern(pattern) else {
+        return vec![(from, to)];
+    };
+
+    let mut ranges = Vec::new();
+    let mut start = -offset.rem_euclid(total);
+    start += ((from - start) / total).floor().max(0.) * total;
+    for (i, len) in pattern.iter().cycle().enumerate() {
+        if start >= to {
+            break;
+        }
+
+        let end = start + len;
+        if i % 2 == 0 && end > from {
+            ranges.push((start.max(from), end.min(to)));
+        }
+        start = end;
+    }
+    ranges
+}
+
+/// Splits the polyline in to the dashes of `pattern`
+pub fn dash(points: &[[f32; 2]], pattern: &[f32], offset: f32) -> Vec<Vec<[f32; 2]>> {
+    dash_ranges(0., length(points), pattern, offset)
+        .into_iter()
+        .map(|(from, to)| trim(points, from, to))
+        .collect()
+}
+
+/// The point at the arc length `at` and the direction of the segment
+/// `forward` selects the segment that starts at `at` when `at` is on a vertex
+fn sample(points: &[[f32; 2]], at: f32, forward: bool) -> Option<([f32; 2], [f32; 2])> {
+    let mut walked = 0.;
+    let mut last = None;
+    for w in points.windows(2) {
+        let len = distance(w[0], w[1]);
+        if len <= 0. {
+            continue;
+        }
+
+        let dir = [(w[1][0] - w[0][0]) / len, (w[1][1] - w[0][1]) / len];
+        let t = ((at - walked) / len).clamp(0., 1.);
+        last = Some((mix(w[0], w[1], t), dir));
+
+        walked += len;
+        if walked > at || (!forward && walked >= at) {
+            break;
+        }
+    }
+    last
+}
+
+fn arrow(tip: [f32; 2], dir: [f32; 2], arrow: &ArrowHead, out: &mut Vec<[f32; 2]>) {
+    let base = [
+        tip[0] - dir[0] * arrow.length,
+        tip[1] - dir[1] * arrow.length,
+    ];
+    let side = [-dir[1] * arrow.width * 0.5, dir[0] * arrow.width * 0.5];
+    out.extend([
+        tip,
+        [base[0] + side[0], base[1] + side[1]],
+        [base[0] - side[0], base[1] - side[1]],
+    ]);
+}
+
+fn tessellate_polyline(
+    points: &[[f32; 2]],
+    closed: bool,
+    options: &StrokeOptions,
+    out: &mut Vec<[f32; 2]>,
+) {
+    let mut points = points.iter();
+    let Some(first) = points.next() else { return };
+
+    let mut builder = Path::builder();
+    builder.begin(point(first[0], first[1]));
+    for p in points {
+        builder.line_to(point(p[0], p[1]));
+    }
+    builder.end(closed);
+    let path = builder.build();
+
+    let mut buffers: VertexBuffers<[f32; 2], u32> = VertexBuffers::new();
+    if let Err(err) = StrokeTessellator::new().tessellate_path(
+        &path,
+        options,
+        &mut BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| {
+            vertex.position().to_array()
+        }),
+    ) {
+        eprintln!("Cannot tessellate stroke: {err:?}");
+        return;
+    }
+
+    out.extend(
+        buffers
+            .indices
+            .iter()
+            .map(|index| buffers.vertices[*index as usize]),
+    );
+}
+
+/// Appends the triangles of the stroked polyline to `out`
+pub fn tessellate(points: &[[f32; 2]], closed: bool, stroke: &Stroke, out: &mut Vec<[f32; 2]>) {
+    if points.len() < 2 || stroke.width <= 0. {
+        return;
+    }
+
+    let options = stroke.options();
+    let mut line = points.to_vec();
+    if closed {
+        line.push(points[0]);
+    }
+    let length = length(&line);
+
+    let mut ranges = stroke.trim_ranges(length, closed);
+    if closed && ranges == [(0., length)] && dash_pattern(&stroke.dash).is_none() {
+        tessellate_polyline(points, true, &options, out);
+        return;
+    }
+
+    if !closed {
+        if let (Some(head), Some(first)) = (&stroke.start_arrow, ranges.first_mut()) {
+            if let Some((tip, dir)) = sample(&line, first.0, true) {
+                arrow(tip, [-dir[0], -dir[1]], head, out);
+                first.0 += head.length;
+            }
+        }
+        if let (Some(head), Some(last)) = (&stroke.end_arrow, ranges.last_mut()) {
+            if let Some((tip, dir)) = sample(&line, last.1, false) {
+                arrow(tip, dir, head, out);
+                last.1 -= head.length;
+            }
+        }
+    }
+
+    for (from, to) in ranges {
+        for (from, to) in dash_ranges(from, to, &stroke.dash, stroke.dash_offset) {
+            let piece = if to > length {
+                // is a closed line and the range goes over the start point
+                let mut piece = trim(&line, from, length);
+                piece.extend(trim(&line, 0., to - length).into_iter().skip(1));
+                piece
+            } else {
+                trim(&line, from, to)
+            };
+            tessellate_polyline(&piece, false, &options, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the trimmed line has no NaN from a zero length segment
+    fn check(points: &[[f32; 2]], from: f32, to: f32) -> Vec<[f32; 2]> {
+        let out = trim(points, from, to);
+        assert!(out.iter().flatten().all(|v| v.is_finite()));
+        out
+    }
+
+    #[test]
+    fn trim_across_segments() {
+        let points = [[0., 0.], [10., 0.], [10., 10.]];
+        assert_eq!(
+            check(&points, 2., 15.),
+            vec![[2., 0.], [10., 0.], [10., 5.]]
+        );
+        assert_eq!(
+            check(&points, 5., 100.),
+            vec![[5., 0.], [10., 0.], [10., 10.]]
+        );
+        assert_eq!(check(&points, 15., 2.), Vec::<[f32; 2]>::new());
+    }
+
+    #[test]
+    fn trim_zero_length_segments() {
+        let out = check(&[[0., 0.], [0., 0.], [10., 0.]], 0., 5.);
+        assert_eq!((out[0], out[out.len() - 1]), ([0., 0.], [5., 0.]));
+
+        let out = check(&[[0., 0.], [5., 0.], [5., 0.], [10., 0.]], 5., 8.);
+        assert_eq!((out[0], out[out.len() - 1]), ([5., 0.], [8., 0.]));
+    }
+
+    #[test]
+    fn dash_patterns() {
+        assert_eq!(dash_pattern(&[]), None);
+        assert_eq!(dash_pattern(&[0., 0.]), None);
+        // negative lengths are empty
+        assert_eq!(dash_pattern(&[-1., 2.]), Some((vec![0., 2.], 2.)));
+        // an odd pattern is repeated
+        assert_eq!(
+            dash_pattern(&[1., 2., 3.]),
+            Some((vec![1., 2., 3., 1., 2., 3.], 12.))
+        );
+    }
+
+    #[test]
+    fn dashes() {
+        assert_eq!(dash_ranges(0., 10., &[], 0.), vec![(0., 10.)]);
+        assert_eq!(
+            dash_ranges(0., 10., &[2., 3.], 0.),
+            vec![(0., 2.), (5., 7.)]
+        );
+        assert_eq!(
+            dash_ranges(0., 10., &[2., 3.], 1.),
+            vec![(0., 1.), (4., 6.), (9., 10.)]
+        );
+        assert_eq!(
+            dash_ranges(0., 10., &[1.], 0.),
+            vec![(0., 1.), (2., 3.), (4., 5.), (6., 7.), (8., 9.)]
+        );
+    }
+
+    #[test]
+    fn dash_offsets() {
+        // a negative offset is the same as the one a pattern length later
+        assert_eq!(
+            dash_ranges(0., 10., &[2., 3.], -1.),
+            dash_ranges(0., 10., &[2., 3.], 4.)
+        );
+        assert_eq!(
+            dash_ranges(0., 10., &[2., 3.], -1.),
+            vec![(1., 3.), (6., 8.)]
+        );
+        // the ranges start at `from`
+        assert_eq!(dash_ranges(6., 10., &[2., 3.], 0.), vec![(6., 7.)]);
+        assert_eq!(dash_ranges(5., 2., &[2., 3.], 0.), vec![]);
+    }
+}
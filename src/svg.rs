@@ -0,0 +1,552 @@
+use std::{collections::HashMap, path::Path as FilePath, sync::Arc};
+
+use lyon::math::{point, Transform};
+use usvg::tiny_skia_path::PathSegment;
+
+use crate::{
+    color::Color,
+    fill::{Fill, GradientStop},
+    gcx::blend::BlendMode,
+    group::{Group, GroupBuilder, GroupId},
+    info::Origin,
+    mask::{Mask, MaskId},
+    node::{Bounds, NodeBounds},
+    path::{FillRule, Path, PathBuilder, PathData},
+    scene::SceneTask,
+    stroke::{LineCap, LineJoin, Stroke},
+};
+
+#[derive(Debug)]
+pub enum SvgError {
+    Io(std::io::Error),
+    Parse(usvg::Error),
+}
+
+impl std::fmt::Display for SvgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgError::Io(err) => write!(f, "Cannot read svg: {err}"),
+            SvgError::Parse(err) => write!(f, "Cannot parse svg: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+impl From<std::io::Error> for SvgError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<usvg::Error> for SvgError {
+    fn from(value: usvg::Error) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// A `<path>` or a basic shape, everything is relative to the center of the parent group
+#[derive(Debug)]
+struct Shape {
+    data: PathData,
+    fill: Fill,
+    fill_rule: FillRule,
+    stroke: Stroke,
+    stroke_color: Color,
+}
+
+#[derive(Debug)]
+enum ElementKind {
+    /// `position` is the center of the bounds relative to the center of the parent group
+    Group {
+        position: [f32; 2],
+        opacity: f32,
+    },
+    Shape(Box<Shape>),
+}
+
+#[derive(Debug)]
+struct Element {
+    id: String,
+    /// The index of the parent group, the root group is the first element and has no parent
+    parent: usize,
+    kind: ElementKind,
+}
+
+#[derive(Debug)]
+struct SvgInner {
+    size: [f32; 2],
+    /// Document units to the units of the elements
+    unit: f32,
+    /// In document order, every group is before its children
+    elements: Vec<Element>,
+}
+
+/// A parsed SVG document, is cheap to clone
+///
+/// paths, groups, transforms, linear and radial gradients are supported
+/// text, images, patterns, masks, clip paths and filters are ignored, the focal point and the spread of gradients too
+#[derive(Debug, Clone)]
+pub struct SvgDocument {
+    inner: Arc<SvgInner>,
+}
+
+impl SvgDocument {
+    pub fn parse(src: &str) -> Result<Self, SvgError> {
+        let tree = usvg::Tree::from_str(src, &usvg::Options::default())?;
+        Ok(Self::convert(&tree))
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SvgError> {
+        let tree = usvg::Tree::from_data(data, &usvg::Options::default())?;
+        Ok(Self::convert(&tree))
+    }
+
+    pub fn from_file(path: impl AsRef<FilePath>) -> Result<Self, SvgError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// The width and height in SVG units
+    pub fn size(&self) -> [f32; 2] {
+        self.inner.size
+    }
+
+    /// The ids of the elements that can be animated, in document order
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.inner
+            .elements
+            .iter()
+            .map(|element| element.id.as_str())
+            .filter(|id| !id.is_empty())
+    }
+
+    fn convert(tree: &usvg::Tree) -> Self {
+        let size = [tree.size().width(), tree.size().height()];
+        // the tessellation tolerance is in pixels, so the document is made about `1000` wide
+        let unit = 1000. / size[0].max(size[1]);
+        let mut converter = Converter {
+            // the center of the document is at `0` and y goes up
+            document: Transform::translation(-size[0] * 0.5, -size[1] * 0.5)
+                .then_scale(unit, -unit),
+            elements: Vec::new(),
+        };
+        converter.group(tree.root(), 0, [0.; 2]);
+
+        Self {
+            inner: Arc::new(SvgInner {
+                size,
+                unit,
+                elements: converter.elements,
+            }),
+        }
+    }
+}
+
+impl std::str::FromStr for SvgDocument {
+    type Err = SvgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+struct Converter {
+    /// From the document to the units of the elements
+    document: Transform,
+    elements: Vec<Element>,
+}
+
+impl Converter {
+    /// `center` is the one of the parent group, the root group has its center at `0`
+    fn group(&mut self, group: &usvg::Group, parent: usize, parent_center: [f32; 2]) {
+        let center = if self.elements.is_empty() {
+            [0.; 2]
+        } else {
+            let bounds = group.abs_bounding_box();
+            let center = point(
+                (bounds.left() + bounds.right()) * 0.5,
+                (bounds.top() + bounds.bottom()) * 0.5,
+            );
+            self.document.transform_point(center).to_array()
+        };
+
+        let index = self.elements.len();
+        self.elements.push(Element {
+            id: group.id().to_string(),
+            parent,
+            kind: ElementKind::Group {
+                position: [center[0] - parent_center[0], center[1] - parent_center[1]],
+                opacity: group.opacity().get(),
+            },
+        });
+
+        for node in group.children() {
+            match node {
+                usvg::Node::Group(child) => self.group(child, index, center),
+                usvg::Node::Path(path) => self.path(path, index, center),
+                usvg::Node::Image(_) | usvg::Node::Text(_) => {}
+            }
+        }
+    }
+
+    fn path(&mut self, path: &usvg::Path, parent: usize, center: [f32; 2]) {
+        if !path.is_visible() {
+            return;
+        }
+
+        let local = to_transform(path.abs_transform())
+            .then(&self.document)
+            .then_translate([-center[0], -center[1]].into());
+        let scale = local.determinant().abs().sqrt();
+        if scale <= 0. || !scale.is_finite() {
+            return;
+        }
+
+        let at = |p: usvg::tiny_skia_path::Point| local.transform_point(point(p.x, p.y)).to_array();
+        let mut builder = PathData::builder();
+        for segment in path.data().segments() {
+            builder = match segment {
+                PathSegment::MoveTo(to) => builder.move_to(at(to)),
+                PathSegment::LineTo(to) => builder.line_to(at(to)),
+                PathSegment::QuadTo(ctrl, to) => builder.quadratic_to(at(ctrl), at(to)),
+                PathSegment::CubicTo(ctrl1, ctrl2, to) => {
+                    builder.cubic_to(at(ctrl1), at(ctrl2), at(to))
+                }
+                PathSegment::Close => builder.close(),
+            };
+        }
+
+        let mut shape = Shape {
+            data: builder.build(),
+            fill: Fill::default(),
+            fill_rule: FillRule::NonZero,
+            stroke: Stroke::new(0.),
+            stroke_color: Color::TRANSPARENT,
+        };
+
+        if let Some(paint) = path.fill() {
+            if let Some(fill) = to_fill(paint.paint(), paint.opacity().get(), local) {
+                shape.fill = fill;
+            }
+            shape.fill_rule = match paint.rule() {
+                usvg::FillRule::NonZero => FillRule::NonZero,
+                usvg::FillRule::EvenOdd => FillRule::EvenOdd,
+            };
+        }
+
+        if let Some(paint) = path.stroke() {
+            let stroke = &mut shape.stroke;
+            stroke.width = paint.width().get() * scale;
+            stroke.cap = match paint.linecap() {
+                usvg::LineCap::Butt => LineCap::Butt,
+                usvg::LineCap::Round => LineCap::Round,
+                usvg::LineCap::Square => LineCap::Square,
+            };
+            stroke.join = match paint.linejoin() {
+                usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => LineJoin::Miter,
+                usvg::LineJoin::Round => LineJoin::Round,
+                usvg::LineJoin::Bevel => LineJoin::Bevel,
+            };
+            stroke.miter_limit = paint.miterlimit().get();
+            if let Some(dash) = paint.dasharray() {
+                stroke.dash = dash.iter().map(|length| length * scale).collect();
+                stroke.dash_offset = paint.dashoffset() * scale;
+            }
+            // the stroke of a path has a single color
+            if let Some(fill) = to_fill(paint.paint(), paint.opacity().get(), local) {
+                shape.stroke_color = fill.color_at(0.);
+            }
+        }
+
+        if !shape.fill.is_visible() && shape.stroke_color.a <= 0. {
+            return;
+        }
+
+        self.elements.push(Element {
+            id: path.id().to_string(),
+            parent,
+            kind: ElementKind::Shape(Box::new(shape)),
+        });
+    }
+}
+
+/// `local` maps the coordinates of the path to the ones of the node
+fn to_fill(paint: &usvg::Paint, opacity: f32, local: Transform) -> Option<Fill> {
+    let color = |color: usvg::Color, alpha: f32| {
+        Color::new(
+            color.red as f32 / 255.,
+            color.green as f32 / 255.,
+            color.blue as f32 / 255.,
+            alpha * opacity,
+        )
+    };
+    let stops = |stops: &[usvg::Stop]| {
+        stops
+            .iter()
+            .map(|stop| {
+                GradientStop::new(
+                    stop.offset().get(),
+                    color(stop.color(), stop.opacity().get()),
+                )
+            })
+            .collect()
+    };
+
+    match paint {
+        usvg::Paint::Color(solid) => Some(Fill::Solid(color(*solid, 1.))),
+        usvg::Paint::LinearGradient(linear) => {
+            let gradient = to_transform(linear.transform()).then(&local);
+            let at = |x, y| gradient.transform_point(point(x, y)).to_array();
+            Some(Fill::linear(
+                at(linear.x1(), linear.y1()),
+                at(linear.x2(), linear.y2()),
+                stops(linear.stops()),
+            ))
+        }
+        usvg::Paint::RadialGradient(radial) => {
+            let gradient = to_transform(radial.transform()).then(&local);
+            let center = gradient.transform_point(point(radial.cx(), radial.cy()));
+            Some(Fill::radial(
+                center.to_array(),
+                radial.r().get() * gradient.determinant().abs().sqrt(),
+                stops(radial.stops()),
+            ))
+        }
+        usvg::Paint::Pattern(_) => None,
+    }
+}
+
+fn to_transform(transform: usvg::Transform) -> Transform {
+    Transform::new(
+        transform.sx,
+        transform.ky,
+        transform.kx,
+        transform.sy,
+        transform.tx,
+        transform.ty,
+    )
+}
+
+#[derive(Debug)]
+pub struct SvgBuilder {
+    pub(super) document: SvgDocument,
+    pub(super) position: [f32; 2],
+    pub(super) size: [f32; 2],
+    pub(super) opacity: f32,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl SvgBuilder {
+    /// `size` is the half size like the one of a rect, the document is stretched to it
+    pub fn new(document: SvgDocument, size: [f32; 2]) -> Self {
+        Self {
+            document,
+            position: [0.; 2],
+            size,
+            opacity: 1.,
+            blend: BlendMode::Normal,
+            z_index: 0,
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    /// The center of the box
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// The blend mode of every path
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// The z index of every path, they are drawn in document order
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// Every path is clipped by the mask, it is the one of the root group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The paths are not drawn, together they are the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+/// The node of an element
+///
+/// the position of a group is the center of its bounds relative to the center of the parent group,
+/// it rotates and scales around it
+pub enum SvgPart<'a> {
+    Group(Group<'a>),
+    Path(Box<Path<'a>>),
+}
+
+impl<'a> SvgPart<'a> {
+    pub async fn drop(self) {
+        match self {
+            SvgPart::Group(group) => group.drop().await,
+            SvgPart::Path(path) => (*path).drop().await,
+        }
+    }
+}
+
+/// The groups and paths of a document, spawned with `SceneTask::svg`
+pub struct Svg<'a> {
+    /// The root group is the first one, the others are in document order
+    nodes: Vec<SvgPart<'a>>,
+    /// The index in `nodes` of every id, the first element with an id has it
+    ids: HashMap<String, usize>,
+    dropped: bool,
+}
+
+impl<'a> Svg<'a> {
+    /// Spawns a group for the box, a group for every `<g>` and a path for every shape
+    pub(crate) async fn spawn(scene: &'a SceneTask, parent: GroupId, builder: SvgBuilder) -> Self {
+        let document = &builder.document.inner;
+        let [width, height] = document.size;
+        // the document is made with y going up
+        let up = match scene.info(|info| info.origin).await {
+            Origin::Center => 1.,
+            Origin::TopLeft => -1.,
+        };
+        let mut nodes = Vec::with_capacity(document.elements.len());
+        let mut ids = HashMap::new();
+        // the group of every element, for a shape the one of its parent
+        let mut group_ids: Vec<GroupId> = Vec::with_capacity(document.elements.len());
+
+        for (i, element) in document.elements.iter().enumerate() {
+            let parent = if i == 0 {
+                parent
+            } else {
+                group_ids[element.parent]
+            };
+            let node = match &element.kind {
+                ElementKind::Group { position, opacity } => {
+                    let group = if i == 0 {
+                        let scale = [
+                            builder.size[0] * 2. / (width * document.unit),
+                            builder.size[1] * 2. / (height * document.unit) * up,
+                        ];
+                        let group = GroupBuilder::new()
+                            .with_position(builder.position)
+                            .with_scale(scale)
+                            .with_opacity(opacity * builder.opacity);
+                        match builder.mask {
+                            Some(mask) => group.with_mask(mask),
+                            None => group,
+                        }
+                    } else {
+                        GroupBuilder::new()
+                            .with_position(*position)
+                            .with_opacity(*opacity)
+                    };
+                    let group = scene.group_in(parent, group).await;
+                    group_ids.push(group.id());
+                    SvgPart::Group(group)
+                }
+                ElementKind::Shape(shape) => {
+                    group_ids.push(parent);
+                    let mut path = PathBuilder::new(shape.data.clone())
+                        .with_fill(shape.fill.clone())
+                        .with_fill_rule(shape.fill_rule)
+                        .with_stroke_style(shape.stroke.clone())
+                        .with_stroke(shape.stroke.width, shape.stroke_color)
+                        .with_blend(builder.blend)
+                        .with_z_index(builder.z_index);
+                    if let Some(id) = builder.mask_source {
+                        path = path.into_mask(id);
+                    }
+                    SvgPart::Path(Box::new(scene.spawn_in(parent, path).await))
+                }
+            };
+
+            if !element.id.is_empty() {
+                ids.entry(element.id.clone()).or_insert(nodes.len());
+            }
+            nodes.push(node);
+        }
+
+        Self {
+            nodes,
+            ids,
+            dropped: false,
+        }
+    }
+
+    /// Drops the paths and the groups, the children first
+    pub async fn drop(mut self) {
+        for node in std::mem::take(&mut self.nodes).into_iter().rev() {
+            node.drop().await;
+        }
+        self.dropped = true;
+    }
+
+    /// The group of the box, its position is the center of the box and its scale stretches the document to it
+    pub fn root(&mut self) -> &mut Group<'a> {
+        let SvgPart::Group(root) = &mut self.nodes[0] else {
+            unreachable!("The first node is the root group")
+        };
+        root
+    }
+
+    /// The node of the element with the `id`
+    pub fn get(&mut self, id: &str) -> Option<&mut SvgPart<'a>> {
+        let index = *self.ids.get(id)?;
+        self.nodes.get_mut(index)
+    }
+
+    /// The group of the `<g>` with the `id`
+    pub fn group(&mut self, id: &str) -> Option<&mut Group<'a>> {
+        match self.get(id)? {
+            SvgPart::Group(group) => Some(group),
+            SvgPart::Path(_) => None,
+        }
+    }
+
+    /// The path of the shape with the `id`
+    pub fn path(&mut self, id: &str) -> Option<&mut Path<'a>> {
+        match self.get(id)? {
+            SvgPart::Path(path) => Some(path.as_mut()),
+            SvgPart::Group(_) => None,
+        }
+    }
+}
+
+impl<'a> Bounds for Svg<'a> {
+    /// The shapes that are drawn, the empty parts of the box are not in it
+    fn bounds(&self) -> NodeBounds {
+        let SvgPart::Group(root) = &self.nodes[0] else {
+            unreachable!("The first node is the root group")
+        };
+        root.bounds()
+    }
+}
+
+impl<'a> Drop for Svg<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Svg when you are done with it!");
+        std::process::abort();
+    }
+}
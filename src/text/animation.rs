@@ -0,0 +1,322 @@
+use crate::signal::{lerp, Executor, Lerp, Signal};
+
+/// How a glyph looks before it appears, every glyph animates to its place with full opacity and scale
+/// the glyphs start one after another in the order of the text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphAnimation {
+    /// In pixels
+    pub offset: [f32; 2],
+    pub opacity: f32,
+    /// Around the center of the glyph
+    pub scale: f32,
+    /// `0` every glyph animates at the same time, `1` a glyph starts when the previous one ends
+    pub stagger: f32,
+    /// The glyphs appear at once when they start instead of interpolating
+    pub step: bool,
+}
+
+impl Default for GlyphAnimation {
+    fn default() -> Self {
+        Self {
+            offset: [0.; 2],
+            opacity: 1.,
+            scale: 1.,
+            stagger: 0.,
+            step: false,
+        }
+    }
+}
+
+impl GlyphAnimation {
+    /// Reveals one glyph after another
+    pub fn typewriter() -> Self {
+        Self {
+            opacity: 0.,
+            stagger: 1.,
+            step: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_offset(mut self, offset: [f32; 2]) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_stagger(mut self, stagger: f32) -> Self {
+        self.stagger = stagger;
+        self
+    }
+
+    pub fn with_step(mut self, step: bool) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// The progress of the glyph `index` of `count` glyphs when the whole text is at `progress`
+    pub fn glyph_progress(&self, progress: f32, index: usize, count: usize) -> f32 {
+        let stagger = self.stagger.clamp(0., 1.);
+        let duration = 1. / (1. + stagger * count.saturating_sub(1) as f32);
+        let start = index as f32 * stagger * duration;
+
+        if self.step {
+            if progress > start || progress >= 1. {
+                1.
+            } else {
+                0.
+            }
+        } else {
+            ((progress - start) / duration).clamp(0., 1.)
+        }
+    }
+
+    /// Offset, opacity and scale of a glyph at its progress
+    pub fn at(&self, progress: f32) -> ([f32; 2], f32, f32) {
+        let progress = progress as f64;
+        (
+            [
+                lerp(self.offset[0], 0., progress),
+                lerp(self.offset[1], 0., progress),
+            ],
+            lerp(self.opacity, 1., progress),
+            lerp(self.scale, 1., progress),
+        )
+    }
+}
+
+/// A step of the edits from one sequence to another, with the indices in the sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Edit {
+    Keep(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Above this the middle of the sequences is replaced instead of diffed
+const MAX_DIFF_CELLS: usize = 1 << 20;
+
+/// The shortest edits from `from` to `to` by the longest common subsequence
+pub(super) fn diff<T: PartialEq>(from: &[T], to: &[T]) -> Vec<Edit> {
+    let prefix = from.iter().zip(to).take_while(|(a, b)| a == b).count();
+    let suffix = from[prefix..]
+        .iter()
+        .rev()
+        .zip(to[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &from[prefix..from.len() - suffix];
+    let b = &to[prefix..to.len() - suffix];
+
+    let mut edits = (0..prefix).map(|i| Edit::Keep(i, i)).collect::<Vec<_>>();
+
+    if (a.len() + 1) * (b.len() + 1) > MAX_DIFF_CELLS {
+        edits.extend((0..a.len()).map(|i| Edit::Delete(prefix + i)));
+        edits.extend((0..b.len()).map(|j| Edit::Insert(prefix + j)));
+    } else {
+        // lengths of the common subsequences of the suffixes
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                edits.push(Edit::Keep(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == b.len()
+                || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                edits.push(Edit::Delete(prefix + i));
+                i += 1;
+            } else {
+                edits.push(Edit::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    let (a_end, b_end) = (from.len() - suffix, to.len() - suffix);
+    edits.extend((0..suffix).map(|i| Edit::Keep(a_end + i, b_end + i)));
+    edits
+}
+
+/// Precomputed retyping from one string to another
+///
+/// deletes the chars that are not in `to` from the back like a backspace, then types the new chars
+#[derive(Debug, Clone)]
+pub struct Retype {
+    from: Vec<char>,
+    to: Vec<char>,
+    edits: Vec<Edit>,
+    deletes: usize,
+    inserts: usize,
+}
+
+impl Retype {
+    pub fn new(from: &str, to: &str) -> Self {
+        let from = from.chars().collect::<Vec<_>>();
+        let to = to.chars().collect::<Vec<_>>();
+        let edits = diff(&from, &to);
+        let count = |kind: fn(&Edit) -> bool| edits.iter().filter(|edit| kind(edit)).count();
+        let deletes = count(|edit| matches!(edit, Edit::Delete(_)));
+        let inserts = count(|edit| matches!(edit, Edit::Insert(_)));
+        Self {
+            from,
+            to,
+            edits,
+            deletes,
+            inserts,
+        }
+    }
+
+    pub fn at(&self, time: f64) -> String {
+        if time <= 0. {
+            return self.from.iter().collect();
+        }
+        if time >= 1. {
+            return self.to.iter().collect();
+        }
+
+        let step = (time * (self.deletes + self.inserts) as f64).floor() as usize;
+        let (mut deleted, mut inserted) = (0, 0);
+        let mut out = String::with_capacity(self.from.len().max(self.to.len()));
+        for edit in &self.edits {
+            match *edit {
+                Edit::Keep(i, _) => out.push(self.from[i]),
+                Edit::Delete(i) => {
+                    // the last delete is the first one to happen
+                    if self.deletes - 1 - deleted >= step {
+                        out.push(self.from[i]);
+                    }
+                    deleted += 1;
+                }
+                Edit::Insert(j) => {
+                    if self.deletes + inserted < step {
+                        out.push(self.to[j]);
+                    }
+                    inserted += 1;
+                }
+            }
+        }
+        out
+    }
+}
+
+impl<'a> Signal<'a, String> {
+    /// Like `tween` from the current value but the diff is computed once, not on every frame
+    pub fn retype(&mut self, to: String, time: f64) -> Executor<'_> {
+        let retype = Retype::new(self.value(), &to);
+        self.tween_with(move |t| retype.at(t), time)
+    }
+}
+
+/// Diffs every time, for tweens use `Signal::retype`
+impl Lerp for String {
+    fn lerp(&self, to: &Self, time: f64) -> Self {
+        Retype::new(self, to).at(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    /// Checks that the edits walk both sequences in order and only keep equal items
+    fn check(from: &str, to: &str) -> Vec<Edit> {
+        let (a, b) = (chars(from), chars(to));
+        let edits = diff(&a, &b);
+        let (mut i, mut j) = (0, 0);
+        for edit in &edits {
+            match *edit {
+                Edit::Keep(x, y) => {
+                    assert_eq!((x, y), (i, j));
+                    assert_eq!(a[x], b[y]);
+                    i += 1;
+                    j += 1;
+                }
+                Edit::Delete(x) => {
+                    assert_eq!(x, i);
+                    i += 1;
+                }
+                Edit::Insert(y) => {
+                    assert_eq!(y, j);
+                    j += 1;
+                }
+            }
+        }
+        assert_eq!((i, j), (a.len(), b.len()));
+        edits
+    }
+
+    fn kept(edits: &[Edit]) -> usize {
+        edits
+            .iter()
+            .filter(|edit| matches!(edit, Edit::Keep(..)))
+            .count()
+    }
+
+    #[test]
+    fn equal() {
+        assert_eq!(
+            check("abc", "abc"),
+            vec![Edit::Keep(0, 0), Edit::Keep(1, 1), Edit::Keep(2, 2)]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(check("", ""), vec![]);
+        assert_eq!(check("", "ab"), vec![Edit::Insert(0), Edit::Insert(1)]);
+        assert_eq!(check("ab", ""), vec![Edit::Delete(0), Edit::Delete(1)]);
+    }
+
+    #[test]
+    fn replace_in_the_middle() {
+        assert_eq!(
+            check("abc", "axc"),
+            vec![
+                Edit::Keep(0, 0),
+                Edit::Delete(1),
+                Edit::Insert(1),
+                Edit::Keep(2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn longest_common_subsequence() {
+        assert_eq!(kept(&check("kitten", "sitting")), 4);
+        assert_eq!(kept(&check("let x = 1;", "let mut x = 10;")), 10);
+        assert_eq!(kept(&check("abcdef", "fedcba")), 1);
+    }
+
+    #[test]
+    fn string_lerp_ends() {
+        let (from, to) = ("hello".to_string(), "help".to_string());
+        assert_eq!(from.lerp(&to, 0.), from);
+        assert_eq!(from.lerp(&to, 1.), to);
+    }
+}
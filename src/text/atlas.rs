@@ -0,0 +1,280 @@
+use std::{
+    cell::{RefCell, RefMut},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use ab_glyph::{point, Font as _, GlyphId};
+
+use crate::gcx::{
+    texture::{Format, InternalFormat, Texture, TextureFilter, TextureTarget, TextureType},
+    DataType, GCX,
+};
+
+use super::font::Font;
+
+/// The width and the height, it doubles when a glyph does not fit up to the max texture size
+const INITIAL_SIZE: usize = 1024;
+/// The smallest max texture size of GLES 3, until `texture` queries the one of the context
+const MIN_MAX_SIZE: usize = 2048;
+/// Empty pixels between the glyphs, so linear filtering will not bleed
+const PADDING: usize = 1;
+/// Glyphs are rasterized at 8 sizes per octave and scaled down to the real size
+const STEPS_PER_OCTAVE: f32 = 8.;
+const MIN_RASTER_SIZE: f32 = 4.;
+const MAX_RASTER_SIZE: f32 = 256.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: usize,
+    glyph: GlyphId,
+    /// Index of the raster size
+    size: i32,
+}
+
+/// A rasterized glyph, the bounds are in pixels of one em at `size` relative to the pen with y going up
+/// the uvs are in texels, so they stay the same when the atlas grows
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AtlasGlyph {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+}
+
+impl AtlasGlyph {
+    pub fn scaled(mut self, scale: f32) -> Self {
+        self.min = [self.min[0] * scale, self.min[1] * scale];
+        self.max = [self.max[0] * scale, self.max[1] * scale];
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Shelf {
+    y: usize,
+    height: usize,
+    x: usize,
+}
+
+/// Single channel coverage of the rasterized glyphs of every font
+///
+/// when a glyph does not fit the atlas grows, at `max_size` the glyph is skipped and the atlas
+/// is cleared by the next `begin`, then the `generation` changes so the meshes need to be rebuilt
+pub(super) struct GlyphAtlas {
+    size: usize,
+    /// `None` until the first `texture`
+    max_size: Option<usize>,
+    data: Vec<u8>,
+    shelves: Vec<Shelf>,
+    /// `None` if the glyph has no outline like a space
+    glyphs: HashMap<GlyphKey, Option<AtlasGlyph>>,
+    texture: Option<Texture>,
+    /// The rows that changed since the last upload, from the first to the one after the last
+    dirty: Option<[usize; 2]>,
+    generation: usize,
+    /// A glyph did not fit since the last `begin`
+    full: bool,
+}
+
+thread_local! {
+    static SHARED: Rc<RefCell<GlyphAtlas>> = Rc::default();
+}
+
+/// The atlas of the thread with the GL context, every text and code node rasterizes in to it
+///
+/// a manager can clear it with `begin` between the update and the render of another one,
+/// so the meshes are built right before they are drawn
+#[derive(Clone)]
+pub(super) struct SharedAtlas(Rc<RefCell<GlyphAtlas>>);
+
+impl Default for SharedAtlas {
+    fn default() -> Self {
+        Self(SHARED.with(Rc::clone))
+    }
+}
+
+impl SharedAtlas {
+    pub fn borrow_mut(&self) -> RefMut<'_, GlyphAtlas> {
+        self.0.borrow_mut()
+    }
+}
+
+impl Default for GlyphAtlas {
+    fn default() -> Self {
+        Self {
+            size: INITIAL_SIZE,
+            max_size: None,
+            data: vec![0; INITIAL_SIZE * INITIAL_SIZE],
+            shelves: Vec::new(),
+            glyphs: HashMap::new(),
+            texture: None,
+            dirty: None,
+            generation: 0,
+            full: false,
+        }
+    }
+}
+
+impl GlyphAtlas {
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Call before the meshes are built, so the atlas is never cleared in the middle of a build
+    /// if a glyph did not fit since the last call every glyph is removed
+    pub fn begin(&mut self) {
+        if !self.full {
+            return;
+        }
+
+        self.data.fill(0);
+        self.shelves.clear();
+        self.glyphs.clear();
+        self.mark_dirty(0, self.size);
+        self.generation += 1;
+        self.full = false;
+    }
+
+    /// The glyph for one em of `size` pixels, will rasterize it if is not in the atlas
+    pub fn glyph(&mut self, font: &Font, glyph: GlyphId, size: f32) -> Option<AtlasGlyph> {
+        let step = (size.clamp(MIN_RASTER_SIZE, MAX_RASTER_SIZE).log2() * STEPS_PER_OCTAVE).ceil();
+        let raster_size = (step / STEPS_PER_OCTAVE).exp2();
+        let key = GlyphKey {
+            font: font.id,
+            glyph,
+            size: step as i32,
+        };
+
+        let atlas_glyph = match self.glyphs.get(&key) {
+            Some(atlas_glyph) => *atlas_glyph,
+            None => {
+                let atlas_glyph = self.rasterize(font, glyph, raster_size);
+                self.glyphs.insert(key, atlas_glyph);
+                atlas_glyph
+            }
+        };
+        atlas_glyph.map(|atlas_glyph| atlas_glyph.scaled(size / raster_size))
+    }
+
+    fn rasterize(&mut self, font: &Font, glyph: GlyphId, size: f32) -> Option<AtlasGlyph> {
+        let outlined = font
+            .font
+            .outline_glyph(glyph.with_scale_and_position(font.scale(size), point(0., 0.)))?;
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+
+        let [x, y] = loop {
+            if let Some(corner) = self.allocate(width, height) {
+                break corner;
+            }
+            if self.size >= self.max_size.unwrap_or(MIN_MAX_SIZE) {
+                if !self.full {
+                    eprintln!("The glyph atlas is full, it will be cleared for the next build");
+                }
+                self.full = true;
+                return None;
+            }
+            self.grow();
+        };
+
+        outlined.draw(|gx, gy, coverage| {
+            let (gx, gy) = (gx as usize, gy as usize);
+            if gx < width && gy < height {
+                self.data[(y + gy) * self.size + x + gx] = (coverage.clamp(0., 1.) * 255.) as u8;
+            }
+        });
+        self.mark_dirty(y, y + height);
+
+        Some(AtlasGlyph {
+            min: [bounds.min.x, -bounds.max.y],
+            max: [bounds.max.x, -bounds.min.y],
+            uv_min: [x as f32, (y + height) as f32],
+            uv_max: [(x + width) as f32, y as f32],
+        })
+    }
+
+    /// Doubles the size up to the max texture size, the glyphs keep their texels in the top left corner
+    fn grow(&mut self) {
+        let size = (self.size * 2).min(self.max_size.unwrap_or(MIN_MAX_SIZE));
+        let mut data = vec![0; size * size];
+        for (row, old) in data
+            .chunks_exact_mut(size)
+            .zip(self.data.chunks_exact(self.size))
+        {
+            row[..self.size].copy_from_slice(old);
+        }
+        self.size = size;
+        self.data = data;
+        // the new texture is made with every row
+        self.texture = None;
+        self.dirty = None;
+    }
+
+    fn mark_dirty(&mut self, from: usize, to: usize) {
+        self.dirty = Some(match self.dirty {
+            Some([first, last]) => [first.min(from), last.max(to)],
+            None => [from, to],
+        });
+    }
+
+    /// Shelf packing, returns the top left corner
+    fn allocate(&mut self, width: usize, height: usize) -> Option<[usize; 2]> {
+        let (w, h) = (width + PADDING, height + PADDING);
+        let size = self.size;
+        if w > size || h > size {
+            return None;
+        }
+
+        if let Some(shelf) = self
+            .shelves
+            .iter_mut()
+            .find(|shelf| shelf.height >= h && shelf.height <= h + h / 2 && shelf.x + w <= size)
+        {
+            shelf.x += w;
+            return Some([shelf.x - w, shelf.y]);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map(|shelf| shelf.y + shelf.height)
+            .unwrap_or(0);
+        if y + h > size {
+            return None;
+        }
+        self.shelves.push(Shelf { y, height: h, x: w });
+        Some([0, y])
+    }
+
+    /// Uploads the rows with new glyphs
+    pub fn texture(&mut self, gcx: &GCX) -> &Texture {
+        self.max_size
+            .get_or_insert_with(|| gcx.max_texture_size().max(MIN_MAX_SIZE as i32) as usize);
+        if let (Some(texture), Some([from, to])) = (&self.texture, self.dirty) {
+            texture.update_rows(
+                from as i32,
+                (to - from) as i32,
+                &self.data[from * self.size..to * self.size],
+            );
+        }
+        self.dirty = None;
+
+        self.texture.get_or_insert_with(|| {
+            let texture = gcx.create_texture(
+                TextureType::Tex2D,
+                TextureTarget::Tex2D,
+                0,
+                InternalFormat::R8,
+                self.size as i32,
+                self.size as i32,
+                Format::Red,
+                DataType::U8,
+                &self.data,
+            );
+            texture.set_filter(TextureFilter::Linear);
+            texture
+        })
+    }
+}
@@ -0,0 +1,556 @@
+use crate::{
+    gcx::{blend::BlendMode, shader::Shader, GCX},
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    node::{offset_bounds, Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    signal::{create_signal, lerp, NSignal, RawSignal, Signal},
+};
+
+use super::{
+    animation::{diff, Edit},
+    atlas::{GlyphAtlas, SharedAtlas},
+    font::Font,
+    highlight::{tokenize, CodeTheme, Language, Token},
+    mesh::{glyph_shader, GlyphMesh, GlyphVertex},
+};
+
+#[derive(Debug)]
+pub struct CodeBuilder {
+    pub(super) code: String,
+    pub(super) language: Language,
+    /// Should be monospace, every char takes the width of a space
+    pub(super) font: Font,
+    pub(super) font_size: f32,
+    pub(super) theme: CodeTheme,
+    pub(super) position: [f32; 2],
+    pub(super) line_height: f32,
+    pub(super) transition: f32,
+    pub(super) selection: Option<[usize; 2]>,
+    pub(super) dim: f32,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl CodeBuilder {
+    /// `font_size` is the size of one em in pixels
+    pub fn new(code: impl Into<String>, language: Language, font: Font, font_size: f32) -> Self {
+        Self {
+            code: code.into(),
+            language,
+            font,
+            font_size,
+            theme: CodeTheme::default(),
+            position: [0.; 2],
+            line_height: 1.2,
+            transition: 1.,
+            selection: None,
+            dim: 0.35,
+            blend: BlendMode::Normal,
+            z_index: 0,
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    pub fn with_theme(mut self, theme: CodeTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// The top left corner of the code
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    /// Highlights the lines from `first` to `last` counting from `0` and dims the others,
+    /// the lines can be given in any order
+    pub fn with_selection(mut self, first: usize, last: usize) -> Self {
+        self.selection = Some([first, last]);
+        self
+    }
+
+    /// The opacity of the lines outside of the selection
+    pub fn with_dim(mut self, dim: f32) -> Self {
+        self.dim = dim;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+pub struct Code<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+    /// For the tokens of the bounds
+    font: Font,
+    language: Language,
+
+    /// Setting the code will start a transition from the current code, that is at `transition`
+    pub code: Signal<'a, String>,
+    pub theme: Signal<'a, CodeTheme>,
+    pub font_size: Signal<'a, f32>,
+    pub position: Signal<'a, [f32; 2]>,
+    pub line_height: Signal<'a, f32>,
+    /// From `0` the previous code to `1` the current code
+    /// the kept tokens slide to their new place, the deleted ones fade out and the inserted ones fade in
+    pub transition: Signal<'a, f32>,
+    pub selection: Signal<'a, Option<[usize; 2]>>,
+    pub dim: Signal<'a, f32>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Code<'a> {
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+
+    /// Animates from the current code to `code` in `time` seconds
+    pub async fn transition_to(&mut self, code: impl Into<String>, time: f64) {
+        self.code.set(code.into()).await;
+        self.transition.set(0.).await;
+        self.transition.tween(0., 1., time).await;
+    }
+}
+
+impl<'a> Bounds for Code<'a> {
+    fn bounds(&self) -> NodeBounds {
+        let tokens = tokenize(self.code.value(), self.language);
+        let up = self.scene.info.try_read().unwrap().origin.up();
+        NodeBounds::Local(
+            self.parent,
+            offset_bounds(
+                *self.position.value(),
+                code_bounds(
+                    &self.font,
+                    *self.font_size.value(),
+                    *self.line_height.value(),
+                    &tokens,
+                ),
+                up,
+            ),
+        )
+    }
+}
+
+/// The chars of the longest line
+fn columns(tokens: &[Token]) -> usize {
+    tokens
+        .iter()
+        .map(|token| token.column + token.text.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+/// The min and the max corner of the lines relative to the position before the y is multiplied by `WorldUp`
+fn code_bounds(font: &Font, size: f32, line_height: f32, tokens: &[Token]) -> [[f32; 2]; 2] {
+    if size <= 0. {
+        return [[0.; 2]; 2];
+    }
+
+    let cell = font.advance(size, font.glyph_id(' '));
+    let (ascent, descent, line_gap) = font.metrics(size);
+    let advance_y = (ascent - descent + line_gap) * line_height;
+    let lines = tokens.iter().map(|token| token.line + 1).max().unwrap_or(0);
+    [
+        [0., -(lines as f32) * advance_y],
+        [columns(tokens) as f32 * cell, 0.],
+    ]
+}
+
+impl<'a> Drop for Code<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Code when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for CodeBuilder {
+    type Node<'a> = Code<'a>;
+    type NodeManager = CodeNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawCode,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        Code {
+            scene,
+            parent,
+            font: self.font.clone(),
+            language: self.language,
+            dropped: false,
+            code: Signal::new(raw.code, scene, self.code.clone()),
+            theme: Signal::new(raw.theme, scene, self.theme),
+            font_size: Signal::new(raw.font_size, scene, self.font_size),
+            position: Signal::new(raw.position, scene, self.position),
+            line_height: Signal::new(raw.line_height, scene, self.line_height),
+            transition: Signal::new(raw.transition, scene, self.transition),
+            selection: Signal::new(raw.selection, scene, self.selection),
+            dim: Signal::new(raw.dim, scene, self.dim),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+pub struct NCode {
+    mesh: GlyphMesh,
+    rebuild: bool,
+    from: Vec<Token>,
+    to: Vec<Token>,
+    edits: Vec<Edit>,
+    parent: GroupId,
+    builder: CodeBuilder,
+    inner: NCodeInner,
+}
+
+pub struct NCodeInner {
+    drop: NSignal<()>,
+    code: NSignal<String>,
+    theme: NSignal<CodeTheme>,
+    font_size: NSignal<f32>,
+    position: NSignal<[f32; 2]>,
+    line_height: NSignal<f32>,
+    transition: NSignal<f32>,
+    selection: NSignal<Option<[usize; 2]>>,
+    dim: NSignal<f32>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+}
+
+pub struct RawCode {
+    drop: RawSignal<()>,
+    code: RawSignal<String>,
+    theme: RawSignal<CodeTheme>,
+    font_size: RawSignal<f32>,
+    position: RawSignal<[f32; 2]>,
+    line_height: RawSignal<f32>,
+    transition: RawSignal<f32>,
+    selection: RawSignal<Option<[usize; 2]>>,
+    dim: RawSignal<f32>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+}
+
+#[derive(Default)]
+pub struct CodeNodeManager {
+    pub(super) codes: Vec<NCode>,
+    pub(super) shader: Option<Shader>,
+    atlas: SharedAtlas,
+
+    pending: Option<NCodeInner>,
+}
+
+impl NodeManager for CodeNodeManager {
+    type NodeBuilder = CodeBuilder;
+    type RawNode = RawCode;
+
+    fn init(&mut self, gcx: &GCX) {
+        self.shader.replace(glyph_shader(gcx));
+    }
+
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
+        let to = tokenize(&builder.code, builder.language);
+        self.codes.push(NCode {
+            mesh: GlyphMesh::new(gcx, self.atlas.borrow_mut().generation()),
+            rebuild: true,
+            from: to.clone(),
+            edits: (0..to.len()).map(|i| Edit::Keep(i, i)).collect(),
+            to,
+            parent,
+            builder,
+            inner: self.pending.take().unwrap(),
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.codes
+            .iter()
+            .map(|code| DrawItem {
+                z_index: code.builder.z_index,
+                parent: code.parent,
+                mask: code.builder.mask,
+                mask_source: code.builder.mask_source,
+                shadow: None,
+            })
+            .collect()
+    }
+
+    fn bounds(&self, graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.codes
+            .iter()
+            .map(|code| {
+                let builder = &code.builder;
+                offset_bounds(
+                    builder.position,
+                    code_bounds(
+                        &builder.font,
+                        builder.font_size,
+                        builder.line_height,
+                        &code.to,
+                    ),
+                    graph.world(code.parent).up,
+                )
+            })
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let Some(shader) = &self.shader else { panic!() };
+        let mut atlas = self.atlas.borrow_mut();
+        atlas.begin();
+        for &i in items {
+            self.codes[i].rebuild(&mut atlas);
+        }
+
+        let texture = atlas.texture(gcx);
+        gcx.use_shader(shader, |gcx| {
+            texture.activate(0);
+            let _ = shader.set_uniform("ATLAS", 0);
+            // the theme colors are in the vertices
+            let _ = shader.set_uniform("Color", (1f32, 1f32, 1f32, 1f32));
+
+            for code in items.iter().map(|&i| &self.codes[i]) {
+                if code.mesh.is_empty() {
+                    continue;
+                }
+
+                let [x, y] = code.builder.position;
+                let _ = shader.set_uniform("Position", (x, y));
+                graph.world(code.parent).set_uniforms(shader);
+                let _blend = gcx.blend(code.builder.blend);
+                code.mesh.draw(&gcx);
+            }
+        });
+    }
+
+    fn create_node(&mut self) -> RawCode {
+        let (ncode, code) = create_signal();
+        let (ntheme, theme) = create_signal();
+        let (nfont_size, font_size) = create_signal();
+        let (nposition, position) = create_signal();
+        let (nline_height, line_height) = create_signal();
+        let (ntransition, transition) = create_signal();
+        let (nselection, selection) = create_signal();
+        let (ndim, dim) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NCodeInner {
+            drop,
+            code,
+            theme,
+            font_size,
+            position,
+            line_height,
+            transition,
+            selection,
+            dim,
+            blend,
+            z_index,
+        });
+
+        RawCode {
+            drop: ndrop,
+            code: ncode,
+            theme: ntheme,
+            font_size: nfont_size,
+            position: nposition,
+            line_height: nline_height,
+            transition: ntransition,
+            selection: nselection,
+            dim: ndim,
+            blend: nblend,
+            z_index: nz_index,
+        }
+    }
+
+    fn update(&mut self) {
+        self.codes.retain_mut(|code| {
+            if let Some(source) = code.inner.code.get() {
+                let to = tokenize(&source, code.builder.language);
+                let keys = |tokens: &[Token]| {
+                    tokens
+                        .iter()
+                        .map(|token| (token.kind, token.text.clone()))
+                        .collect::<Vec<_>>()
+                };
+                code.edits = diff(&keys(&code.to), &keys(&to));
+                code.from = std::mem::replace(&mut code.to, to);
+                code.builder.code = source;
+                code.rebuild = true;
+            }
+            if let Some(theme) = code.inner.theme.get() {
+                code.builder.theme = theme;
+                code.rebuild = true;
+            }
+            if let Some(font_size) = code.inner.font_size.get() {
+                code.builder.font_size = font_size;
+                code.rebuild = true;
+            }
+            if let Some(line_height) = code.inner.line_height.get() {
+                code.builder.line_height = line_height;
+                code.rebuild = true;
+            }
+            if let Some(transition) = code.inner.transition.get() {
+                code.builder.transition = transition;
+                code.rebuild = true;
+            }
+            if let Some(selection) = code.inner.selection.get() {
+                code.builder.selection = selection;
+                code.rebuild = true;
+            }
+            if let Some(dim) = code.inner.dim.get() {
+                code.builder.dim = dim;
+                code.rebuild = true;
+            }
+            if let Some(position) = code.inner.position.get() {
+                code.builder.position = position;
+            }
+            if let Some(blend) = code.inner.blend.get() {
+                code.builder.blend = blend;
+            }
+            if let Some(z_index) = code.inner.z_index.get() {
+                code.builder.z_index = z_index;
+            }
+
+            code.inner.drop.get().is_none()
+        });
+    }
+}
+
+impl NCode {
+    /// Builds the mesh if it changed or if the atlas was cleared since it was built
+    fn rebuild(&mut self, atlas: &mut GlyphAtlas) {
+        if !self.rebuild && self.mesh.generation == atlas.generation() {
+            return;
+        }
+
+        let mesh = self.build_mesh(atlas);
+        self.mesh.set(&mesh, atlas.generation());
+        self.rebuild = false;
+    }
+
+    fn build_mesh(&self, atlas: &mut GlyphAtlas) -> Vec<GlyphVertex> {
+        let builder = &self.builder;
+        let size = builder.font_size;
+        let mut mesh = Vec::new();
+        if size <= 0. {
+            return mesh;
+        }
+
+        let font = &builder.font;
+        let cell = font.advance(size, font.glyph_id(' '));
+        let (ascent, descent, line_gap) = font.metrics(size);
+        let advance_y = (ascent - descent + line_gap) * builder.line_height;
+        let top = -(advance_y - (ascent - descent)) * 0.5 - ascent;
+        let pen = |token: &Token| {
+            [
+                token.column as f32 * cell,
+                top - token.line as f32 * advance_y,
+            ]
+        };
+
+        let selection = builder
+            .selection
+            .map(|[first, last]| [first.min(last), first.max(last)]);
+        let line_alpha = |line: usize| match selection {
+            Some([first, last]) if !(first..=last).contains(&line) => builder.dim,
+            _ => 1.,
+        };
+
+        if let (Some([first, last]), true) = (selection, builder.theme.highlight.a > 0.) {
+            let columns = columns(&self.to);
+            let min = [-cell * 0.5, -(last as f32 + 1.) * advance_y];
+            let max = [(columns as f32 + 0.5) * cell, -(first as f32) * advance_y];
+            mesh.extend(GlyphVertex::solid(min, max, builder.theme.highlight));
+        }
+
+        let t = builder.transition.clamp(0., 1.);
+        let slide = t * t * (3. - 2. * t);
+        for edit in self.edits.iter() {
+            let (token, pen, alpha) = match *edit {
+                Edit::Keep(i, j) => {
+                    let (from, to) = (pen(&self.from[i]), pen(&self.to[j]));
+                    let pen = [
+                        lerp(from[0], to[0], slide as f64),
+                        lerp(from[1], to[1], slide as f64),
+                    ];
+                    let alpha = lerp(
+                        line_alpha(self.from[i].line),
+                        line_alpha(self.to[j].line),
+                        t as f64,
+                    );
+                    (&self.to[j], pen, alpha)
+                }
+                Edit::Delete(i) => {
+                    let token = &self.from[i];
+                    let alpha = (1. - t * 2.).clamp(0., 1.);
+                    (token, pen(token), alpha * line_alpha(token.line))
+                }
+                Edit::Insert(j) => {
+                    let token = &self.to[j];
+                    let alpha = (t * 2. - 1.).clamp(0., 1.);
+                    (token, pen(token), alpha * line_alpha(token.line))
+                }
+            };
+            if alpha <= 0. {
+                continue;
+            }
+
+            let mut color = builder.theme.color(token.kind);
+            color.a *= alpha;
+            for (i, c) in token.text.chars().enumerate() {
+                let Some(glyph) = atlas.glyph(font, font.glyph_id(c), size) else {
+                    continue;
+                };
+
+                let pen = [pen[0] + i as f32 * cell, pen[1]];
+                mesh.extend(GlyphVertex::glyph(glyph, pen, 1., color));
+            }
+        }
+        mesh
+    }
+}
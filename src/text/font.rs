@@ -0,0 +1,120 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use ab_glyph::{Font as _, FontArc, GlyphId, PxScale};
+
+static FONT_ID: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    Invalid,
+}
+
+impl std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FontError::Io(err) => write!(f, "Cannot read font: {err}"),
+            FontError::Invalid => write!(f, "Invalid font, expected TrueType or OpenType"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+impl From<std::io::Error> for FontError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// The face for shaping, parsed once for every font
+struct Shaper {
+    /// Borrows the data of `font`, so it is declared first to be dropped first
+    face: rustybuzz::Face<'static>,
+    _font: FontArc,
+}
+
+/// A TrueType or OpenType font, is cheap to clone
+#[derive(Clone)]
+pub struct Font {
+    pub(super) id: usize,
+    pub(super) font: FontArc,
+    shaper: Arc<Shaper>,
+}
+
+impl std::fmt::Debug for Font {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Font").field("id", &self.id).finish()
+    }
+}
+
+impl PartialEq for Font {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Font {
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, FontError> {
+        let font = FontArc::try_from_vec(data).map_err(|_| FontError::Invalid)?;
+        // SAFETY: the data is on the heap behind the `FontArc` that the shaper keeps alive
+        // and it is never changed, so it is valid for as long as the face
+        let data: &'static [u8] = unsafe { std::mem::transmute(font.font_data()) };
+        let shaper = Shaper {
+            face: rustybuzz::Face::from_slice(data, 0).ok_or(FontError::Invalid)?,
+            _font: font.clone(),
+        };
+        Ok(Self {
+            id: FONT_ID.fetch_add(1, Ordering::Relaxed),
+            font,
+            shaper: Arc::new(shaper),
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Pixels per font unit when one em is `size` pixels
+    pub(super) fn px(&self, size: f32) -> f32 {
+        size / self.font.units_per_em().unwrap_or(1000.)
+    }
+
+    /// The scale where one em is `size` pixels
+    pub(super) fn scale(&self, size: f32) -> PxScale {
+        PxScale::from(self.font.height_unscaled() * self.px(size))
+    }
+
+    /// Ascent, descent and line gap in pixels
+    pub(super) fn metrics(&self, size: f32) -> (f32, f32, f32) {
+        let px = self.px(size);
+        (
+            self.font.ascent_unscaled() * px,
+            self.font.descent_unscaled() * px,
+            self.font.line_gap_unscaled() * px,
+        )
+    }
+
+    pub(super) fn glyph_id(&self, c: char) -> GlyphId {
+        self.font.glyph_id(c)
+    }
+
+    pub(super) fn advance(&self, size: f32, glyph: GlyphId) -> f32 {
+        self.font.h_advance_unscaled(glyph) * self.px(size)
+    }
+
+    pub(super) fn has_glyph(&self, c: char) -> bool {
+        self.font.glyph_id(c).0 != 0
+    }
+
+    /// The face for shaping
+    pub(super) fn face(&self) -> &rustybuzz::Face<'_> {
+        &self.shaper.face
+    }
+}
@@ -0,0 +1,582 @@
+use crate::color::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    /// No highlighting
+    Plain,
+}
+
+impl Language {
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+                "super", "trait", "true", "type", "unsafe", "use", "where", "while", "yield",
+            ],
+            Language::Python => &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+                "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+                "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+                "raise", "return", "self", "try", "while", "with", "yield", "match", "case",
+            ],
+            Language::JavaScript => &[
+                "async",
+                "await",
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "debugger",
+                "default",
+                "delete",
+                "do",
+                "else",
+                "export",
+                "extends",
+                "false",
+                "finally",
+                "for",
+                "from",
+                "function",
+                "if",
+                "import",
+                "in",
+                "instanceof",
+                "let",
+                "new",
+                "null",
+                "of",
+                "return",
+                "static",
+                "super",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "typeof",
+                "undefined",
+                "var",
+                "void",
+                "while",
+                "with",
+                "yield",
+            ],
+            Language::Plain => &[],
+        }
+    }
+
+    fn types(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &[
+                "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16",
+                "i32", "i64", "i128", "isize", "f32", "f64",
+            ],
+            Language::Python => &[
+                "int", "float", "str", "bool", "list", "dict", "set", "tuple", "bytes", "object",
+            ],
+            Language::JavaScript | Language::Plain => &[],
+        }
+    }
+
+    fn line_comment(&self) -> Option<&'static str> {
+        match self {
+            Language::Rust | Language::JavaScript => Some("//"),
+            Language::Python => Some("#"),
+            Language::Plain => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Text,
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Function,
+    Type,
+    Punctuation,
+}
+
+/// A token without whitespace, `line` and `column` are in chars from `0`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeTheme {
+    pub text: Color,
+    pub keyword: Color,
+    pub string: Color,
+    pub number: Color,
+    pub comment: Color,
+    pub function: Color,
+    pub ty: Color,
+    pub punctuation: Color,
+    /// Background of the selected lines
+    pub highlight: Color,
+}
+
+impl CodeTheme {
+    pub fn dark() -> Self {
+        Self {
+            text: Color::hex(0xabb2bfff),
+            keyword: Color::hex(0xc678ddff),
+            string: Color::hex(0x98c379ff),
+            number: Color::hex(0xd19a66ff),
+            comment: Color::hex(0x7f848eff),
+            function: Color::hex(0x61afefff),
+            ty: Color::hex(0xe5c07bff),
+            punctuation: Color::hex(0xabb2bfff),
+            highlight: Color::hex(0xffffff1a),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            text: Color::hex(0x383a42ff),
+            keyword: Color::hex(0xa626a4ff),
+            string: Color::hex(0x50a14fff),
+            number: Color::hex(0x986801ff),
+            comment: Color::hex(0xa0a1a7ff),
+            function: Color::hex(0x4078f2ff),
+            ty: Color::hex(0xc18401ff),
+            punctuation: Color::hex(0x383a42ff),
+            highlight: Color::hex(0x0000001a),
+        }
+    }
+
+    pub fn color(&self, kind: TokenKind) -> Color {
+        match kind {
+            TokenKind::Text => self.text,
+            TokenKind::Keyword => self.keyword,
+            TokenKind::String => self.string,
+            TokenKind::Number => self.number,
+            TokenKind::Comment => self.comment,
+            TokenKind::Function => self.function,
+            TokenKind::Type => self.ty,
+            TokenKind::Punctuation => self.punctuation,
+        }
+    }
+}
+
+impl Default for CodeTheme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    at: usize,
+    language: Language,
+    tokens: Vec<Token>,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.at + offset).copied()
+    }
+
+    fn starts_with(&self, pattern: &str) -> bool {
+        pattern
+            .chars()
+            .enumerate()
+            .all(|(i, c)| self.peek(i) == Some(c))
+    }
+
+    /// Pushes the chars until `end` as a token, multi line tokens are split in to one token per line
+    fn push(&mut self, kind: TokenKind, end: usize) {
+        let end = end.min(self.chars.len());
+        let mut text = String::new();
+        let (mut line, mut column) = (self.line, self.column);
+        for &c in &self.chars[self.at..end] {
+            if c == '\n' {
+                if !text.is_empty() {
+                    self.tokens.push(Token {
+                        kind,
+                        text: std::mem::take(&mut text),
+                        line,
+                        column,
+                    });
+                }
+                self.line += 1;
+                self.column = 0;
+                (line, column) = (self.line, 0);
+                continue;
+            }
+
+            if c.is_whitespace() && kind != TokenKind::String && kind != TokenKind::Comment {
+                self.column += 1;
+                continue;
+            }
+            if text.is_empty() {
+                column = self.column;
+            }
+            text.push(c);
+            self.column += 1;
+        }
+        if !text.is_empty() {
+            self.tokens.push(Token {
+                kind,
+                text,
+                line,
+                column,
+            });
+        }
+        self.at = end;
+    }
+
+    /// The end of the string that starts at `start` and ends with `quote`
+    fn string_end(&self, start: usize, quote: &str, escapes: bool, multiline: bool) -> usize {
+        let quote = quote.chars().collect::<Vec<_>>();
+        let mut i = start;
+        while i < self.chars.len() {
+            if escapes && self.chars[i] == '\\' {
+                i += 2;
+                continue;
+            }
+            if self.chars[i..].starts_with(&quote) {
+                return i + quote.len();
+            }
+            if self.chars[i] == '\n' && !multiline {
+                return i;
+            }
+            i += 1;
+        }
+        i
+    }
+
+    fn word_end(&self, start: usize) -> usize {
+        let mut i = start;
+        while i < self.chars.len() && (self.chars[i].is_alphanumeric() || self.chars[i] == '_') {
+            i += 1;
+        }
+        i
+    }
+
+    fn next(&mut self) -> bool {
+        let Some(c) = self.peek(0) else {
+            return false;
+        };
+        let language = self.language;
+
+        if c.is_whitespace() {
+            self.push(TokenKind::Text, self.at + 1);
+        } else if language == Language::Plain {
+            let mut end = self.at;
+            while end < self.chars.len() && !self.chars[end].is_whitespace() {
+                end += 1;
+            }
+            self.push(TokenKind::Text, end);
+        } else if language.line_comment().is_some_and(|l| self.starts_with(l)) {
+            let end = (self.at..self.chars.len())
+                .find(|i| self.chars[*i] == '\n')
+                .unwrap_or(self.chars.len());
+            self.push(TokenKind::Comment, end);
+        } else if language != Language::Python && self.starts_with("/*") {
+            let end = self.string_end(self.at + 2, "*/", false, true);
+            self.push(TokenKind::Comment, end);
+        } else if let Some(end) = self.string() {
+            self.push(TokenKind::String, end);
+        } else if c.is_ascii_digit() {
+            let mut end = self.at + 1;
+            while end < self.chars.len() {
+                let c = self.chars[end];
+                let fraction =
+                    c == '.' && self.chars.get(end + 1).is_some_and(|c| c.is_ascii_digit());
+                if !(c.is_alphanumeric() || c == '_' || fraction) {
+                    break;
+                }
+                end += 1;
+            }
+            self.push(TokenKind::Number, end);
+        } else if c.is_alphabetic() || c == '_' {
+            let end = self.word_end(self.at);
+            let word = self.chars[self.at..end].iter().collect::<String>();
+            let next = self.chars.get(end).copied();
+
+            let kind = if language.keywords().contains(&word.as_str()) {
+                TokenKind::Keyword
+            } else if next == Some('(') || (language == Language::Rust && next == Some('!')) {
+                TokenKind::Function
+            } else if language.types().contains(&word.as_str())
+                || word.starts_with(|c: char| c.is_uppercase())
+            {
+                TokenKind::Type
+            } else {
+                TokenKind::Text
+            };
+            let end = if kind == TokenKind::Function && next == Some('!') {
+                end + 1
+            } else {
+                end
+            };
+            self.push(kind, end);
+        } else if language == Language::Rust && c == '\'' {
+            // a lifetime or a label
+            let end = self.word_end(self.at + 1);
+            self.push(TokenKind::Type, end.max(self.at + 1));
+        } else if language == Language::Python && c == '@' {
+            let end = self.word_end(self.at + 1);
+            self.push(TokenKind::Function, end.max(self.at + 1));
+        } else {
+            self.push(TokenKind::Punctuation, self.at + 1);
+        }
+        true
+    }
+
+    /// The end of the string literal at the current char if there is one
+    fn string(&self) -> Option<usize> {
+        let prefix = self.chars[self.at..]
+            .iter()
+            .take_while(|c| c.is_alphabetic())
+            .count();
+        let prefix_text = self.chars[self.at..self.at + prefix]
+            .iter()
+            .collect::<String>()
+            .to_lowercase();
+        let quote_at = self.at + prefix;
+        let quote = *self.chars.get(quote_at)?;
+
+        match self.language {
+            Language::Rust => {
+                let raw = prefix_text == "r" || prefix_text == "br";
+                if raw {
+                    let hashes = self.chars[quote_at..]
+                        .iter()
+                        .take_while(|c| **c == '#')
+                        .count();
+                    if self.chars.get(quote_at + hashes) != Some(&'"') {
+                        return None;
+                    }
+                    let end = format!("\"{}", "#".repeat(hashes));
+                    return Some(self.string_end(quote_at + hashes + 1, &end, false, true));
+                }
+                if !matches!(prefix_text.as_str(), "" | "b") {
+                    return None;
+                }
+                match quote {
+                    '"' => Some(self.string_end(quote_at + 1, "\"", true, true)),
+                    // a char literal, a lifetime will not have the closing quote
+                    '\'' => {
+                        let escaped = self.chars.get(quote_at + 1) == Some(&'\\');
+                        let close = if escaped {
+                            (quote_at + 2..self.chars.len().min(quote_at + 12))
+                                .find(|i| self.chars[*i] == '\'')
+                        } else {
+                            Some(quote_at + 2).filter(|i| self.chars.get(*i) == Some(&'\''))
+                        };
+                        close.map(|close| close + 1)
+                    }
+                    _ => None,
+                }
+            }
+            Language::Python => {
+                let prefixes = ["", "r", "b", "f", "u", "rb", "br", "fr", "rf"];
+                if !prefixes.contains(&prefix_text.as_str()) || !matches!(quote, '"' | '\'') {
+                    return None;
+                }
+                let escapes = !prefix_text.contains('r');
+                let triple = quote.to_string().repeat(3);
+                if self.chars[quote_at..].starts_with(&triple.chars().collect::<Vec<_>>()) {
+                    Some(self.string_end(quote_at + 3, &triple, escapes, true))
+                } else {
+                    Some(self.string_end(quote_at + 1, &quote.to_string(), escapes, false))
+                }
+            }
+            Language::JavaScript => {
+                if prefix != 0 || !matches!(quote, '"' | '\'' | '`') {
+                    return None;
+                }
+                Some(self.string_end(quote_at + 1, &quote.to_string(), true, quote == '`'))
+            }
+            Language::Plain => None,
+        }
+    }
+}
+
+/// Splits the source in to highlighted tokens, tabs are 4 columns
+pub fn tokenize(source: &str, language: Language) -> Vec<Token> {
+    let mut lexer = Lexer {
+        chars: source.replace('\t', "    ").chars().collect(),
+        at: 0,
+        language,
+        tokens: Vec::new(),
+        line: 0,
+        column: 0,
+    };
+    while lexer.next() {}
+    lexer.tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::{Comment, Function, Keyword, Number, Punctuation, Text, Type};
+
+    fn kinds(source: &str, language: Language) -> Vec<(TokenKind, String)> {
+        tokenize(source, language)
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    fn expected(tokens: &[(TokenKind, &str)]) -> Vec<(TokenKind, String)> {
+        tokens
+            .iter()
+            .map(|(kind, text)| (*kind, text.to_string()))
+            .collect()
+    }
+
+    fn positions(source: &str, language: Language) -> Vec<(String, usize, usize)> {
+        tokenize(source, language)
+            .into_iter()
+            .map(|token| (token.text, token.line, token.column))
+            .collect()
+    }
+
+    #[test]
+    fn rust_keywords_functions_and_numbers() {
+        assert_eq!(
+            kinds("fn main() { let x = 1.5; }", Language::Rust),
+            expected(&[
+                (Keyword, "fn"),
+                (Function, "main"),
+                (Punctuation, "("),
+                (Punctuation, ")"),
+                (Punctuation, "{"),
+                (Keyword, "let"),
+                (Text, "x"),
+                (Punctuation, "="),
+                (Number, "1.5"),
+                (Punctuation, ";"),
+                (Punctuation, "}"),
+            ])
+        );
+    }
+
+    #[test]
+    fn rust_macros_strings_and_comments() {
+        assert_eq!(
+            kinds(r#"println!("a\"b"); // done"#, Language::Rust),
+            expected(&[
+                (Function, "println!"),
+                (Punctuation, "("),
+                (TokenKind::String, r#""a\"b""#),
+                (Punctuation, ")"),
+                (Punctuation, ";"),
+                (Comment, "// done"),
+            ])
+        );
+        assert_eq!(
+            kinds(r##"r#"a"b"# 'c'"##, Language::Rust),
+            expected(&[
+                (TokenKind::String, r##"r#"a"b"#"##),
+                (TokenKind::String, "'c'")
+            ])
+        );
+    }
+
+    #[test]
+    fn rust_lifetimes_and_types() {
+        assert_eq!(
+            kinds("&'a str, Vec<u8>", Language::Rust),
+            expected(&[
+                (Punctuation, "&"),
+                (Type, "'a"),
+                (Type, "str"),
+                (Punctuation, ","),
+                (Type, "Vec"),
+                (Punctuation, "<"),
+                (Type, "u8"),
+                (Punctuation, ">"),
+            ])
+        );
+    }
+
+    #[test]
+    fn python() {
+        assert_eq!(
+            kinds("@cache\ndef f(s: str): # hi", Language::Python),
+            expected(&[
+                (Function, "@cache"),
+                (Keyword, "def"),
+                (Function, "f"),
+                (Punctuation, "("),
+                (Text, "s"),
+                (Punctuation, ":"),
+                (Type, "str"),
+                (Punctuation, ")"),
+                (Punctuation, ":"),
+                (Comment, "# hi"),
+            ])
+        );
+        assert_eq!(
+            kinds(r#"f"{x}" + 'y'"#, Language::Python),
+            expected(&[
+                (TokenKind::String, r#"f"{x}""#),
+                (Punctuation, "+"),
+                (TokenKind::String, "'y'"),
+            ])
+        );
+    }
+
+    #[test]
+    fn javascript_template_strings() {
+        assert_eq!(
+            kinds("const s = `a\nb`;", Language::JavaScript),
+            expected(&[
+                (Keyword, "const"),
+                (Text, "s"),
+                (Punctuation, "="),
+                (TokenKind::String, "`a"),
+                (TokenKind::String, "b`"),
+                (Punctuation, ";"),
+            ])
+        );
+    }
+
+    #[test]
+    fn plain_splits_on_whitespace() {
+        assert_eq!(
+            kinds("fn main() {}", Language::Plain),
+            expected(&[(Text, "fn"), (Text, "main()"), (Text, "{}")])
+        );
+    }
+
+    #[test]
+    fn lines_and_columns() {
+        assert_eq!(
+            positions("a\n\tb  c", Language::Rust),
+            vec![
+                ("a".to_string(), 0, 0),
+                ("b".to_string(), 1, 4),
+                ("c".to_string(), 1, 7),
+            ]
+        );
+        assert_eq!(
+            positions("/* a\n  b */ x", Language::JavaScript),
+            vec![
+                ("/* a".to_string(), 0, 0),
+                ("  b */".to_string(), 1, 0),
+                ("x".to_string(), 1, 7),
+            ]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+use std::ops::Range;
+
+use ab_glyph::GlyphId;
+use rustybuzz::{Direction, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+
+use super::{font::Font, TextAlign};
+
+/// A glyph with the pen position of its baseline in pixels relative to the anchor, y goes up
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PlacedGlyph {
+    /// Index in the font fallback chain
+    pub font: usize,
+    pub glyph: GlyphId,
+    pub pen: [f32; 2],
+    /// Byte index in the text of the first char of the glyph
+    pub cluster: usize,
+}
+
+/// A shaped glyph in pixels
+#[derive(Debug, Clone, Copy)]
+struct ShapedGlyph {
+    font: usize,
+    glyph: GlyphId,
+    advance: f32,
+    offset: [f32; 2],
+    /// Byte index in the text of the first char of the glyph
+    cluster: usize,
+}
+
+/// Shapes and places `text`, the paragraphs are split by `\n` and are wrapped at `max_width` pixels
+/// the lines are aligned around the anchor horizontally and the whole block is centered vertically
+/// nothing is placed if the size is not positive
+pub(super) fn layout(
+    fonts: &[Font],
+    text: &str,
+    size: f32,
+    alignment: TextAlign,
+    line_height: f32,
+    max_width: Option<f32>,
+) -> Vec<PlacedGlyph> {
+    if size <= 0. {
+        return Vec::new();
+    }
+
+    let (ascent, descent, line_gap) = fonts[0].metrics(size);
+    let advance_y = (ascent - descent + line_gap) * line_height;
+
+    let bidi = BidiInfo::new(text, None);
+    let breaks = linebreaks(text).collect::<Vec<_>>();
+
+    let mut lines = Vec::new();
+    for (index, para) in bidi.paragraphs.iter().enumerate() {
+        let content = para.range.start
+            ..para.range.start + text[para.range.clone()].trim_end_matches(is_newline).len();
+        let paragraph_breaks = breaks
+            .iter()
+            .filter(|(at, _)| *at > content.start && *at <= para.range.end)
+            .map(|(at, opportunity)| ((*at).min(content.end), *opportunity));
+
+        for line in break_lines(
+            fonts,
+            text,
+            &bidi,
+            content.clone(),
+            paragraph_breaks,
+            size,
+            max_width,
+        ) {
+            lines.push((index, line));
+        }
+    }
+    if text.is_empty() || text.ends_with(is_newline) {
+        lines.push((bidi.paragraphs.len(), text.len()..text.len()));
+    }
+
+    let height = (ascent - descent) + advance_y * lines.len().saturating_sub(1) as f32;
+    let mut baseline = height * 0.5 - ascent;
+
+    let mut placed = Vec::with_capacity(text.len());
+    for (para, line) in lines {
+        let start = placed.len();
+        let mut x = 0.;
+        if let (Some(para), false) = (bidi.paragraphs.get(para), line.is_empty()) {
+            let (levels, runs) = bidi.visual_runs(para, line);
+            for run in runs {
+                let rtl = levels[run.start].is_rtl();
+                for glyph in shape(fonts, text, run, rtl, size) {
+                    placed.push(PlacedGlyph {
+                        font: glyph.font,
+                        glyph: glyph.glyph,
+                        pen: [x + glyph.offset[0], baseline + glyph.offset[1]],
+                        cluster: glyph.cluster,
+                    });
+                    x += glyph.advance;
+                }
+            }
+        }
+
+        let shift = match alignment {
+            TextAlign::Left => 0.,
+            TextAlign::Center => -x * 0.5,
+            TextAlign::Right => -x,
+        };
+        for glyph in placed[start..].iter_mut() {
+            glyph.pen[0] += shift;
+        }
+
+        baseline -= advance_y;
+    }
+    placed
+}
+
+fn is_newline(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{85}' | '\u{2029}')
+}
+
+/// Greedy line breaking on the UAX #14 break opportunities
+/// the trailing whitespace of a line does not count to its width and is not part of the range
+fn break_lines(
+    fonts: &[Font],
+    text: &str,
+    bidi: &BidiInfo,
+    content: Range<usize>,
+    breaks: impl Iterator<Item = (usize, BreakOpportunity)>,
+    size: f32,
+    max_width: Option<f32>,
+) -> Vec<Range<usize>> {
+    let trim = |range: Range<usize>| range.start..range.start + text[range].trim_end().len();
+
+    let mut lines = Vec::new();
+    let mut start = content.start;
+    let mut segment_start = content.start;
+    let mut width = 0.;
+    for (at, opportunity) in breaks {
+        if let Some(max_width) = max_width {
+            let segment = segment_start..at;
+            let rtl = bidi
+                .levels
+                .get(segment.start)
+                .is_some_and(|level| level.is_rtl());
+            let visible_end = trim(segment.clone()).end;
+            let (mut full, mut visible) = (0., 0.);
+            for glyph in shape(fonts, text, segment, rtl, size) {
+                full += glyph.advance;
+                if glyph.cluster < visible_end {
+                    visible += glyph.advance;
+                }
+            }
+
+            if start < segment_start && width + visible > max_width {
+                lines.push(trim(start..segment_start));
+                start = segment_start;
+                width = 0.;
+            }
+            width += full;
+        }
+        segment_start = at;
+
+        if at >= content.end {
+            break;
+        }
+        if opportunity == BreakOpportunity::Mandatory {
+            lines.push(trim(start..at));
+            start = at;
+            width = 0.;
+        }
+    }
+    lines.push(trim(start..content.end));
+    lines
+}
+
+/// Splits the text in to runs of the first font in the chain that has the glyphs
+/// whitespace and joiners stay with the font of the previous char
+fn font_runs(fonts: &[Font], text: &str, range: Range<usize>) -> Vec<(usize, Range<usize>)> {
+    let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+    for (i, c) in text[range.clone()].char_indices() {
+        let at = range.start + i..range.start + i + c.len_utf8();
+        let current = runs.last().map(|run| run.0);
+
+        let keep =
+            c.is_whitespace() || matches!(c, '\u{200C}' | '\u{200D}' | '\u{FE00}'..='\u{FE0F}');
+        let font = match current {
+            Some(font) if keep || fonts[font].has_glyph(c) => font,
+            _ => fonts
+                .iter()
+                .position(|font| font.has_glyph(c))
+                .unwrap_or(current.unwrap_or(0)),
+        };
+
+        match runs.last_mut() {
+            Some(run) if run.0 == font => run.1.end = at.end,
+            _ => runs.push((font, at)),
+        }
+    }
+    runs
+}
+
+/// The glyphs of a run with a single direction in visual order
+fn shape(
+    fonts: &[Font],
+    text: &str,
+    range: Range<usize>,
+    rtl: bool,
+    size: f32,
+) -> Vec<ShapedGlyph> {
+    let mut runs = font_runs(fonts, text, range);
+    if rtl {
+        runs.reverse();
+    }
+
+    let mut shaped = Vec::new();
+    for (index, run) in runs {
+        let font = &fonts[index];
+
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(&text[run.clone()]);
+        buffer.set_direction(if rtl {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        });
+        let output = rustybuzz::shape(font.face(), &[], buffer);
+
+        let px = font.px(size);
+        for (info, position) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+            shaped.push(ShapedGlyph {
+                font: index,
+                glyph: GlyphId(info.glyph_id as u16),
+                advance: position.x_advance as f32 * px,
+                offset: [position.x_offset as f32 * px, position.y_offset as f32 * px],
+                cluster: run.start + info.cluster as usize,
+            });
+        }
+    }
+    shaped
+}
@@ -0,0 +1,192 @@
+use crate::{
+    color::Color,
+    gcx::{
+        buffer::{BufferType, BufferUsage},
+        shader::Shader,
+        vertex_array::{Field, Fields, VertexArray},
+        GCXShaded, PrimitiveType, GCX,
+    },
+};
+
+use super::atlas::AtlasGlyph;
+
+/// Two triangles for every quad of four vertices that go around it
+fn quad_indices(quads: usize) -> Vec<u32> {
+    (0..quads as u32)
+        .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|i| quad * 4 + i))
+        .collect()
+}
+
+/// A corner of a quad of the text and the code nodes
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GlyphVertex {
+    /// In pixels relative to the position
+    position: [f32; 2],
+    /// In texels of the atlas
+    uv: [f32; 2],
+    /// Multiplied with the `Color` uniform
+    color: Color,
+    /// `1` for a quad that does not sample the atlas
+    solid: f32,
+}
+
+impl Fields for GlyphVertex {
+    fn fields() -> Vec<Field> {
+        vec![
+            Field::new::<[f32; 2]>("position"),
+            Field::new::<[f32; 2]>("uv"),
+            Field::new::<Color>("color"),
+            Field::new::<f32>("solid"),
+        ]
+    }
+}
+
+impl GlyphVertex {
+    /// The corners of the glyph with the pen at `pen`, it is scaled by `scale` around its center
+    pub(super) fn glyph(glyph: AtlasGlyph, pen: [f32; 2], scale: f32, color: Color) -> [Self; 4] {
+        let corner = |i: usize, max: bool| {
+            let center = (glyph.min[i] + glyph.max[i]) * 0.5;
+            let edge = if max { glyph.max[i] } else { glyph.min[i] };
+            pen[i] + center + (edge - center) * scale
+        };
+        let pick = |max: bool, i: usize| {
+            if max {
+                glyph.uv_max[i]
+            } else {
+                glyph.uv_min[i]
+            }
+        };
+
+        [[false, false], [true, false], [true, true], [false, true]].map(|[right, top]| Self {
+            position: [corner(0, right), corner(1, top)],
+            uv: [pick(right, 0), pick(top, 1)],
+            color,
+            solid: 0.,
+        })
+    }
+
+    /// The corners of a rectangle that is filled with `color`
+    pub(super) fn solid(min: [f32; 2], max: [f32; 2], color: Color) -> [Self; 4] {
+        [
+            [min[0], min[1]],
+            [max[0], min[1]],
+            [max[0], max[1]],
+            [min[0], max[1]],
+        ]
+        .map(|position| Self {
+            position,
+            uv: [0.; 2],
+            color,
+            solid: 1.,
+        })
+    }
+}
+
+/// The quads of a text or a code node, the uvs point in to the shared atlas
+pub(super) struct GlyphMesh {
+    va: VertexArray,
+    count: i32,
+    /// The atlas generation that the mesh was built with
+    pub generation: usize,
+}
+
+impl GlyphMesh {
+    pub fn new(gcx: &GCX, generation: usize) -> Self {
+        let buffer = gcx.create_buffer::<GlyphVertex>(
+            BufferType::ArrayBuffer,
+            &[],
+            BufferUsage::DRAW_DYNAMIC,
+        );
+        let index_buffer = gcx.create_buffer::<u32>(
+            BufferType::ElementArrayBuffer,
+            &[],
+            BufferUsage::DRAW_DYNAMIC,
+        );
+        let va = gcx
+            .create_vertex_array::<GlyphVertex>(buffer)
+            .add_index_buffer::<u32>(index_buffer)
+            .build(gcx);
+        Self {
+            va,
+            count: 0,
+            generation,
+        }
+    }
+
+    pub fn set(&mut self, vertices: &[GlyphVertex], generation: usize) {
+        self.va
+            .array_buffer
+            .set_data(vertices, BufferUsage::DRAW_DYNAMIC);
+        let indices = quad_indices(vertices.len() / 4);
+        self.va.set_indices(&indices, BufferUsage::DRAW_DYNAMIC);
+        self.count = indices.len() as i32;
+        self.generation = generation;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn draw(&self, gcx: &GCXShaded) {
+        gcx.use_vertex_array(&self.va, |gcx| {
+            gcx.draw_elements(PrimitiveType::Triangles, self.count);
+        });
+    }
+}
+
+/// Draws the `GlyphMesh`, the color of the vertices is multiplied with the `Color` uniform
+pub(super) fn glyph_shader(gcx: &GCX) -> Shader {
+    gcx.create_shader()
+        .vertex(
+            r#"
+                #version 320 es
+
+                precision highp float;
+
+                layout(location = 0) in vec2 pos;
+                layout(location = 1) in vec2 uv;
+                layout(location = 2) in vec4 color;
+                layout(location = 3) in float solid;
+
+                uniform vec2 Position;
+                uniform mat3 World;
+                uniform float WorldUp;
+
+                out vec2 UV;
+                out vec4 VertexColor;
+                out float Solid;
+
+                void main(){
+                    gl_Position = vec4((World * vec3(Position + pos * vec2(1.0, WorldUp), 1.0)).xy, 0.0, 1.0);
+                    UV = uv;
+                    VertexColor = color;
+                    Solid = solid;
+                }
+            "#,
+        )
+        .fragment(
+            r#"
+                #version 320 es
+
+                precision highp float;
+
+                // the uvs are in texels of the atlas, it can grow
+                uniform sampler2D ATLAS;
+                uniform vec4 Color;
+                uniform float WorldOpacity;
+
+                in vec2 UV;
+                in vec4 VertexColor;
+                in float Solid;
+                out vec4 color;
+                void main(){
+                    vec4 tint = VertexColor * Color;
+                    float alpha = mix(texture(ATLAS, UV / vec2(textureSize(ATLAS, 0))).r, 1.0, Solid) * tint.a * WorldOpacity;
+                    color = vec4(tint.rgb * alpha, alpha);
+                }
+                "#,
+        )
+        .build(gcx)
+        .unwrap()
+}
@@ -0,0 +1,626 @@
+pub mod animation;
+mod atlas;
+pub mod code;
+pub mod font;
+pub mod highlight;
+mod layout;
+mod mesh;
+
+use crate::{
+    color::Color,
+    gcx::{blend::BlendMode, shader::Shader, GCX},
+    group::{GroupId, SceneGraph},
+    mask::{Mask, MaskId},
+    node::{offset_bounds, union, Bounds, DrawItem, NodeBounds, NodeBuilder, NodeManager},
+    scene::SceneTask,
+    shadow::{Glow, Shadow},
+    signal::{create_signal, Executor, NSignal, RawSignal, Signal},
+};
+
+use self::{
+    animation::GlyphAnimation,
+    atlas::{GlyphAtlas, SharedAtlas},
+    font::Font,
+    layout::{layout, PlacedGlyph},
+    mesh::{glyph_shader, GlyphMesh, GlyphVertex},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug)]
+pub struct TextBuilder {
+    pub(super) text: String,
+    /// The first font that has a glyph for a char is used
+    pub(super) fonts: Vec<Font>,
+    pub(super) font_size: f32,
+    pub(super) color: Color,
+    pub(super) position: [f32; 2],
+    pub(super) alignment: TextAlign,
+    pub(super) line_height: f32,
+    pub(super) max_width: Option<f32>,
+    pub(super) progress: f32,
+    pub(super) glyph_animation: GlyphAnimation,
+    pub(super) blend: BlendMode,
+    pub(super) z_index: i32,
+    pub(super) shadow: Shadow,
+    pub(super) mask: Option<Mask>,
+    pub(super) mask_source: Option<MaskId>,
+}
+
+impl TextBuilder {
+    /// `font_size` is the size of one em in pixels
+    pub fn new(text: impl Into<String>, font: Font, font_size: f32) -> Self {
+        Self {
+            text: text.into(),
+            fonts: vec![font],
+            font_size,
+            color: Color::WHITE,
+            position: [0.; 2],
+            alignment: TextAlign::Left,
+            line_height: 1.,
+            max_width: None,
+            progress: 1.,
+            glyph_animation: GlyphAnimation::default(),
+            blend: BlendMode::Normal,
+            z_index: 0,
+            shadow: Shadow::default(),
+            mask: None,
+            mask_source: None,
+        }
+    }
+
+    /// Adds a font to the end of the fallback chain, for the chars that the previous fonts do not have
+    pub fn with_fallback(mut self, font: Font) -> Self {
+        self.fonts.push(font);
+        self
+    }
+
+    pub fn with_color(mut self, color: impl Into<Color>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    /// The anchor of the text, the lines are aligned around it and the block is centered vertically on it
+    pub fn with_position(mut self, position: [f32; 2]) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: TextAlign) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Multiplier of the line advance from the font
+    pub fn with_line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    /// Wraps the lines that are wider than `max_width` pixels
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// From `0` where every glyph is in the start state of the `GlyphAnimation` to `1` where the text is fully shown
+    pub fn with_progress(mut self, progress: f32) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    pub fn with_glyph_animation(mut self, glyph_animation: GlyphAnimation) -> Self {
+        self.glyph_animation = glyph_animation;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    /// A shadow behind the node, `offset` and `blur` are in pixels of the screen
+    pub fn with_shadow(mut self, color: impl Into<Color>, offset: [f32; 2], blur: f32) -> Self {
+        self.shadow.color = color.into();
+        self.shadow.offset = offset;
+        self.shadow.blur = blur;
+        self
+    }
+
+    /// A glow around the node, `radius` is in pixels of the screen
+    pub fn with_glow(mut self, color: impl Into<Color>, radius: f32) -> Self {
+        self.shadow.glow = Glow {
+            color: color.into(),
+            radius,
+        };
+        self
+    }
+
+    /// Only the parts inside of the mask are drawn, this replaces the mask of the group
+    pub fn with_mask(mut self, mask: Mask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The node is not drawn, it is the shape of the mask with the `id`
+    pub fn into_mask(mut self, id: MaskId) -> Self {
+        self.mask_source = Some(id);
+        self
+    }
+}
+
+pub struct Text<'a> {
+    scene: &'a SceneTask,
+    parent: GroupId,
+    /// For the layout of the bounds
+    fonts: Vec<Font>,
+
+    pub text: Signal<'a, String>,
+    pub font_size: Signal<'a, f32>,
+    pub color: Signal<'a, Color>,
+    pub position: Signal<'a, [f32; 2]>,
+    pub alignment: Signal<'a, TextAlign>,
+    pub line_height: Signal<'a, f32>,
+    pub max_width: Signal<'a, Option<f32>>,
+    pub progress: Signal<'a, f32>,
+    pub glyph_animation: Signal<'a, GlyphAnimation>,
+    pub blend: Signal<'a, BlendMode>,
+    pub z_index: Signal<'a, i32>,
+    pub shadow_color: Signal<'a, Color>,
+    pub shadow_offset: Signal<'a, [f32; 2]>,
+    pub shadow_blur: Signal<'a, f32>,
+    pub glow: Signal<'a, Glow>,
+
+    drop: Signal<'a, ()>,
+    dropped: bool,
+}
+
+impl<'a> Text<'a> {
+    pub async fn drop(mut self) {
+        self.drop.set(()).await;
+        self.scene.update().await;
+        self.dropped = true;
+    }
+
+    /// Tweens `progress` from `0` to `1`, with `GlyphAnimation::typewriter` this types the text
+    pub fn reveal(&mut self, time: f64) -> Executor<'_> {
+        self.progress.tween(0., 1., time)
+    }
+
+    /// Tweens the text to `to` by deleting the chars that are not in `to` and typing the new ones
+    pub fn retype(&mut self, to: impl Into<String>, time: f64) -> Executor<'_> {
+        let from = self.text.value().clone();
+        self.text.tween(from, to.into(), time)
+    }
+}
+
+impl<'a> Bounds for Text<'a> {
+    fn bounds(&self) -> NodeBounds {
+        let size = *self.font_size.value();
+        let placed = layout(
+            &self.fonts,
+            self.text.value(),
+            size,
+            *self.alignment.value(),
+            *self.line_height.value(),
+            *self.max_width.value(),
+        );
+        let up = self.scene.info.try_read().unwrap().origin.up();
+        NodeBounds::Local(
+            self.parent,
+            offset_bounds(
+                *self.position.value(),
+                placed_bounds(&self.fonts, &placed, size),
+                up,
+            ),
+        )
+    }
+}
+
+/// The min and the max corner relative to the position before the y is multiplied by `WorldUp`
+/// every glyph goes from its pen to its advance and from the descent to the ascent
+fn placed_bounds(fonts: &[Font], placed: &[PlacedGlyph], size: f32) -> [[f32; 2]; 2] {
+    let Some(font) = fonts.first() else {
+        return [[0.; 2]; 2];
+    };
+    let (ascent, descent, _) = font.metrics(size);
+    placed
+        .iter()
+        .map(|glyph| {
+            let [x, y] = glyph.pen;
+            let advance = fonts[glyph.font].advance(size, glyph.glyph);
+            [[x, y + descent], [x + advance, y + ascent]]
+        })
+        .reduce(union)
+        .unwrap_or([[0.; 2]; 2])
+}
+
+impl<'a> Drop for Text<'a> {
+    fn drop(&mut self) {
+        if self.dropped {
+            return;
+        }
+
+        eprintln!("You need to call drop on Text when you are done with it!");
+        std::process::abort();
+    }
+}
+
+impl NodeBuilder for TextBuilder {
+    type Node<'a> = Text<'a>;
+    type NodeManager = TextNodeManager;
+
+    fn create_node_ref<'a>(
+        &self,
+        raw: RawText,
+        parent: GroupId,
+        scene: &'a SceneTask,
+    ) -> Self::Node<'a> {
+        Text {
+            scene,
+            parent,
+            fonts: self.fonts.clone(),
+            dropped: false,
+            text: Signal::new(raw.text, scene, self.text.clone()),
+            font_size: Signal::new(raw.font_size, scene, self.font_size),
+            color: Signal::new(raw.color, scene, self.color),
+            position: Signal::new(raw.position, scene, self.position),
+            alignment: Signal::new(raw.alignment, scene, self.alignment),
+            line_height: Signal::new(raw.line_height, scene, self.line_height),
+            max_width: Signal::new(raw.max_width, scene, self.max_width),
+            progress: Signal::new(raw.progress, scene, self.progress),
+            glyph_animation: Signal::new(raw.glyph_animation, scene, self.glyph_animation),
+            blend: Signal::new(raw.blend, scene, self.blend),
+            z_index: Signal::new(raw.z_index, scene, self.z_index),
+            shadow_color: Signal::new(raw.shadow_color, scene, self.shadow.color),
+            shadow_offset: Signal::new(raw.shadow_offset, scene, self.shadow.offset),
+            shadow_blur: Signal::new(raw.shadow_blur, scene, self.shadow.blur),
+            glow: Signal::new(raw.glow, scene, self.shadow.glow),
+            drop: Signal::new(raw.drop, scene, ()),
+        }
+    }
+}
+
+pub struct NText {
+    mesh: GlyphMesh,
+    /// The text needs to be shaped again
+    relayout: bool,
+    /// The glyphs need to be placed again
+    rebuild: bool,
+    placed: Vec<PlacedGlyph>,
+    /// The index of every placed glyph in the order of the text
+    order: Vec<usize>,
+    parent: GroupId,
+    builder: TextBuilder,
+    inner: NTextInner,
+}
+
+pub struct NTextInner {
+    drop: NSignal<()>,
+    text: NSignal<String>,
+    font_size: NSignal<f32>,
+    color: NSignal<Color>,
+    position: NSignal<[f32; 2]>,
+    alignment: NSignal<TextAlign>,
+    line_height: NSignal<f32>,
+    max_width: NSignal<Option<f32>>,
+    progress: NSignal<f32>,
+    glyph_animation: NSignal<GlyphAnimation>,
+    blend: NSignal<BlendMode>,
+    z_index: NSignal<i32>,
+    shadow_color: NSignal<Color>,
+    shadow_offset: NSignal<[f32; 2]>,
+    shadow_blur: NSignal<f32>,
+    glow: NSignal<Glow>,
+}
+
+pub struct RawText {
+    drop: RawSignal<()>,
+    text: RawSignal<String>,
+    font_size: RawSignal<f32>,
+    color: RawSignal<Color>,
+    position: RawSignal<[f32; 2]>,
+    alignment: RawSignal<TextAlign>,
+    line_height: RawSignal<f32>,
+    max_width: RawSignal<Option<f32>>,
+    progress: RawSignal<f32>,
+    glyph_animation: RawSignal<GlyphAnimation>,
+    blend: RawSignal<BlendMode>,
+    z_index: RawSignal<i32>,
+    shadow_color: RawSignal<Color>,
+    shadow_offset: RawSignal<[f32; 2]>,
+    shadow_blur: RawSignal<f32>,
+    glow: RawSignal<Glow>,
+}
+
+#[derive(Default)]
+pub struct TextNodeManager {
+    pub(super) texts: Vec<NText>,
+    pub(super) shader: Option<Shader>,
+    atlas: SharedAtlas,
+
+    pending: Option<NTextInner>,
+}
+
+impl NodeManager for TextNodeManager {
+    type NodeBuilder = TextBuilder;
+    type RawNode = RawText;
+
+    fn init(&mut self, gcx: &GCX) {
+        self.shader.replace(glyph_shader(gcx));
+    }
+
+    fn init_node(&mut self, gcx: &GCX, builder: Self::NodeBuilder, parent: GroupId) {
+        self.texts.push(NText {
+            mesh: GlyphMesh::new(gcx, self.atlas.borrow_mut().generation()),
+            relayout: true,
+            rebuild: true,
+            placed: Vec::new(),
+            order: Vec::new(),
+            parent,
+            builder,
+            inner: self.pending.take().unwrap(),
+        });
+    }
+
+    fn draw_items(&self) -> Vec<DrawItem> {
+        self.texts
+            .iter()
+            .map(|text| DrawItem {
+                z_index: text.builder.z_index,
+                parent: text.parent,
+                mask: text.builder.mask,
+                mask_source: text.builder.mask_source,
+                shadow: Some(text.builder.shadow).filter(Shadow::is_visible),
+            })
+            .collect()
+    }
+
+    /// The texts that are not placed yet are placed for this
+    fn bounds(&self, graph: &SceneGraph) -> Vec<[[f32; 2]; 2]> {
+        self.texts
+            .iter()
+            .map(|text| {
+                let builder = &text.builder;
+                let placed = if text.relayout {
+                    &layout(
+                        &builder.fonts,
+                        &builder.text,
+                        builder.font_size,
+                        builder.alignment,
+                        builder.line_height,
+                        builder.max_width,
+                    )
+                } else {
+                    &text.placed
+                };
+                offset_bounds(
+                    builder.position,
+                    placed_bounds(&builder.fonts, placed, builder.font_size),
+                    graph.world(text.parent).up,
+                )
+            })
+            .collect()
+    }
+
+    fn render(&mut self, gcx: &GCX, graph: &SceneGraph, items: &[usize]) {
+        let Some(shader) = &self.shader else { panic!() };
+        let mut atlas = self.atlas.borrow_mut();
+        atlas.begin();
+        for &i in items {
+            self.texts[i].rebuild(&mut atlas);
+        }
+
+        let texture = atlas.texture(gcx);
+        gcx.use_shader(shader, |gcx| {
+            texture.activate(0);
+            let _ = shader.set_uniform("ATLAS", 0);
+
+            for text in items.iter().map(|&i| &self.texts[i]) {
+                if text.mesh.is_empty() {
+                    continue;
+                }
+
+                let [x, y] = text.builder.position;
+                let color = text.builder.color;
+                let _ = shader.set_uniform("Position", (x, y));
+                let _ = shader.set_uniform("Color", (color.r, color.g, color.b, color.a));
+                graph.world(text.parent).set_uniforms(shader);
+                let _blend = gcx.blend(text.builder.blend);
+                text.mesh.draw(&gcx);
+            }
+        });
+    }
+
+    fn create_node(&mut self) -> RawText {
+        let (ntext, text) = create_signal();
+        let (nfont_size, font_size) = create_signal();
+        let (ncolor, color) = create_signal();
+        let (nposition, position) = create_signal();
+        let (nalignment, alignment) = create_signal();
+        let (nline_height, line_height) = create_signal();
+        let (nmax_width, max_width) = create_signal();
+        let (nprogress, progress) = create_signal();
+        let (nglyph_animation, glyph_animation) = create_signal();
+        let (nblend, blend) = create_signal();
+        let (nz_index, z_index) = create_signal();
+        let (nshadow_color, shadow_color) = create_signal();
+        let (nshadow_offset, shadow_offset) = create_signal();
+        let (nshadow_blur, shadow_blur) = create_signal();
+        let (nglow, glow) = create_signal();
+        let (ndrop, drop) = create_signal();
+
+        self.pending = Some(NTextInner {
+            drop,
+            text,
+            font_size,
+            color,
+            position,
+            alignment,
+            line_height,
+            max_width,
+            progress,
+            glyph_animation,
+            blend,
+            z_index,
+            shadow_color,
+            shadow_offset,
+            shadow_blur,
+            glow,
+        });
+
+        RawText {
+            drop: ndrop,
+            text: ntext,
+            font_size: nfont_size,
+            color: ncolor,
+            position: nposition,
+            alignment: nalignment,
+            line_height: nline_height,
+            max_width: nmax_width,
+            progress: nprogress,
+            glyph_animation: nglyph_animation,
+            blend: nblend,
+            z_index: nz_index,
+            shadow_color: nshadow_color,
+            shadow_offset: nshadow_offset,
+            shadow_blur: nshadow_blur,
+            glow: nglow,
+        }
+    }
+
+    fn update(&mut self) {
+        self.texts.retain_mut(|text| {
+            if let Some(string) = text.inner.text.get() {
+                text.builder.text = string;
+                text.relayout = true;
+            }
+            if let Some(font_size) = text.inner.font_size.get() {
+                text.builder.font_size = font_size;
+                text.relayout = true;
+            }
+            if let Some(alignment) = text.inner.alignment.get() {
+                text.builder.alignment = alignment;
+                text.relayout = true;
+            }
+            if let Some(line_height) = text.inner.line_height.get() {
+                text.builder.line_height = line_height;
+                text.relayout = true;
+            }
+            if let Some(max_width) = text.inner.max_width.get() {
+                text.builder.max_width = max_width;
+                text.relayout = true;
+            }
+            if let Some(progress) = text.inner.progress.get() {
+                text.builder.progress = progress;
+                text.rebuild = true;
+            }
+            if let Some(glyph_animation) = text.inner.glyph_animation.get() {
+                text.builder.glyph_animation = glyph_animation;
+                text.rebuild = true;
+            }
+            if let Some(color) = text.inner.color.get() {
+                text.builder.color = color;
+            }
+            if let Some(position) = text.inner.position.get() {
+                text.builder.position = position;
+            }
+            if let Some(blend) = text.inner.blend.get() {
+                text.builder.blend = blend;
+            }
+            if let Some(z_index) = text.inner.z_index.get() {
+                text.builder.z_index = z_index;
+            }
+            if let Some(color) = text.inner.shadow_color.get() {
+                text.builder.shadow.color = color;
+            }
+            if let Some(offset) = text.inner.shadow_offset.get() {
+                text.builder.shadow.offset = offset;
+            }
+            if let Some(blur) = text.inner.shadow_blur.get() {
+                text.builder.shadow.blur = blur;
+            }
+            if let Some(glow) = text.inner.glow.get() {
+                text.builder.shadow.glow = glow;
+            }
+
+            text.inner.drop.get().is_none()
+        });
+    }
+}
+
+impl NText {
+    fn layout(&mut self) {
+        let builder = &self.builder;
+        self.placed = layout(
+            &builder.fonts,
+            &builder.text,
+            builder.font_size,
+            builder.alignment,
+            builder.line_height,
+            builder.max_width,
+        );
+
+        let mut by_cluster = (0..self.placed.len()).collect::<Vec<_>>();
+        by_cluster.sort_by_key(|i| self.placed[*i].cluster);
+        self.order = vec![0; self.placed.len()];
+        for (order, i) in by_cluster.into_iter().enumerate() {
+            self.order[i] = order;
+        }
+    }
+
+    /// Builds the mesh if it changed or if the atlas was cleared since it was built
+    fn rebuild(&mut self, atlas: &mut GlyphAtlas) {
+        if self.relayout {
+            self.layout();
+            self.relayout = false;
+            self.rebuild = true;
+        }
+        if !self.rebuild && self.mesh.generation == atlas.generation() {
+            return;
+        }
+
+        let mesh = self.build_mesh(atlas);
+        self.mesh.set(&mesh, atlas.generation());
+        self.rebuild = false;
+    }
+
+    fn build_mesh(&self, atlas: &mut GlyphAtlas) -> Vec<GlyphVertex> {
+        let builder = &self.builder;
+        let animation = builder.glyph_animation;
+
+        let mut mesh = Vec::new();
+        for (placed, order) in self.placed.iter().zip(&self.order) {
+            let progress = animation.glyph_progress(builder.progress, *order, self.placed.len());
+            let (offset, alpha, scale) = animation.at(progress);
+            if alpha <= 0. || scale <= 0. {
+                continue;
+            }
+
+            let font = &builder.fonts[placed.font];
+            let Some(glyph) = atlas.glyph(font, placed.glyph, builder.font_size) else {
+                continue;
+            };
+
+            let pen = [placed.pen[0] + offset[0], placed.pen[1] + offset[1]];
+            mesh.extend(GlyphVertex::glyph(
+                glyph,
+                pen,
+                scale,
+                Color::new(1., 1., 1., alpha),
+            ));
+        }
+        mesh
+    }
+}